@@ -0,0 +1,108 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, Ident, LitStr, parse_macro_input};
+
+/// Generates a `MaterialProperty` constant for each field of a struct, an implementation of `MaterialProperties` that registers them,
+/// and an extension trait named `<Struct>Ext` implemented for `GenericMaterial` with a typed getter for each field.
+///
+/// The property key defaults to the field's name, and can be overridden with `#[property(key = "...")]`.
+///
+/// # Examples
+/// ```ignore
+/// #[derive(MaterialProperties)]
+/// pub struct MyProperties {
+///     pub collision: bool,
+///     #[property(key = "footstep_sounds")]
+///     pub sounds: String,
+/// }
+///
+/// App::new().register_material_properties::<MyProperties>();
+///
+/// fn read(material: &GenericMaterial) {
+///     let _ = MyProperties::COLLISION;
+///     let _: Result<&String, GetPropertyError> = material.sounds();
+/// }
+/// ```
+#[proc_macro_derive(MaterialProperties, attributes(property))]
+pub fn derive_material_properties(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+
+	match material_properties(input) {
+		Ok(tokens) => tokens.into(),
+		Err(err) => err.into_compile_error().into(),
+	}
+}
+
+fn material_properties(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+	let Data::Struct(data) = &input.data else {
+		return Err(syn::Error::new_spanned(
+			&input.ident,
+			"MaterialProperties can only be derived for structs",
+		));
+	};
+	let Fields::Named(fields) = &data.fields else {
+		return Err(syn::Error::new_spanned(&input.ident, "MaterialProperties requires named fields"));
+	};
+
+	let ident = &input.ident;
+	let vis = &input.vis;
+	let ext_ident = format_ident!("{ident}Ext");
+
+	let mut consts = Vec::new();
+	let mut registrations = Vec::new();
+	let mut getter_signatures = Vec::new();
+	let mut getters = Vec::new();
+
+	for field in &fields.named {
+		let field_ident = field.ident.as_ref().unwrap();
+		let ty = &field.ty;
+		let const_ident = Ident::new(&field_ident.to_string().to_uppercase(), Span::call_site());
+
+		let mut key = LitStr::new(&field_ident.to_string(), field_ident.span());
+		for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("property")) {
+			attr.parse_nested_meta(|meta| {
+				if meta.path.is_ident("key") {
+					key = meta.value()?.parse()?;
+					Ok(())
+				} else {
+					Err(meta.error("unknown property attribute, expected `key`"))
+				}
+			})?;
+		}
+
+		consts.push(quote! {
+			#vis const #const_ident: ::bevy_materialize::material_property::MaterialProperty<#ty> = ::bevy_materialize::material_property::MaterialProperty::new(#key);
+		});
+		registrations.push(quote! {
+			::bevy_materialize::material_property::MaterialPropertyAppExt::register_material_property(app, Self::#const_ident);
+		});
+		getter_signatures.push(quote! {
+			fn #field_ident(&self) -> ::core::result::Result<&#ty, ::bevy_materialize::material_property::GetPropertyError>;
+		});
+		getters.push(quote! {
+			fn #field_ident(&self) -> ::core::result::Result<&#ty, ::bevy_materialize::material_property::GetPropertyError> {
+				self.get_property(#ident::#const_ident)
+			}
+		});
+	}
+
+	Ok(quote! {
+		impl #ident {
+			#(#consts)*
+		}
+
+		impl ::bevy_materialize::material_property::MaterialProperties for #ident {
+			fn register(app: &mut ::bevy::app::App) {
+				#(#registrations)*
+			}
+		}
+
+		#vis trait #ext_ident {
+			#(#getter_signatures)*
+		}
+		impl #ext_ident for ::bevy_materialize::generic_material::GenericMaterial {
+			#(#getters)*
+		}
+	})
+}
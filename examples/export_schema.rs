@@ -0,0 +1,21 @@
+//! Prints a JSON description of the registered materials and properties, for external editors to build their forms from.
+//!
+//! Run with `cargo run --example export_schema --features json > schema.json`.
+
+use bevy::prelude::*;
+use bevy_materialize::{load::create_material_test_app, prelude::*, schema::MaterializeSchema};
+
+pub trait MyMaterialProperties {
+	const COLLISION: MaterialProperty<bool> = MaterialProperty::new("collision");
+	const SOUNDS: MaterialProperty<String> = MaterialProperty::new("sounds");
+}
+impl MyMaterialProperties for GenericMaterial {}
+
+fn main() {
+	let mut app = create_material_test_app(TomlMaterialDeserializer);
+	app.register_material_property(GenericMaterial::COLLISION)
+		.register_material_property(GenericMaterial::SOUNDS);
+
+	let schema = MaterializeSchema::from_world(app.world());
+	println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+}
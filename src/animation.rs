@@ -1,10 +1,19 @@
 use std::time::Duration;
 
+#[cfg(feature = "bevy_pbr")]
+use std::any::TypeId;
+
+#[cfg(feature = "bevy_pbr")]
+use bevy::reflect::{TypeInfo, TypeRegistry};
 use bevy::{
+	asset::LoadState,
 	platform::collections::{HashMap, HashSet},
 	prelude::*,
 };
+use thiserror::Error;
 
+#[cfg(feature = "bevy_pbr")]
+use crate::{erased_material::ErasedMaterialHandle, lerp::lerp_reflect};
 use crate::{
 	material_property::{GetPropertyError, MaterialPropertyAppExt},
 	prelude::*,
@@ -38,16 +47,28 @@ impl AnimationPlugin {
 	pub fn setup_animated_materials(
 		mut animated_materials: ResMut<AnimatedGenericMaterials>,
 		generic_materials: Res<Assets<GenericMaterial>>,
+		asset_server: Res<AssetServer>,
 		time: Res<Time>,
+		#[cfg(feature = "bevy_pbr")] type_registry: Res<AppTypeRegistry>,
 
 		mut asset_events: MessageReader<AssetEvent<GenericMaterial>>,
 		mut failed_reading: Local<HashSet<AssetId<GenericMaterial>>>,
+		// Animations of modified materials, kept until they're set up again, which may take several frames if their next materials are loading.
+		mut reloaded: Local<HashMap<AssetId<GenericMaterial>, MaterialAnimations>>,
 	) {
 		for event in asset_events.read() {
-			let AssetEvent::Modified { id } = event else { continue };
-
-			failed_reading.remove(id);
-			animated_materials.states.remove(id);
+			match event {
+				AssetEvent::Modified { id } => {
+					failed_reading.remove(id);
+					if let Some(animations) = animated_materials.states.remove(id) {
+						reloaded.insert(*id, animations);
+					}
+				}
+				AssetEvent::Removed { id } => {
+					reloaded.remove(id);
+				}
+				_ => {}
+			}
 		}
 
 		for (id, generic_material) in generic_materials.iter() {
@@ -56,6 +77,8 @@ impl AnimationPlugin {
 				continue;
 			}
 
+			let previous = reloaded.remove(&id);
+
 			let mut animations = match generic_material.get_property(GenericMaterial::ANIMATION).cloned() {
 				Ok(x) => x,
 				Err(GetPropertyError::NotFound) => continue,
@@ -66,6 +89,47 @@ impl AnimationPlugin {
 				}
 			};
 
+			// Validate once here rather than failing every frame while animating.
+			#[cfg(feature = "bevy_pbr")]
+			if let Some(images) = &animations.images
+				&& let Err(err) = images.validate(&type_registry.read(), generic_material.handle.inner().type_id())
+			{
+				error!("Invalid image animation in GenericMaterial: {err}");
+				failed_reading.insert(id);
+				continue;
+			}
+
+			// Wait for the whole chain of next materials to load, so a missing one is reported here rather than when switching to it.
+			if let Some(next) = &animations.next {
+				match next.check_chain(&generic_materials, &asset_server) {
+					Ok(NextChain::Ends) => {}
+					Ok(NextChain::Loops(handle)) => {
+						let path = handle.path().map(ToString::to_string).unwrap_or_else(|| format!("{handle:?}"));
+						warn!("Next animation chain of GenericMaterial loops back to {path}, so it switches between materials forever");
+					}
+					Ok(NextChain::Loading) => {
+						// Not set up yet, so keep the previous state for when it is.
+						if let Some(previous) = previous {
+							reloaded.insert(id, previous);
+						}
+						continue;
+					}
+					Err(err) => {
+						error!("Invalid next animation in GenericMaterial: {err}");
+						failed_reading.insert(id);
+						continue;
+					}
+				}
+			}
+
+			// If the animation didn't change, keep playing from where it was rather than restarting.
+			if let Some(previous) = previous
+				&& previous.reflect_partial_eq(&animations) == Some(true)
+			{
+				animated_materials.states.insert(id, previous);
+				continue;
+			}
+
 			// Make next not switch instantly, slightly hacky.
 			if let Some(animation) = &mut animations.next {
 				animation.state.next_frame_time = animation.new_next_frame_time(time.elapsed());
@@ -85,29 +149,90 @@ impl AnimationPlugin {
 		query: Query<(Entity, &GenericMaterial3d)>,
 	) {
 		let now = time.elapsed();
+		let AnimatedGenericMaterials { states, scheduled_frames } = &mut *animated_materials;
 
-		for (id, animations) in &mut animated_materials.states {
+		for (id, animations) in states.iter_mut() {
 			// Material switching
 			if let Some(animation) = &mut animations.next
 				&& animation.state.next_frame_time <= now
 			{
 				animation.advance_frame(now);
 
-				for (entity, generic_material_3d) in &query {
-					if generic_material_3d.id() != *id {
-						continue;
+				#[cfg(feature = "bevy_pbr")]
+				if let Some(seconds) = animation.transition_seconds.filter(|seconds| *seconds > 0.) {
+					animation.transition = Some(NextTransition {
+						start: now,
+						duration: Duration::from_secs_f32(seconds),
+					});
+				}
+
+				#[cfg(feature = "bevy_pbr")]
+				let switch = animation.transition.is_none();
+				#[cfg(not(feature = "bevy_pbr"))]
+				let switch = true;
+
+				if switch {
+					for (entity, generic_material_3d) in &query {
+						if generic_material_3d.id() != *id {
+							continue;
+						}
+
+						commands.entity(entity).insert(GenericMaterial3d(animation.material.clone()));
+					}
+				}
+			}
+
+			// Cross-fading into the next material
+			#[cfg(feature = "bevy_pbr")]
+			if let Some(animation) = &mut animations.next
+				&& let Some(transition) = &animation.transition
+			{
+				let t = (now.saturating_sub(transition.start).as_secs_f32() / transition.duration.as_secs_f32()).min(1.);
+
+				let entities: Vec<Entity> = query
+					.iter()
+					.filter(|(_, generic_material_3d)| generic_material_3d.id() == *id)
+					.map(|(entity, _)| entity)
+					.collect();
+
+				if t >= 1. {
+					animation.transition = None;
+
+					for entity in entities {
+						commands.entity(entity).insert(GenericMaterial3d(animation.material.clone()));
 					}
 
-					commands.entity(entity).insert(GenericMaterial3d(animation.material.clone()));
+					let id = *id;
+					commands.queue(move |world: &mut World| {
+						if let Some(mut blends) = world.get_resource_mut::<NextTransitionBlends>() {
+							blends.0.remove(&id);
+						}
+					});
+				} else {
+					let (id, target) = (*id, animation.material.id());
+					commands.queue(move |world: &mut World| blend_next_transition(world, id, target, t, entities));
+				}
+			}
+
+			// Externally scheduled frames that are due, if several are, the one scheduled latest wins.
+			#[cfg(feature = "bevy_pbr")]
+			let mut scheduled_frame: Option<&ScheduledFrame> = None;
+			#[cfg(feature = "bevy_pbr")]
+			for frame in scheduled_frames.iter().filter(|frame| frame.id == *id && frame.at <= now) {
+				if scheduled_frame.is_none_or(|scheduled| frame.at >= scheduled.at) {
+					scheduled_frame = Some(frame);
 				}
 			}
 
 			// Image switching
 			#[cfg(feature = "bevy_pbr")]
 			if let Some(animation) = &mut animations.images
-				&& animation.state.next_frame_time <= now
+				&& (scheduled_frame.is_some() || animation.state.next_frame_time <= now)
 			{
-				animation.advance_frame(now);
+				match scheduled_frame {
+					Some(scheduled) => animation.set_frame(scheduled.frame, now),
+					None => animation.advance_frame(now),
+				}
 				let Some(generic_material) = generic_materials.get(*id) else { continue };
 
 				for (field_name, frames) in &animation.fields {
@@ -123,6 +248,9 @@ impl AnimationPlugin {
 				}
 			}
 		}
+
+		// Due frames have been applied, or their material isn't animated.
+		scheduled_frames.retain(|frame| frame.at > now);
 	}
 }
 
@@ -130,6 +258,25 @@ impl AnimationPlugin {
 #[derive(Resource, Reflect, Default)]
 pub struct AnimatedGenericMaterials {
 	pub states: HashMap<AssetId<GenericMaterial>, MaterialAnimations>,
+	/// Image animation frames scheduled with [`set_frame_at`](Self::set_frame_at), applied in [`AnimationPlugin::animate_materials`].
+	pub scheduled_frames: Vec<ScheduledFrame>,
+}
+impl AnimatedGenericMaterials {
+	/// Schedules the [`ImagesAnimation`] of the material `id` to jump to `frame` once [`Time::elapsed`] reaches `at`, such as to line an animation up with an audio cue.
+	///
+	/// The animation continues at its own framerate from there. If the material has no image animation when the frame is due, it's discarded.
+	pub fn set_frame_at(&mut self, id: impl Into<AssetId<GenericMaterial>>, frame: usize, at: Duration) {
+		self.scheduled_frames.push(ScheduledFrame { id: id.into(), frame, at });
+	}
+}
+
+/// An image animation frame scheduled with [`AnimatedGenericMaterials::set_frame_at`].
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub struct ScheduledFrame {
+	pub id: AssetId<GenericMaterial>,
+	pub frame: usize,
+	/// The elapsed time, as in [`Time::elapsed`], the frame is shown at.
+	pub at: Duration,
 }
 
 /// Animations stored in a [`GenericMaterial`].
@@ -162,13 +309,21 @@ pub trait MaterialAnimation {
 }
 
 /// Switch to [`material`](Self::material) after [`seconds`](Self::seconds).
+///
+/// If [`transition_seconds`](Self::transition_seconds) is set, the material cross-fades into the next one over that duration rather than switching instantly.
+/// This only works if both are of the same material type.
 #[derive(Reflect, Debug, Clone)]
 pub struct NextAnimation {
 	pub seconds: f32,
 	pub material: Handle<GenericMaterial>,
+	#[reflect(default)]
+	pub transition_seconds: Option<f32>,
 
 	#[reflect(ignore)]
 	pub state: GenericMaterialAnimationState,
+	#[cfg(feature = "bevy_pbr")]
+	#[reflect(ignore)]
+	pub transition: Option<NextTransition>,
 }
 impl MaterialAnimation for NextAnimation {
 	fn state_mut(&mut self) -> &mut GenericMaterialAnimationState {
@@ -180,6 +335,102 @@ impl MaterialAnimation for NextAnimation {
 	}
 }
 
+impl NextAnimation {
+	/// Follows the chain of next materials starting from this animation, checking that each of them loaded.
+	pub fn check_chain(
+		&self,
+		generic_materials: &Assets<GenericMaterial>,
+		asset_server: &AssetServer,
+	) -> Result<NextChain, AnimationValidationError> {
+		let mut visited = HashSet::new();
+		let mut current = self.material.clone();
+
+		loop {
+			let Some(generic_material) = generic_materials.get(&current) else {
+				return match asset_server.load_state(&current) {
+					LoadState::Failed(err) => Err(AnimationValidationError::NextFailedToLoad {
+						path: current.path().map(ToString::to_string).unwrap_or_else(|| format!("{current:?}")),
+						err: err.to_string(),
+					}),
+					_ => Ok(NextChain::Loading),
+				};
+			};
+
+			if !visited.insert(current.id()) {
+				return Ok(NextChain::Loops(current));
+			}
+
+			let Ok(MaterialAnimations { next: Some(next), .. }) = generic_material.get_property(GenericMaterial::ANIMATION) else {
+				return Ok(NextChain::Ends);
+			};
+			current = next.material.clone();
+		}
+	}
+}
+
+/// How a chain of [`NextAnimation`]s ends, returned by [`NextAnimation::check_chain`].
+#[derive(Debug, Clone)]
+pub enum NextChain {
+	/// The last material in the chain doesn't switch to another.
+	Ends,
+	/// The chain loops back to this material.
+	Loops(Handle<GenericMaterial>),
+	/// A material in the chain is still loading.
+	Loading,
+}
+
+/// An in-progress cross-fade of a [`NextAnimation`].
+#[cfg(feature = "bevy_pbr")]
+#[derive(Debug, Clone)]
+pub struct NextTransition {
+	pub start: Duration,
+	pub duration: Duration,
+}
+
+/// Material blended between the materials of a [`NextAnimation`] transition, keyed by the generic material transitioning.
+#[cfg(feature = "bevy_pbr")]
+#[derive(Resource, Default)]
+struct NextTransitionBlends(HashMap<AssetId<GenericMaterial>, ErasedMaterialHandle>);
+
+/// Returns the blended material of `id`, if it's cross-fading into its next material.
+#[cfg(feature = "bevy_pbr")]
+pub(crate) fn next_transition_blend(world: &World, id: AssetId<GenericMaterial>) -> Option<ErasedMaterialHandle> {
+	world.get_resource::<NextTransitionBlends>()?.0.get(&id).cloned()
+}
+
+/// Interpolates the material of `id` towards `target` by `t`, and applies the result to `entities`.
+///
+/// Entities given the material after the blend is created get it from [`next_transition_blend`] instead.
+#[cfg(feature = "bevy_pbr")]
+fn blend_next_transition(world: &mut World, id: AssetId<GenericMaterial>, target: AssetId<GenericMaterial>, t: f32, entities: Vec<Entity>) {
+	let generic_materials = world.resource::<Assets<GenericMaterial>>();
+	let (Some(from), Some(to)) = (generic_materials.get(id), generic_materials.get(target)) else { return };
+	let (from, to) = (from.handle.clone(), to.handle.clone());
+
+	// Materials of different types can't be blended, these just switch when the transition ends.
+	if from.inner().type_id() != to.inner().type_id() {
+		return;
+	}
+
+	let Some(mut blended) = from.clone_material(world) else { return };
+	let Some(to) = to.get_from_world(world) else { return };
+	lerp_reflect(blended.as_partial_reflect_mut(), to.as_partial_reflect(), t);
+
+	let existing = world.get_resource_or_init::<NextTransitionBlends>().0.get(&id).cloned();
+	match existing {
+		Some(blend) => blend.patch(world, blended.into_reflect()),
+		None => {
+			let blend = blended.add_asset(world.resource::<AssetServer>());
+			world.resource_mut::<NextTransitionBlends>().0.insert(id, blend.clone());
+
+			for entity in entities {
+				let Ok(entity) = world.get_entity_mut(entity) else { continue };
+				blend.clone().insert(entity);
+			}
+		}
+	}
+}
+
 /// Allows different image [`fields`](Self::fields) to cycle a list of images at a specified [`fps`](Self::fps).
 #[derive(Reflect, Debug, Clone)]
 pub struct ImagesAnimation {
@@ -192,6 +443,37 @@ pub struct ImagesAnimation {
 	#[reflect(ignore)]
 	pub state: GenericMaterialAnimationState,
 }
+#[cfg(feature = "bevy_pbr")]
+impl ImagesAnimation {
+	/// Checks that every animated field exists on the material type, and can hold an image.
+	pub fn validate(&self, type_registry: &TypeRegistry, material_type: TypeId) -> Result<(), AnimationValidationError> {
+		let Some(type_info) = type_registry.get_type_info(material_type) else {
+			return Err(AnimationValidationError::MaterialNotRegistered);
+		};
+		let TypeInfo::Struct(struct_info) = type_info else {
+			return Err(AnimationValidationError::NotAStruct(type_info.type_path()));
+		};
+
+		for field_name in self.fields.keys() {
+			let Some(field) = struct_info.field(field_name) else {
+				return Err(AnimationValidationError::FieldNotFound {
+					field: field_name.clone(),
+					ty: struct_info.type_path(),
+				});
+			};
+
+			if !field.is::<Handle<Image>>() && !field.is::<Option<Handle<Image>>>() {
+				return Err(AnimationValidationError::WrongFieldType {
+					field: field_name.clone(),
+					ty: struct_info.type_path(),
+					found: field.type_path(),
+				});
+			}
+		}
+
+		Ok(())
+	}
+}
 impl MaterialAnimation for ImagesAnimation {
 	fn state_mut(&mut self) -> &mut GenericMaterialAnimationState {
 		&mut self.state
@@ -201,6 +483,28 @@ impl MaterialAnimation for ImagesAnimation {
 		current_time + Duration::from_secs_f32(1. / self.fps)
 	}
 }
+impl ImagesAnimation {
+	/// Jumps to `frame`, and restarts the frame timer from `current_time`.
+	pub fn set_frame(&mut self, frame: usize, current_time: Duration) {
+		self.state.next_frame_time = self.new_next_frame_time(current_time);
+		self.state.current_frame = frame;
+	}
+}
+
+/// Errors that may occur when validating a [`MaterialAnimations`] against the material it animates, or the materials it switches to.
+#[derive(Error, Debug, Clone)]
+pub enum AnimationValidationError {
+	#[error("The material's type isn't registered")]
+	MaterialNotRegistered,
+	#[error("{0} isn't a struct, so its fields can't be animated")]
+	NotAStruct(&'static str),
+	#[error("Animated field {field} doesn't exist on {ty}")]
+	FieldNotFound { field: String, ty: &'static str },
+	#[error("Animated field {field} of {ty} is of type {found}, but animation frames are images")]
+	WrongFieldType { field: String, ty: &'static str, found: &'static str },
+	#[error("Next material {path} failed to load: {err}")]
+	NextFailedToLoad { path: String, err: String },
+}
 
 /// Stores the current frame, and schedules when the next frame should occur.
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,102 @@
+//! Integration with [`bevy_asset_loader`], so generic materials can be declared in asset collections.
+//!
+//! [`Handle<GenericMaterial>`] fields with `#[asset(path = "...")]` work without any of this.
+//! These are for dynamic assets, which resolve asset keys to material paths at runtime.
+
+use bevy::{asset::UntypedHandle, prelude::*};
+use bevy_asset_loader::dynamic_asset::{DynamicAsset, DynamicAssetCollection, DynamicAssetType, DynamicAssets};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::prelude::*;
+
+/// A dynamic asset resolving to one or more [`GenericMaterial`]s, for registering with [`DynamicAssets`].
+///
+/// # Examples
+/// ```ignore
+/// #[derive(AssetCollection, Resource)]
+/// struct Materials {
+///     #[asset(key = "bricks")]
+///     bricks: Handle<GenericMaterial>,
+/// }
+///
+/// dynamic_assets.register_asset("bricks", Box::new(GenericMaterialDynamicAsset::File {
+///     path: "materials/bricks.toml".to_string(),
+/// }));
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum GenericMaterialDynamicAsset {
+	/// A generic material loaded from a file.
+	File { path: String },
+	/// A list of generic materials, for collection fields such as `Vec<Handle<GenericMaterial>>`.
+	Files { paths: Vec<String> },
+	/// A generic material holding a [`StandardMaterial`] with the image at `path` as its `base_color_texture`, mirroring `bevy_asset_loader`'s `standard_material` attribute.
+	#[cfg(feature = "bevy_pbr")]
+	StandardMaterial { path: String },
+}
+impl DynamicAsset for GenericMaterialDynamicAsset {
+	fn load(&self, asset_server: &AssetServer) -> Vec<UntypedHandle> {
+		match self {
+			Self::File { path } => vec![asset_server.load::<GenericMaterial>(path).untyped()],
+			Self::Files { paths } => paths.iter().map(|path| asset_server.load::<GenericMaterial>(path).untyped()).collect(),
+			#[cfg(feature = "bevy_pbr")]
+			Self::StandardMaterial { path } => vec![asset_server.load::<Image>(path).untyped()],
+		}
+	}
+
+	fn build(&self, world: &mut World) -> Result<DynamicAssetType, anyhow::Error> {
+		let asset_server = world.resource::<AssetServer>();
+		let get_handle = |path: &String| {
+			asset_server
+				.get_handle::<GenericMaterial>(path)
+				.ok_or_else(|| GenericMaterialDynamicAssetError::NotLoaded(path.clone()))
+		};
+
+		Ok(match self {
+			Self::File { path } => DynamicAssetType::Single(get_handle(path)?.untyped()),
+			Self::Files { paths } => DynamicAssetType::Collection(
+				paths
+					.iter()
+					.map(|path| Ok(get_handle(path)?.untyped()))
+					.collect::<Result<_, GenericMaterialDynamicAssetError>>()?,
+			),
+			#[cfg(feature = "bevy_pbr")]
+			Self::StandardMaterial { path } => {
+				let image = asset_server
+					.get_handle::<Image>(path)
+					.ok_or_else(|| GenericMaterialDynamicAssetError::NotLoaded(path.clone()))?;
+				let material = asset_server.add(StandardMaterial::from(image));
+				DynamicAssetType::Single(asset_server.add(GenericMaterial::new(material)).untyped())
+			}
+		})
+	}
+}
+
+/// Registers every material of the set as a [`GenericMaterialDynamicAsset::File`], keyed by its name in the set.
+///
+/// This allows using material set files as dynamic asset files.
+///
+/// # Examples
+/// ```ignore
+/// app.add_loading_state(
+///     LoadingState::new(GameState::Loading)
+///         .continue_to_state(GameState::Playing)
+///         .with_dynamic_assets_file::<GenericMaterialSet>("tiles.materialset.toml")
+///         .load_collection::<Tiles>(),
+/// );
+/// ```
+impl DynamicAssetCollection for GenericMaterialSet {
+	fn register(&self, dynamic_assets: &mut DynamicAssets) {
+		for (name, handle) in &self.materials {
+			let Some(path) = handle.path() else { continue };
+			dynamic_assets.register_asset(name, Box::new(GenericMaterialDynamicAsset::File { path: path.to_string() }));
+		}
+	}
+}
+
+/// Errors that may occur when building a [`GenericMaterialDynamicAsset`].
+#[derive(Error, Debug, Clone)]
+pub enum GenericMaterialDynamicAssetError {
+	#[error("No handle for {0}, it wasn't loaded")]
+	NotLoaded(String),
+}
@@ -0,0 +1,133 @@
+use bevy::{platform::collections::HashSet, prelude::*};
+
+use crate::prelude::*;
+
+impl GenericMaterial {
+	/// Material property describing the physics collider entities using the material should have, only works if [`ColliderPlugin`] is added.
+	///
+	/// This crate doesn't create colliders itself, physics integrations do so with the functions registered with [`ColliderAppExt::register_collider_applier`].
+	///
+	/// # Examples
+	/// ```toml
+	/// [properties.collider]
+	/// shape = "Convex"
+	/// friction = 0.4
+	/// restitution = 0.1
+	/// ```
+	pub const COLLIDER: MaterialProperty<ColliderProperty> = MaterialProperty::new("collider");
+}
+
+/// Collider settings for the [`COLLIDER`](GenericMaterial::COLLIDER) property.
+#[derive(Reflect, Debug, Clone, PartialEq, Default)]
+#[reflect(Default)]
+pub struct ColliderProperty {
+	#[reflect(default)]
+	pub shape: ColliderShape,
+	/// Friction coefficient of the collider. If [`None`], the physics engine's default is used.
+	#[reflect(default)]
+	pub friction: Option<f32>,
+	/// Restitution (bounciness) coefficient of the collider. If [`None`], the physics engine's default is used.
+	#[reflect(default)]
+	pub restitution: Option<f32>,
+}
+
+/// Hint for what shape of collider to build from an entity's mesh.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[reflect(Default)]
+pub enum ColliderShape {
+	/// A triangle mesh collider matching the mesh exactly.
+	#[default]
+	Trimesh,
+	/// The convex hull of the mesh.
+	Convex,
+	/// No collider, appliers should remove any collider they previously inserted.
+	None,
+}
+
+/// Functions that build colliders for entities with the [`COLLIDER`](GenericMaterial::COLLIDER) property, registered by physics integrations.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ColliderAppliers {
+	pub inner: Vec<fn(&ColliderProperty, &mut EntityWorldMut)>,
+}
+
+pub trait ColliderAppExt {
+	/// Adds a function to [`ColliderAppliers`], which is called with entities whose [`GenericMaterial3d`] has the [`COLLIDER`](GenericMaterial::COLLIDER) property,
+	/// when the material is applied, loaded, or reloaded.
+	///
+	/// # Examples
+	/// ```ignore
+	/// // An adapter for Avian.
+	/// app.register_collider_applier(|collider, entity| {
+	///     let Some(mesh) = entity.get::<Mesh3d>().map(|mesh| mesh.0.clone()) else { return };
+	///     let Some(mesh) = entity.world().resource::<Assets<Mesh>>().get(&mesh) else { return };
+	///
+	///     let shape = match collider.shape {
+	///         ColliderShape::Trimesh => Collider::trimesh_from_mesh(mesh),
+	///         ColliderShape::Convex => Collider::convex_hull_from_mesh(mesh),
+	///         ColliderShape::None => None,
+	///     };
+	///
+	///     match shape {
+	///         Some(shape) => entity.insert((shape, Friction::new(collider.friction.unwrap_or(0.5)))),
+	///         None => entity.remove::<Collider>(),
+	///     };
+	/// });
+	/// ```
+	fn register_collider_applier(&mut self, applier: fn(&ColliderProperty, &mut EntityWorldMut)) -> &mut Self;
+}
+impl ColliderAppExt for App {
+	fn register_collider_applier(&mut self, applier: fn(&ColliderProperty, &mut EntityWorldMut)) -> &mut Self {
+		self.world_mut().get_resource_or_init::<ColliderAppliers>().inner.push(applier);
+		self
+	}
+}
+
+/// Calls the [`ColliderAppliers`] for entities whose [`GenericMaterial3d`] was changed, or whose material was loaded or modified.
+pub fn apply_collider_property(
+	mut commands: Commands,
+	query: Query<(Entity, Ref<GenericMaterial3d>)>,
+	mut asset_events: MessageReader<AssetEvent<GenericMaterial>>,
+	generic_materials: Res<Assets<GenericMaterial>>,
+	appliers: Res<ColliderAppliers>,
+) {
+	let modified: HashSet<AssetId<GenericMaterial>> = asset_events
+		.read()
+		.filter_map(|event| match event {
+			AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+			_ => None,
+		})
+		.collect();
+
+	if appliers.inner.is_empty() {
+		return;
+	}
+
+	for (entity, holder) in &query {
+		if !holder.is_changed() && !modified.contains(&holder.id()) {
+			continue;
+		}
+		let Some(generic_material) = generic_materials.get(&holder.0) else { continue };
+		let Ok(collider) = generic_material.get_property(GenericMaterial::COLLIDER).cloned() else { continue };
+
+		let appliers = appliers.inner.clone();
+		commands.queue(move |world: &mut World| {
+			let Ok(mut entity) = world.get_entity_mut(entity) else { return };
+			for applier in appliers {
+				applier(&collider, &mut entity);
+			}
+		});
+	}
+}
+
+/// Registers the [`COLLIDER`](GenericMaterial::COLLIDER) property, and calls the [`ColliderAppliers`] registered by physics integrations.
+pub struct ColliderPlugin;
+impl Plugin for ColliderPlugin {
+	fn build(&self, app: &mut App) {
+		#[rustfmt::skip]
+		app
+			.register_material_property(GenericMaterial::COLLIDER)
+			.init_resource::<ColliderAppliers>()
+			.add_systems(PreUpdate, apply_collider_property)
+		;
+	}
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use bevy::{color::ColorToComponents, prelude::*, reflect::ReflectRef};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// Plain-data description of a [`GenericMaterial`], independent of Bevy's reflection internals.
+///
+/// Meant for external tools, scripting languages, or saving user-created materials. Created with [`GenericMaterial::to_description`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct MaterialDescription {
+	/// The full type path of the material, or [`None`] if it isn't loaded.
+	#[serde(rename = "type")]
+	pub ty: Option<String>,
+	/// Fields of the material. Fields of nested structs are separated with `.` (e.g. `base.base_color`).
+	pub material: BTreeMap<String, MaterialDescriptionValue>,
+	pub properties: BTreeMap<String, MaterialDescriptionValue>,
+}
+
+/// A single value in a [`MaterialDescription`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MaterialDescriptionValue {
+	Bool(bool),
+	Int(i64),
+	Float(f64),
+	/// Strings, and enum variants without fields.
+	String(String),
+	/// Linear RGBA.
+	Color([f32; 4]),
+	/// The asset path of a texture.
+	Texture(String),
+}
+impl MaterialDescriptionValue {
+	/// Describes `value`, returning [`None`] if it isn't one of the supported kinds of values.
+	pub fn from_reflect(value: &dyn PartialReflect) -> Option<Self> {
+		macro_rules! downcast {
+			($($ty:ty => $f:expr),* $(,)?) => {
+				$(if let Some(value) = value.try_downcast_ref::<$ty>() {
+					#[allow(clippy::redundant_closure_call)]
+					return ($f)(value);
+				})*
+			};
+		}
+
+		downcast! {
+			bool => |x: &bool| Some(Self::Bool(*x)),
+			i8 => |x: &i8| Some(Self::Int((*x).into())),
+			i16 => |x: &i16| Some(Self::Int((*x).into())),
+			i32 => |x: &i32| Some(Self::Int((*x).into())),
+			i64 => |x: &i64| Some(Self::Int(*x)),
+			u8 => |x: &u8| Some(Self::Int((*x).into())),
+			u16 => |x: &u16| Some(Self::Int((*x).into())),
+			u32 => |x: &u32| Some(Self::Int((*x).into())),
+			u64 => |x: &u64| i64::try_from(*x).ok().map(Self::Int),
+			f32 => |x: &f32| Some(Self::Float((*x).into())),
+			f64 => |x: &f64| Some(Self::Float(*x)),
+			String => |x: &String| Some(Self::String(x.clone())),
+			Color => |x: &Color| Some(Self::Color(x.to_linear().to_f32_array())),
+			LinearRgba => |x: &LinearRgba| Some(Self::Color(x.to_f32_array())),
+			Srgba => |x: &Srgba| Some(Self::Color(LinearRgba::from(*x).to_f32_array())),
+			Handle<Image> => |x: &Handle<Image>| x.path().map(|path| Self::Texture(path.to_string())),
+			Option<Handle<Image>> => |x: &Option<Handle<Image>>| x.as_ref()?.path().map(|path| Self::Texture(path.to_string())),
+		}
+
+		match value.reflect_ref() {
+			ReflectRef::Enum(e) if e.field_len() == 0 => Some(Self::String(e.variant_name().to_string())),
+			_ => None,
+		}
+	}
+}
+
+impl GenericMaterial {
+	/// Returns a plain-data description of this material and its properties. See [`MaterialDescription`].
+	///
+	/// Values that can't be described, such as custom structs in properties or handles to assets that aren't images, are left out.
+	pub fn to_description(&self, world: &World) -> MaterialDescription {
+		fn collect(prefix: &str, value: &dyn PartialReflect, out: &mut BTreeMap<String, MaterialDescriptionValue>) {
+			if let Some(description) = MaterialDescriptionValue::from_reflect(value) {
+				out.insert(prefix.to_string(), description);
+				return;
+			}
+
+			let ReflectRef::Struct(s) = value.reflect_ref() else { return };
+			for (name, field) in s.iter_fields() {
+				let name = if prefix.is_empty() {
+					name.to_string()
+				} else {
+					format!("{prefix}.{name}")
+				};
+				collect(&name, field, out);
+			}
+		}
+
+		let mut description = MaterialDescription::default();
+
+		if let Some(material) = self.handle.get_from_world(world) {
+			description.ty = Some(material.reflect_type_path().to_string());
+			collect("", material.as_partial_reflect(), &mut description.material);
+		}
+
+		for (key, value) in &self.properties {
+			if let Some(value) = MaterialDescriptionValue::from_reflect(value.as_partial_reflect()) {
+				description.properties.insert(key.clone(), value);
+			}
+		}
+
+		description
+	}
+}
@@ -0,0 +1,201 @@
+use std::{io, path::Path};
+
+use bevy::{
+	asset::{
+		AssetLoader, LoadContext, RenderAssetUsages,
+		io::{AssetReader, AssetReaderError, AssetReaderFuture, AssetSourceBuilder, PathStream, Reader, VecReader},
+	},
+	color::ColorToPacked,
+	image::ImageSampler,
+	prelude::*,
+	render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+	tasks::ConditionalSendFuture,
+};
+
+/// Adds the `dev://` asset source, which generates checkerboard and grid materials for blockouts without needing any textures on disk.
+///
+/// Paths are of the form `dev://<pattern>/<size>/<color>.<extension>`, where everything after the pattern is optional.
+/// - `pattern` is either `checker` or `grid`.
+/// - `size` is the resolution of the texture in pixels. (Default: `64`)
+/// - `color` is a hex color. (Default: `808080`)
+/// - `extension` is `toml` or `json`, depending on which [`MaterialDeserializer`] you use.
+///
+/// Since these are served as regular material files, you can inherit from them and add properties on top.
+/// ```toml
+/// inherits = "dev://checker/64/ff8000.toml"
+///
+/// [properties]
+/// collision = true
+/// ```
+///
+/// NOTE: Like all asset sources, this must be added before [`AssetPlugin`] (usually a part of [`DefaultPlugins`]).
+pub struct DevMaterialsPlugin;
+impl Plugin for DevMaterialsPlugin {
+	fn build(&self, app: &mut App) {
+		app.register_asset_source("dev", AssetSourceBuilder::new(|| Box::new(DevAssetReader)));
+	}
+
+	fn finish(&self, app: &mut App) {
+		app.register_asset_loader(DevTextureLoader);
+	}
+}
+
+/// Pattern of a generated dev material.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevPattern {
+	Checker,
+	Grid,
+}
+
+/// Parameters of a generated dev material, see [`DevMaterialsPlugin`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DevMaterial {
+	pub pattern: DevPattern,
+	pub size: u32,
+	pub color: Srgba,
+}
+impl DevMaterial {
+	/// How many cells the pattern has along each side of the texture.
+	pub const CELLS: u32 = 8;
+
+	/// Parses parameters from a path inside the `dev://` source, such as `checker/64/ff8000.toml`.
+	pub fn from_path(path: &Path) -> Option<Self> {
+		let path = path.with_extension("");
+		let mut parts = path.iter().map(|part| part.to_str());
+
+		let pattern = match parts.next()?? {
+			"checker" => DevPattern::Checker,
+			"grid" => DevPattern::Grid,
+			_ => return None,
+		};
+		let size = match parts.next() {
+			Some(size) => size?.parse().ok()?,
+			None => 64,
+		};
+		let color = match parts.next() {
+			Some(color) => Srgba::hex(color?).ok()?,
+			None => Srgba::rgb(0.5, 0.5, 0.5),
+		};
+
+		if parts.next().is_some() {
+			return None;
+		}
+
+		Some(Self { pattern, size, color })
+	}
+
+	/// Generates the material's texture.
+	pub fn image(&self) -> Image {
+		let size = self.size.max(Self::CELLS);
+		let cell_size = size / Self::CELLS;
+
+		let light = self.color.to_u8_array();
+		let dark = Srgba::new(self.color.red * 0.6, self.color.green * 0.6, self.color.blue * 0.6, self.color.alpha).to_u8_array();
+
+		let mut data = Vec::with_capacity((size * size * 4) as usize);
+		for y in 0..size {
+			for x in 0..size {
+				let is_light = match self.pattern {
+					DevPattern::Checker => (x / cell_size + y / cell_size) % 2 == 0,
+					DevPattern::Grid => x % cell_size != 0 && y % cell_size != 0,
+				};
+
+				data.extend_from_slice(if is_light { &light } else { &dark });
+			}
+		}
+
+		let mut image = Image::new(
+			Extent3d {
+				width: size,
+				height: size,
+				depth_or_array_layers: 1,
+			},
+			TextureDimension::D2,
+			data,
+			TextureFormat::Rgba8UnormSrgb,
+			RenderAssetUsages::default(),
+		);
+		image.sampler = ImageSampler::nearest();
+
+		image
+	}
+
+	/// Produces the text of the material file at `path`, referencing its texture by its full `dev://` path.
+	///
+	/// Relative paths in inherited materials are resolved relative to the material that inherits them, so a relative texture path wouldn't point back into `dev://`.
+	fn material_file(path: &Path) -> Option<String> {
+		let texture = path.with_extension(DevTextureLoader::EXTENSION);
+		let texture = texture.iter().map(|part| part.to_str()).collect::<Option<Vec<_>>>()?.join("/");
+		let texture = format!("dev://{texture}");
+
+		match path.extension()?.to_str()? {
+			"toml" => Some(format!("[material]\nbase_color_texture = \"{texture}\"\nperceptual_roughness = 0.9\n")),
+			"json" => Some(format!(
+				r#"{{ "material": {{ "base_color_texture": "{texture}", "perceptual_roughness": 0.9 }} }}"#
+			)),
+			_ => None,
+		}
+	}
+}
+
+/// Serves generated material files and empty texture files for [`DevTextureLoader`] to fill in.
+struct DevAssetReader;
+impl AssetReader for DevAssetReader {
+	fn read<'a>(&'a self, path: &'a Path) -> impl AssetReaderFuture<Value: Reader + 'a> {
+		async move {
+			DevMaterial::from_path(path).ok_or_else(|| AssetReaderError::NotFound(path.to_path_buf()))?;
+
+			let bytes = match path.extension().and_then(|extension| extension.to_str()) {
+				Some(DevTextureLoader::EXTENSION) => Vec::new(),
+				_ => DevMaterial::material_file(path)
+					.ok_or_else(|| AssetReaderError::NotFound(path.to_path_buf()))?
+					.into_bytes(),
+			};
+
+			Ok(VecReader::new(bytes))
+		}
+	}
+
+	fn read_meta<'a>(&'a self, path: &'a Path) -> impl AssetReaderFuture<Value: Reader + 'a> {
+		async move { Err::<VecReader, _>(AssetReaderError::NotFound(path.to_path_buf())) }
+	}
+
+	fn read_directory<'a>(&'a self, path: &'a Path) -> impl ConditionalSendFuture<Output = Result<Box<PathStream>, AssetReaderError>> {
+		async move { Err(AssetReaderError::NotFound(path.to_path_buf())) }
+	}
+
+	fn is_directory<'a>(&'a self, _path: &'a Path) -> impl ConditionalSendFuture<Output = Result<bool, AssetReaderError>> {
+		async move { Ok(false) }
+	}
+}
+
+/// Generates the textures of dev materials from their path.
+#[derive(TypePath)]
+pub struct DevTextureLoader;
+impl DevTextureLoader {
+	pub const EXTENSION: &str = "devtex";
+}
+impl AssetLoader for DevTextureLoader {
+	type Asset = Image;
+	type Settings = ();
+	type Error = io::Error;
+
+	fn load(
+		&self,
+		_reader: &mut dyn Reader,
+		_settings: &Self::Settings,
+		load_context: &mut LoadContext,
+	) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+		Box::pin(async move {
+			let Some(material) = DevMaterial::from_path(load_context.path().path()) else {
+				return Err(io::Error::other(format!("Invalid dev texture path: {}", load_context.path())));
+			};
+
+			Ok(material.image())
+		})
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&[Self::EXTENSION]
+	}
+}
@@ -0,0 +1,69 @@
+use bevy::{
+	asset::UntypedAssetId,
+	diagnostic::{DEFAULT_MAX_HISTORY_LENGTH, Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+	platform::collections::{HashMap, HashSet},
+	prelude::*,
+};
+
+use crate::{GenericMaterial, generic_material::ExtractedGenericMaterial};
+
+/// Adds diagnostics counting how many entities use generic materials, and how many unique generic and inner materials they use.
+///
+/// Per-material counts are stored in [`GenericMaterialUsage`], which is useful for spotting materials that could be deduplicated.
+/// Use [`LogDiagnosticsPlugin`](bevy::diagnostic::LogDiagnosticsPlugin) to output the diagnostics to the console.
+pub struct GenericMaterialDiagnosticsPlugin {
+	/// The total number of values to keep.
+	pub max_history_length: usize,
+}
+impl Default for GenericMaterialDiagnosticsPlugin {
+	fn default() -> Self {
+		Self {
+			max_history_length: DEFAULT_MAX_HISTORY_LENGTH,
+		}
+	}
+}
+impl Plugin for GenericMaterialDiagnosticsPlugin {
+	fn build(&self, app: &mut App) {
+		for path in [Self::ENTITIES, Self::UNIQUE_GENERIC_MATERIALS, Self::UNIQUE_INNER_MATERIALS] {
+			app.register_diagnostic(Diagnostic::new(path).with_max_history_length(self.max_history_length));
+		}
+
+		#[rustfmt::skip]
+		app
+			.init_resource::<GenericMaterialUsage>()
+			.add_systems(Update, Self::diagnostic_system)
+		;
+	}
+}
+impl GenericMaterialDiagnosticsPlugin {
+	/// Number of entities with a generic material applied.
+	pub const ENTITIES: DiagnosticPath = DiagnosticPath::const_new("generic_material/entities");
+	/// Number of unique [`GenericMaterial`]s applied to entities.
+	pub const UNIQUE_GENERIC_MATERIALS: DiagnosticPath = DiagnosticPath::const_new("generic_material/unique_generic_materials");
+	/// Number of unique inner materials applied to entities. This can be higher than [`UNIQUE_GENERIC_MATERIALS`](Self::UNIQUE_GENERIC_MATERIALS)
+	/// when features like texture overrides create copies of materials.
+	pub const UNIQUE_INNER_MATERIALS: DiagnosticPath = DiagnosticPath::const_new("generic_material/unique_inner_materials");
+
+	/// Updates [`GenericMaterialUsage`] and the diagnostic measurements.
+	pub fn diagnostic_system(mut diagnostics: Diagnostics, mut usage: ResMut<GenericMaterialUsage>, query: Query<&ExtractedGenericMaterial>) {
+		usage.entities_per_material.clear();
+		let mut inner_materials: HashSet<UntypedAssetId> = HashSet::default();
+		let mut entities = 0;
+
+		for extracted in &query {
+			entities += 1;
+			*usage.entities_per_material.entry(extracted.generic_material).or_default() += 1;
+			inner_materials.insert(extracted.material);
+		}
+
+		diagnostics.add_measurement(&Self::ENTITIES, || entities as f64);
+		diagnostics.add_measurement(&Self::UNIQUE_GENERIC_MATERIALS, || usage.entities_per_material.len() as f64);
+		diagnostics.add_measurement(&Self::UNIQUE_INNER_MATERIALS, || inner_materials.len() as f64);
+	}
+}
+
+/// How many entities use each [`GenericMaterial`], updated every frame by [`GenericMaterialDiagnosticsPlugin`].
+#[derive(Resource, Debug, Clone, Default)]
+pub struct GenericMaterialUsage {
+	pub entities_per_material: HashMap<AssetId<GenericMaterial>, usize>,
+}
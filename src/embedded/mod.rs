@@ -0,0 +1,33 @@
+use bevy::{asset::embedded_asset, prelude::*};
+
+/// Embeds the built-in material files into the binary, making them available even without an assets folder.
+///
+/// The built-in materials are in TOML, so loading them requires a [`MaterializePlugin`](crate::MaterializePlugin) using [`TomlMaterialDeserializer`](crate::prelude::TomlMaterialDeserializer).
+pub struct EmbeddedMaterialsPlugin;
+impl Plugin for EmbeddedMaterialsPlugin {
+	fn build(&self, app: &mut App) {
+		embedded_asset!(app, "src/embedded", "missing.toml");
+	}
+}
+impl EmbeddedMaterialsPlugin {
+	/// Bright magenta unlit material, meant as a placeholder for materials that are missing or failed to load.
+	pub const MISSING: &str = "embedded://bevy_materialize/missing.toml";
+}
+
+/// Embeds material files into the binary via [`embedded_asset`], registering them under `embedded://<your crate>/<path>`.
+///
+/// Paths are relative to the file this is called in, and like [`embedded_asset`], the `src` directory is trimmed from the final path.
+///
+/// # Examples
+/// ```ignore
+/// // In src/lib.rs, with the file at src/materials/fallback.toml
+/// embedded_materials!(app, "materials/fallback.toml", "materials/grid.toml");
+/// // Then
+/// asset_server.load::<GenericMaterial>("embedded://my_crate/materials/fallback.toml");
+/// ```
+#[macro_export]
+macro_rules! embedded_materials {
+	($app:expr, $($path:expr),+ $(,)?) => {{
+		$(::bevy::asset::embedded_asset!($app, $path);)+
+	}};
+}
@@ -1,31 +1,67 @@
-use std::{borrow::Cow, fmt};
+use std::{
+	any::{Any, TypeId},
+	borrow::Cow,
+	fmt,
+	sync::Arc,
+};
 
 use bevy::{
 	asset::{AssetPath, LoadContext, UntypedAssetId},
 	prelude::*,
-	reflect::{GetTypeRegistration, ReflectMut, Typed},
+	reflect::{GetTypeRegistration, ReflectMut, ReflectRef, Typed},
 };
 
-/// Type-erased [`Material`].
-pub trait ErasedMaterial: Send + Sync + Reflect + Struct {
+use crate::generic_material::ReflectGenericMaterial;
+
+/// Type-erased [`Material`], or [`Material2d`](bevy::sprite_render::Material2d).
+///
+/// Materials don't need to be structs, enum and tuple struct materials are patched as a whole rather than per-field.
+pub trait ErasedMaterial: Send + Sync + Reflect {
 	fn add_labeled_asset(self: Box<Self>, load_context: &mut LoadContext, label: String) -> ErasedMaterialHandle;
 	fn add_asset(self: Box<Self>, asset_server: &AssetServer) -> ErasedMaterialHandle;
 	fn clone_erased(&self) -> Box<dyn ErasedMaterial>;
 }
-impl<M: Material + Reflect + Struct + Clone> ErasedMaterial for M {
+impl<M: Asset + Reflect + Clone> ErasedMaterial for M {
 	fn add_labeled_asset(self: Box<Self>, load_context: &mut LoadContext, label: String) -> ErasedMaterialHandle {
-		load_context.add_labeled_asset(label, *self).into()
+		ErasedMaterialHandle::from_registry(load_context.add_labeled_asset(label, *self))
 	}
 
 	fn add_asset(self: Box<Self>, asset_server: &AssetServer) -> ErasedMaterialHandle {
-		asset_server.add(*self).into()
+		ErasedMaterialHandle::from_registry(asset_server.add(*self))
 	}
 
 	fn clone_erased(&self) -> Box<dyn ErasedMaterial> {
 		Box::new(self.clone())
 	}
 }
-impl<M: Material + Reflect + Struct + Clone> From<M> for Box<dyn ErasedMaterial> {
+/// Downcasting to the concrete material type. [`as_any`](Reflect::as_any) comes from [`Reflect`].
+impl dyn ErasedMaterial {
+	/// Returns whether the material is of type `M`.
+	#[inline]
+	pub fn is<M: Any>(&self) -> bool {
+		Reflect::as_any(self).is::<M>()
+	}
+
+	#[inline]
+	pub fn downcast_ref<M: Any>(&self) -> Option<&M> {
+		Reflect::as_any(self).downcast_ref()
+	}
+
+	#[inline]
+	pub fn downcast_mut<M: Any>(&mut self) -> Option<&mut M> {
+		Reflect::as_any_mut(self).downcast_mut()
+	}
+
+	/// Converts to the concrete material type, returning `self` back if the material isn't of type `M`.
+	pub fn downcast<M: Any>(self: Box<Self>) -> Result<Box<M>, Box<Self>> {
+		if self.is::<M>() {
+			Ok(Reflect::into_any(self).downcast().expect("type was just checked"))
+		} else {
+			Err(self)
+		}
+	}
+}
+impl<M: Asset + Reflect + Clone> From<M> for Box<dyn ErasedMaterial> {
 	fn from(value: M) -> Self {
 		Box::new(value)
 	}
@@ -36,23 +72,96 @@ impl Clone for Box<dyn ErasedMaterial> {
 	}
 }
 
+/// The kind of mesh an [`ErasedMaterialHandle`] inserts its material for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaterialTarget {
+	/// Inserts [`MeshMaterial3d`], used for entities with [`Mesh3d`].
+	Mesh3d,
+	/// Inserts [`MeshMaterial2d`](bevy::sprite_render::MeshMaterial2d), used for entities with [`Mesh2d`].
+	#[cfg(feature = "bevy_sprite_render")]
+	Mesh2d,
+	/// Inserts [`MeshMaterial3d`] for entities with [`MeshletMesh3d`](bevy::pbr::experimental::meshlet::MeshletMesh3d), which meshlet rendering reads its material from.
+	///
+	/// Every 3D material supports this target, but the material's shaders must be compatible with meshlets.
+	#[cfg(feature = "meshlet")]
+	MeshletMesh3d,
+}
+impl MaterialTarget {
+	/// Picks the target an entity wants based on which mesh component it has, or whether it has a [`GenericMaterial2d`](crate::GenericMaterial2d).
+	/// Defaults to [`MaterialTarget::Mesh3d`].
+	pub fn of_entity(entity: &EntityWorldMut) -> Self {
+		#[cfg(feature = "bevy_sprite_render")]
+		if entity.contains::<Mesh2d>() || entity.contains::<crate::GenericMaterial2d>() {
+			return Self::Mesh2d;
+		}
+
+		#[cfg(feature = "meshlet")]
+		if entity.contains::<bevy::pbr::experimental::meshlet::MeshletMesh3d>() {
+			return Self::MeshletMesh3d;
+		}
+
+		#[cfg(not(any(feature = "bevy_sprite_render", feature = "meshlet")))]
+		let _ = entity;
+
+		Self::Mesh3d
+	}
+}
+
 // Wrapper struct instead of dyn-compatible trait because `Handle<T>` is always the same size (we have `UntypedHandle`)
 
 /// Wrapper over [`UntypedHandle`] specifically for reflected [`Material`]s, containing functions related to managing said materials on entities.
 #[derive(Clone)]
 pub struct ErasedMaterialHandle {
 	inner: UntypedHandle,
+	/// The same handle as `inner`, but typed as `Handle<M>`, so it can be borrowed through [`downcast_ref`](Self::downcast_ref).
+	typed: Arc<dyn Any + Send + Sync>,
 	vtable: &'static ErasedMaterialHandleVTable,
 }
 #[allow(clippy::type_complexity)]
 impl ErasedMaterialHandle {
-	pub fn new<M: Material + Reflect>(handle: Handle<M>) -> Self {
+	fn with_vtable<M: Asset>(handle: Handle<M>, vtable: &'static ErasedMaterialHandleVTable) -> Self {
 		Self {
-			inner: handle.untyped(),
-			vtable: ErasedMaterialHandleVTable::of::<M>(),
+			inner: handle.clone().untyped(),
+			typed: Arc::new(handle),
+			vtable,
 		}
 	}
 
+	pub fn new<M: Material + Reflect>(handle: Handle<M>) -> Self {
+		Self::with_vtable(handle, ErasedMaterialHandleVTable::of::<M>())
+	}
+
+	/// Creates a handle to a material that only implements [`Material2d`](bevy::sprite_render::Material2d), such as [`ColorMaterial`].
+	///
+	/// When inserted, this always inserts [`MeshMaterial2d`](bevy::sprite_render::MeshMaterial2d).
+	#[cfg(feature = "bevy_sprite_render")]
+	pub fn new_2d<M: bevy::sprite_render::Material2d + Reflect>(handle: Handle<M>) -> Self {
+		Self::with_vtable(handle, ErasedMaterialHandleVTable::of_2d::<M>())
+	}
+
+	/// Creates a handle that picks how to insert its material from the material's registered [`ReflectGenericMaterial`] when inserted.
+	///
+	/// This is what [`ErasedMaterial`] uses, as it can't know whether the material is 2D or 3D.
+	pub fn from_registry<M: Asset + Reflect>(handle: Handle<M>) -> Self {
+		Self::with_vtable(handle, ErasedMaterialHandleVTable::of_registry::<M>())
+	}
+
+	/// Creates a handle to a [`SkyboxMaterial`](crate::skybox::SkyboxMaterial), which isn't a mesh material.
+	///
+	/// When inserted, this inserts [`SkyboxMaterial3d`](crate::skybox::SkyboxMaterial3d), which then drives the entity's skybox and environment map light.
+	#[cfg(feature = "skybox")]
+	pub fn new_skybox(handle: Handle<crate::skybox::SkyboxMaterial>) -> Self {
+		Self::with_vtable(handle, ErasedMaterialHandleVTable::of_skybox())
+	}
+
+	/// Creates a handle to a material that implements both [`Material`] and [`Material2d`](bevy::sprite_render::Material2d).
+	///
+	/// When inserted, this picks [`MeshMaterial2d`](bevy::sprite_render::MeshMaterial2d) for entities with [`Mesh2d`], and [`MeshMaterial3d`] otherwise.
+	#[cfg(feature = "bevy_sprite_render")]
+	pub fn new_2d_and_3d<M: Material + bevy::sprite_render::Material2d + Reflect>(handle: Handle<M>) -> Self {
+		Self::with_vtable(handle, ErasedMaterialHandleVTable::of_2d_and_3d::<M>())
+	}
+
 	#[inline]
 	pub fn inner(&self) -> &UntypedHandle {
 		&self.inner
@@ -62,6 +171,35 @@ impl ErasedMaterialHandle {
 		self.inner
 	}
 
+	/// Returns whether this is a handle to a material of type `M`.
+	#[inline]
+	pub fn is<M: Asset>(&self) -> bool {
+		self.inner.type_id() == TypeId::of::<M>()
+	}
+
+	/// Returns a typed clone of this handle, or [`None`] if the material isn't of type `M`.
+	#[inline]
+	pub fn typed<M: Asset>(&self) -> Option<Handle<M>> {
+		self.inner.clone().try_typed().ok()
+	}
+
+	/// Returns the typed `Handle<M>` of this material as [`Any`].
+	#[inline]
+	pub fn as_any(&self) -> &dyn Any {
+		&*self.typed
+	}
+
+	/// Borrows the typed handle, or returns [`None`] if `T` isn't the `Handle<M>` of this material's type.
+	///
+	/// # Examples
+	/// ```ignore
+	/// let handle: Option<&Handle<StandardMaterial>> = generic_material.handle.downcast_ref::<Handle<StandardMaterial>>();
+	/// ```
+	#[inline]
+	pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+		self.as_any().downcast_ref()
+	}
+
 	#[inline]
 	pub fn id(&self) -> UntypedAssetId {
 		self.inner.id()
@@ -72,16 +210,30 @@ impl ErasedMaterialHandle {
 		self.inner.path()
 	}
 
-	/// Inserts the appropriate [`MeshMaterial3d`] on an entity.
+	/// Returns whether this material can be inserted for `target`.
+	#[inline]
+	pub fn supports(&self, target: MaterialTarget) -> bool {
+		self.vtable.targets.contains(&target)
+	}
+
+	/// Inserts the appropriate mesh material component on an entity, picked by [`MaterialTarget::of_entity`].
+	///
+	/// If the material doesn't support the entity's target, this falls back to the material's main target, [`MeshMaterial3d`] for 3D materials.
 	#[inline]
 	pub fn insert(self, entity: EntityWorldMut) {
-		(self.vtable.insert)(self.inner, entity);
+		let target = MaterialTarget::of_entity(&entity);
+		let target = if self.supports(target) {
+			target
+		} else {
+			self.vtable.targets.first().copied().unwrap_or(target)
+		};
+		(self.vtable.insert)(self.inner, target, entity);
 	}
 
-	/// Removes the appropriate [`MeshMaterial3d`] from an entity.
+	/// Removes every mesh material component this material could have inserted from an entity.
 	#[inline]
 	pub fn remove(&self, entity: EntityWorldMut) {
-		(self.vtable.remove)(entity);
+		(self.vtable.remove)(&self.inner, entity);
 	}
 
 	/// Gets the asset from the world's appropriate [`Assets<...>`] collection.
@@ -90,6 +242,39 @@ impl ErasedMaterialHandle {
 		(self.vtable.get_from_world)(self.id(), world)
 	}
 
+	/// Gets the handle in an entity's mesh material component for this material's type.
+	///
+	/// This can differ from this handle, such as for entities given a unique copy with [`UniqueGenericMaterial`](crate::material_overrides::UniqueGenericMaterial).
+	#[inline]
+	pub fn get_from_entity(&self, world: &World, entity: Entity) -> Option<Self> {
+		(self.vtable.get_from_entity)(&self.inner, world, entity)
+	}
+
+	/// Creates an owned copy of this material through its registered [`ReflectGenericMaterial`], for making variants of it.
+	///
+	/// Returns [`None`] if the material isn't loaded, isn't a registered generic material, or couldn't be copied.
+	pub fn clone_material(&self, world: &World) -> Option<Box<dyn ErasedMaterial>> {
+		let current = self.get_from_world(world)?;
+
+		// The registered default is the only way we can create a new instance of an erased type.
+		let mut copy = world
+			.resource::<AppTypeRegistry>()
+			.read()
+			.get_type_data::<ReflectGenericMaterial>(self.inner().type_id())?
+			.default();
+		copy.try_apply(current.as_partial_reflect()).ok()?;
+
+		Some(copy)
+	}
+
+	/// Adds a copy of this material as a new asset, so it can be changed without affecting everything else using this material,
+	/// such as for damage flashes on a single entity. See also [`UniqueGenericMaterial`](crate::material_overrides::UniqueGenericMaterial).
+	///
+	/// Returns [`None`] under the same conditions as [`clone_material`](Self::clone_material).
+	pub fn make_unique(&self, world: &World) -> Option<Self> {
+		Some(self.clone_material(world)?.add_asset(world.resource::<AssetServer>()))
+	}
+
 	/// Runs a function on the reference to this asset grabbed from the world's appropriate [`Assets<...>`] collection
 	///
 	/// Passes the world through to the function to allow for mutable world access while having access to the material.
@@ -112,7 +297,44 @@ impl ErasedMaterialHandle {
 		(self.vtable.asset_scope_mut)(self.id(), world, f);
 	}
 
+	/// Applies every top-level field of `new` that differs from this material's current value, leaving the rest untouched.
+	///
+	/// This is used to hot reload materials in place, keeping this handle's identity intact. If the material isn't a struct, `new` is applied as a whole.
+	pub fn patch(&self, world: &mut World, new: Box<dyn Reflect>) {
+		self.asset_scope_mut(
+			world,
+			Box::new(move |_, material| {
+				let Some(material) = material else { return };
+
+				let result = if let (ReflectMut::Struct(old), ReflectRef::Struct(new)) = (material.reflect_mut(), new.reflect_ref()) {
+					let mut result = Ok(());
+
+					for (name, new_field) in new.iter_fields() {
+						let Some(old_field) = old.field_mut(name) else { continue };
+						if old_field.reflect_partial_eq(new_field) == Some(true) {
+							continue;
+						}
+
+						if let Err(err) = old_field.try_apply(new_field) {
+							result = Err(err);
+						}
+					}
+
+					result
+				} else {
+					material.try_apply(new.as_partial_reflect())
+				};
+
+				if let Err(err) = result {
+					error!("Failed to patch material {}: {err}", new.reflect_short_type_path());
+				}
+			}),
+		);
+	}
+
 	/// Attempts to modify a single field in the material. Writes an error out if something fails.
+	///
+	/// Only struct materials have fields, for other materials this does nothing but warn.
 	pub fn modify_field<T: Reflect + Typed + FromReflect + GetTypeRegistration>(
 		&self,
 		world: &mut World,
@@ -124,7 +346,13 @@ impl ErasedMaterialHandle {
 			world,
 			Box::new(move |_, material| {
 				let Some(material) = material else { return };
-				let ReflectMut::Struct(s) = material.reflect_mut() else { return };
+				let ReflectMut::Struct(s) = material.reflect_mut() else {
+					warn!(
+						"Tried to modify field {field_name} of {}, but only struct materials have fields",
+						material.reflect_short_type_path()
+					);
+					return;
+				};
 
 				let Some(field) = s.field_mut(&field_name) else {
 					error!(
@@ -153,49 +381,184 @@ impl ErasedMaterialHandle {
 
 #[allow(clippy::type_complexity)]
 struct ErasedMaterialHandleVTable {
-	insert: fn(UntypedHandle, EntityWorldMut),
-	remove: fn(EntityWorldMut),
+	targets: &'static [MaterialTarget],
+	insert: fn(UntypedHandle, MaterialTarget, EntityWorldMut),
+	remove: fn(&UntypedHandle, EntityWorldMut),
 	get_from_world: for<'w> fn(UntypedAssetId, &'w World) -> Option<&'w dyn Reflect>,
+	get_from_entity: fn(&UntypedHandle, &World, Entity) -> Option<ErasedMaterialHandle>,
 	asset_scope: fn(UntypedAssetId, &mut World, Box<dyn FnOnce(&mut World, Option<&dyn Reflect>) + Send + Sync>),
 	asset_scope_mut: fn(UntypedAssetId, &mut World, Box<dyn FnOnce(&mut World, Option<&mut dyn Reflect>) + Send + Sync>),
 }
+#[allow(clippy::type_complexity)]
 impl ErasedMaterialHandleVTable {
 	fn of<M: Material + Reflect>() -> &'static Self {
 		&Self {
-			insert: |handle, mut entity| {
+			targets: &[
+				MaterialTarget::Mesh3d,
+				#[cfg(feature = "meshlet")]
+				MaterialTarget::MeshletMesh3d,
+			],
+			insert: |handle, _target, mut entity| {
 				entity.insert(MeshMaterial3d::<M>(handle.typed_debug_checked()));
 			},
-			remove: |mut entity| {
+			remove: |_handle, mut entity| {
 				entity.remove::<MeshMaterial3d<M>>();
 			},
-			get_from_world: |id, world| {
-				let asset: &dyn Reflect = world.get_resource::<Assets<M>>()?.get(id.typed_debug_checked())?;
-				Some(asset)
+			get_from_world: Self::get_from_world::<M>,
+			get_from_entity: |_handle, world, entity| {
+				let material = world.get::<MeshMaterial3d<M>>(entity)?;
+				Some(ErasedMaterialHandle::with_vtable(material.0.clone(), Self::of::<M>()))
+			},
+			asset_scope: Self::asset_scope::<M>,
+			asset_scope_mut: Self::asset_scope_mut::<M>,
+		}
+	}
+
+	#[cfg(feature = "bevy_sprite_render")]
+	fn of_2d_and_3d<M: Material + bevy::sprite_render::Material2d + Reflect>() -> &'static Self {
+		use bevy::sprite_render::MeshMaterial2d;
+
+		&Self {
+			targets: &[
+				MaterialTarget::Mesh3d,
+				MaterialTarget::Mesh2d,
+				#[cfg(feature = "meshlet")]
+				MaterialTarget::MeshletMesh3d,
+			],
+			insert: |handle, target, mut entity| match target {
+				MaterialTarget::Mesh3d => {
+					entity.insert(MeshMaterial3d::<M>(handle.typed_debug_checked()));
+				}
+				MaterialTarget::Mesh2d => {
+					entity.insert(MeshMaterial2d::<M>(handle.typed_debug_checked()));
+				}
+				#[cfg(feature = "meshlet")]
+				MaterialTarget::MeshletMesh3d => {
+					entity.insert(MeshMaterial3d::<M>(handle.typed_debug_checked()));
+				}
+			},
+			remove: |_handle, mut entity| {
+				entity.remove::<(MeshMaterial3d<M>, MeshMaterial2d<M>)>();
+			},
+			get_from_world: Self::get_from_world::<M>,
+			get_from_entity: |_handle, world, entity| {
+				let handle = match world.get::<MeshMaterial3d<M>>(entity) {
+					Some(material) => material.0.clone(),
+					None => world.get::<MeshMaterial2d<M>>(entity)?.0.clone(),
+				};
+				Some(ErasedMaterialHandle::with_vtable(handle, Self::of_2d_and_3d::<M>()))
+			},
+			asset_scope: Self::asset_scope::<M>,
+			asset_scope_mut: Self::asset_scope_mut::<M>,
+		}
+	}
+
+	#[cfg(feature = "bevy_sprite_render")]
+	fn of_2d<M: bevy::sprite_render::Material2d + Reflect>() -> &'static Self {
+		use bevy::sprite_render::MeshMaterial2d;
+
+		&Self {
+			targets: &[MaterialTarget::Mesh2d],
+			insert: |handle, _target, mut entity| {
+				entity.insert(MeshMaterial2d::<M>(handle.typed_debug_checked()));
+			},
+			remove: |_handle, mut entity| {
+				entity.remove::<MeshMaterial2d<M>>();
+			},
+			get_from_world: Self::get_from_world::<M>,
+			get_from_entity: |_handle, world, entity| {
+				let material = world.get::<MeshMaterial2d<M>>(entity)?;
+				Some(ErasedMaterialHandle::with_vtable(material.0.clone(), Self::of_2d::<M>()))
 			},
-			asset_scope: |id, world, f| {
-				world.resource_scope(|world, assets: Mut<'_, Assets<M>>| {
-					let asset = assets.get(id.typed_debug_checked());
-					let asset: Option<&dyn Reflect> = match asset {
-						Some(m) => Some(m),
-						None => None,
-					};
-
-					f(world, asset);
-				});
+			asset_scope: Self::asset_scope::<M>,
+			asset_scope_mut: Self::asset_scope_mut::<M>,
+		}
+	}
+
+	#[cfg(feature = "skybox")]
+	fn of_skybox() -> &'static Self {
+		use crate::skybox::{SkyboxMaterial, SkyboxMaterial3d};
+		use bevy::light::{EnvironmentMapLight, Skybox};
+
+		&Self {
+			targets: &[],
+			insert: |handle, _target, mut entity| {
+				entity.insert(SkyboxMaterial3d(handle.typed_debug_checked()));
 			},
-			asset_scope_mut: |id, world, f| {
-				world.resource_scope(|world, mut assets: Mut<'_, Assets<M>>| {
-					let asset = assets.get_mut(id.typed_debug_checked());
-					let asset: Option<&mut dyn Reflect> = match asset {
-						Some(m) => Some(m.into_inner()),
-						None => None,
-					};
-
-					f(world, asset);
-				});
+			remove: |_handle, mut entity| {
+				entity.remove::<(SkyboxMaterial3d, Skybox, EnvironmentMapLight)>();
 			},
+			get_from_world: Self::get_from_world::<SkyboxMaterial>,
+			get_from_entity: |_handle, world, entity| {
+				let material = world.get::<SkyboxMaterial3d>(entity)?;
+				Some(ErasedMaterialHandle::with_vtable(material.0.clone(), Self::of_skybox()))
+			},
+			asset_scope: Self::asset_scope::<SkyboxMaterial>,
+			asset_scope_mut: Self::asset_scope_mut::<SkyboxMaterial>,
 		}
 	}
+
+	fn of_registry<M: Asset + Reflect>() -> &'static Self {
+		&Self {
+			targets: &[],
+			insert: |handle, _target, entity| {
+				let Some(handle) = Self::resolve(entity.world(), handle) else { return };
+				handle.insert(entity);
+			},
+			remove: |handle, entity| {
+				let Some(handle) = Self::resolve(entity.world(), handle.clone()) else { return };
+				handle.remove(entity);
+			},
+			get_from_world: Self::get_from_world::<M>,
+			get_from_entity: |handle, world, entity| Self::resolve(world, handle.clone())?.get_from_entity(world, entity),
+			asset_scope: Self::asset_scope::<M>,
+			asset_scope_mut: Self::asset_scope_mut::<M>,
+		}
+	}
+
+	/// Re-creates `handle` through its material's registered [`ReflectGenericMaterial`].
+	fn resolve(world: &World, handle: UntypedHandle) -> Option<ErasedMaterialHandle> {
+		let type_registry = world.resource::<AppTypeRegistry>().read();
+		let Some(reflect_generic_material) = type_registry.get_type_data::<ReflectGenericMaterial>(handle.type_id()) else {
+			error!("Tried to insert material {handle:?}, but its type isn't registered as a generic material");
+			return None;
+		};
+
+		Some((reflect_generic_material.new_handle)(handle))
+	}
+
+	fn get_from_world<M: Asset + Reflect>(id: UntypedAssetId, world: &World) -> Option<&dyn Reflect> {
+		let asset: &dyn Reflect = world.get_resource::<Assets<M>>()?.get(id.typed_debug_checked())?;
+		Some(asset)
+	}
+
+	fn asset_scope<M: Asset + Reflect>(id: UntypedAssetId, world: &mut World, f: Box<dyn FnOnce(&mut World, Option<&dyn Reflect>) + Send + Sync>) {
+		world.resource_scope(|world, assets: Mut<'_, Assets<M>>| {
+			let asset = assets.get(id.typed_debug_checked());
+			let asset: Option<&dyn Reflect> = match asset {
+				Some(m) => Some(m),
+				None => None,
+			};
+
+			f(world, asset);
+		});
+	}
+
+	fn asset_scope_mut<M: Asset + Reflect>(
+		id: UntypedAssetId,
+		world: &mut World,
+		f: Box<dyn FnOnce(&mut World, Option<&mut dyn Reflect>) + Send + Sync>,
+	) {
+		world.resource_scope(|world, mut assets: Mut<'_, Assets<M>>| {
+			let asset = assets.get_mut(id.typed_debug_checked());
+			let asset: Option<&mut dyn Reflect> = match asset {
+				Some(m) => Some(m.into_inner()),
+				None => None,
+			};
+
+			f(world, asset);
+		});
+	}
 }
 impl<M: Material + Reflect> From<Handle<M>> for ErasedMaterialHandle {
 	fn from(value: Handle<M>) -> Self {
@@ -207,3 +570,60 @@ impl fmt::Debug for ErasedMaterialHandle {
 		self.inner.fmt(f)
 	}
 }
+
+#[cfg(feature = "bevy_pbr")]
+#[test]
+fn downcast_handle() {
+	let handle = Handle::<StandardMaterial>::default();
+	let erased = ErasedMaterialHandle::new(handle.clone());
+
+	assert_eq!(erased.downcast_ref::<Handle<StandardMaterial>>(), Some(&handle));
+	assert!(erased.downcast_ref::<Handle<Image>>().is_none());
+	assert!(erased.downcast_ref::<StandardMaterial>().is_none());
+	assert!(erased.as_any().is::<Handle<StandardMaterial>>());
+}
+
+#[cfg(feature = "bevy_sprite_render")]
+#[test]
+fn insert_for_mesh_target() {
+	use bevy::{render::render_resource::AsBindGroup, sprite_render::MeshMaterial2d};
+
+	#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
+	struct FlatMaterial {
+		#[uniform(0)]
+		color: LinearRgba,
+	}
+	impl Material for FlatMaterial {}
+	impl bevy::sprite_render::Material2d for FlatMaterial {}
+
+	let mut world = World::new();
+	let handle = Handle::<FlatMaterial>::default();
+	let erased = ErasedMaterialHandle::new_2d_and_3d(handle.clone());
+
+	let entity_2d = world.spawn(Mesh2d::default()).id();
+	let entity_3d = world.spawn(Mesh3d::default()).id();
+	erased.clone().insert(world.entity_mut(entity_2d));
+	erased.clone().insert(world.entity_mut(entity_3d));
+
+	assert_eq!(
+		world.get::<MeshMaterial2d<FlatMaterial>>(entity_2d).map(|material| &material.0),
+		Some(&handle)
+	);
+	assert!(world.get::<MeshMaterial3d<FlatMaterial>>(entity_2d).is_none());
+	assert_eq!(
+		world.get::<MeshMaterial3d<FlatMaterial>>(entity_3d).map(|material| &material.0),
+		Some(&handle)
+	);
+	assert!(world.get::<MeshMaterial2d<FlatMaterial>>(entity_3d).is_none());
+
+	// 2D-only materials fall back to their 2D component, even on 3D meshes.
+	let color_material = Handle::<ColorMaterial>::default();
+	ErasedMaterialHandle::new_2d(color_material.clone()).insert(world.entity_mut(entity_3d));
+	assert_eq!(
+		world.get::<MeshMaterial2d<ColorMaterial>>(entity_3d).map(|material| &material.0),
+		Some(&color_material)
+	);
+
+	erased.remove(world.entity_mut(entity_2d));
+	assert!(world.get::<MeshMaterial2d<FlatMaterial>>(entity_2d).is_none());
+}
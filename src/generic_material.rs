@@ -1,18 +1,35 @@
+use std::borrow::Cow;
 use std::sync::{Arc, RwLock};
 
-use bevy::{platform::collections::HashMap, prelude::*, reflect::TypeRegistration};
+#[cfg(feature = "bevy_pbr")]
+use bevy::reflect::ReflectRef;
+use bevy::{
+	asset::{AssetMut, AssetPath},
+	ecs::system::SystemParam,
+	platform::collections::HashMap,
+	prelude::*,
+	reflect::TypeRegistration,
+};
 
 #[cfg(feature = "bevy_pbr")]
 use bevy::ecs::{lifecycle::HookContext, world::DeferredWorld};
 
 #[cfg(feature = "bevy_pbr")]
-use crate::erased_material::{ErasedMaterial, ErasedMaterialHandle};
+use bevy::asset::LoadContext;
+
+#[cfg(feature = "bevy_pbr")]
+use crate::{
+	GenericMaterialLoadFallback,
+	erased_material::{ErasedMaterial, ErasedMaterialHandle},
+};
 
 use crate::{material_property::GetPropertyError, prelude::MaterialProperty};
 
 /// Generic version of [`MeshMaterial3d`]. Stores a handle to a [`GenericMaterial`].
 ///
 /// When on an entity, this automatically inserts the appropriate [`MeshMaterial3d`].
+/// If the entity has a `Mesh2d` and the material was registered for 2D (see [`MaterializeAppExt::register_generic_material_2d_and_3d`](crate::MaterializeAppExt)),
+/// `MeshMaterial2d` is inserted instead.
 ///
 /// When removing or replacing this component, the inserted [`MeshMaterial3d`] will be removed.
 #[derive(Component, Reflect, Debug, Clone, PartialEq, Eq, Default, Deref, DerefMut)]
@@ -21,27 +38,70 @@ use crate::{material_property::GetPropertyError, prelude::MaterialProperty};
 pub struct GenericMaterial3d(pub Handle<GenericMaterial>);
 impl GenericMaterial3d {
 	#[cfg(feature = "bevy_pbr")]
-	fn on_discard(mut world: DeferredWorld, ctx: HookContext) {
-		let generic_material_handle = &world.entity(ctx.entity).get::<Self>().unwrap().0;
-		let Some(generic_material) = world.resource::<Assets<GenericMaterial>>().get(generic_material_handle) else { return };
-		let material_handle = generic_material.handle.clone();
-
-		world.commands().queue(move |world: &mut World| {
-			let Ok(mut entity) = world.get_entity_mut(ctx.entity) else { return };
+	fn on_discard(world: DeferredWorld, ctx: HookContext) {
+		let generic_material_handle = world.entity(ctx.entity).get::<Self>().unwrap().0.clone();
+		discard_generic_material(world, ctx.entity, &generic_material_handle);
+	}
+}
 
-			entity.remove::<GenericMaterialApplied>();
-			material_handle.remove(entity);
-		});
+/// 2D version of [`GenericMaterial3d`]. Stores a handle to a [`GenericMaterial`].
+///
+/// When on an entity, this automatically inserts the appropriate `MeshMaterial2d`, so the material must be registered for 2D
+/// (see [`MaterializeAppExt::register_generic_material_2d`](crate::MaterializeAppExt)).
+///
+/// When removing or replacing this component, the inserted `MeshMaterial2d` will be removed.
+#[cfg(feature = "bevy_sprite_render")]
+#[derive(Component, Reflect, Debug, Clone, PartialEq, Eq, Default, Deref, DerefMut)]
+#[component(on_discard = Self::on_discard)]
+#[reflect(Component, Default)]
+pub struct GenericMaterial2d(pub Handle<GenericMaterial>);
+#[cfg(feature = "bevy_sprite_render")]
+impl GenericMaterial2d {
+	fn on_discard(world: DeferredWorld, ctx: HookContext) {
+		let generic_material_handle = world.entity(ctx.entity).get::<Self>().unwrap().0.clone();
+		discard_generic_material(world, ctx.entity, &generic_material_handle);
 	}
 }
 
-/// Automatically put on entities when their [`GenericMaterial3d`] inserts [`MeshMaterial3d`].
+/// Removes the material that a [`GenericMaterial3d`] or [`GenericMaterial2d`] applied to `entity`.
+#[cfg(feature = "bevy_pbr")]
+fn discard_generic_material(mut world: DeferredWorld, entity: Entity, generic_material_handle: &Handle<GenericMaterial>) {
+	// This world might not have materials set up at all, e.g. a headless simulation world.
+	let Some(generic_materials) = world.get_resource::<Assets<GenericMaterial>>() else { return };
+	let Some(generic_material) = generic_materials.get(generic_material_handle).or_else(|| {
+		// The material may have failed to load, and the entity been given the fallback instead.
+		generic_materials.get(&world.get_resource::<GenericMaterialLoadFallback>()?.0)
+	}) else {
+		return;
+	};
+	let material_handle = generic_material.handle.clone();
+
+	world.commands().queue(move |world: &mut World| {
+		let Ok(mut entity) = world.get_entity_mut(entity) else { return };
+
+		entity.remove::<(GenericMaterialApplied, ExtractedGenericMaterial)>();
+		material_handle.remove(entity);
+	});
+}
+
+/// Automatically put on entities when their [`GenericMaterial3d`] inserts [`MeshMaterial3d`], or their `GenericMaterial2d` inserts `MeshMaterial2d`.
 /// This is required because [`MeshMaterial3d`] is generic, and as such can't be used in query parameters for generic materials.
 #[cfg(feature = "bevy_pbr")]
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct GenericMaterialApplied;
 
+/// Plain-data description of the material a [`GenericMaterial3d`] was applied with, inserted alongside [`GenericMaterialApplied`].
+///
+/// Unlike [`GenericMaterial3d`], reading this doesn't require access to [`Assets<GenericMaterial>`], making it friendly to extraction into the render world or other worlds.
+#[cfg(feature = "bevy_pbr")]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractedGenericMaterial {
+	pub generic_material: AssetId<GenericMaterial>,
+	/// The id of the inner material asset, its type can be retrieved via [`UntypedAssetId::type_id`](bevy::asset::UntypedAssetId::type_id).
+	pub material: bevy::asset::UntypedAssetId,
+}
+
 /// Material asset containing a type-erased material handle, and arbitrary user-defined properties.
 #[derive(Asset, TypePath, Debug)]
 #[cfg_attr(not(feature = "bevy_pbr"), derive(Default))]
@@ -49,19 +109,42 @@ pub struct GenericMaterial {
 	#[cfg(feature = "bevy_pbr")]
 	pub handle: ErasedMaterialHandle,
 	pub properties: HashMap<String, Box<dyn Reflect>>,
+	/// Stable identifier set with the `id` key of the material file (e.g. `id = "world.stone_floor"`), indexed by [`GenericMaterialIds`].
+	///
+	/// Game data can refer to materials by id rather than by path, so moving or renaming a material file only requires changing the file itself.
+	pub id: Option<String>,
+	/// Values cached by [`get_or_compute_property`](Self::get_or_compute_property), see [`clear_computed_properties`](Self::clear_computed_properties).
+	pub(crate) computed: RwLock<HashMap<String, Box<dyn Reflect>>>,
 }
 impl GenericMaterial {
 	#[cfg(feature = "bevy_pbr")]
 	pub fn new(handle: impl Into<ErasedMaterialHandle>) -> Self {
 		Self {
 			handle: handle.into(),
-			properties: HashMap::default(),
+			properties: default(),
+			id: None,
+			computed: default(),
 		}
 	}
 
-	/// Sets a property to `value`.
+	/// Inserts this material's mesh material component on an entity directly, for exclusive systems and tests.
+	///
+	/// Unlike [`GenericMaterial3d`], this doesn't wait for the scheduled systems, insert [`GenericMaterialApplied`], or apply properties.
+	#[cfg(feature = "bevy_pbr")]
+	pub fn apply_to(&self, entity: EntityWorldMut) {
+		self.handle.clone().insert(entity);
+	}
+
+	/// Removes the mesh material component [`apply_to`](Self::apply_to) inserted from an entity.
+	#[cfg(feature = "bevy_pbr")]
+	pub fn remove_from(&self, entity: EntityWorldMut) {
+		self.handle.remove(entity);
+	}
+
+	/// Sets a property to `value`. Values computed by [`get_or_compute_property`](Self::get_or_compute_property) are cleared, as they may depend on it.
 	pub fn set_property_manual<T: Reflect>(&mut self, key: impl Into<String>, value: T) {
 		self.properties.insert(key.into(), Box::new(value));
+		self.computed.get_mut().unwrap().clear();
 	}
 
 	/// Sets a property to `value`.
@@ -81,6 +164,357 @@ impl GenericMaterial {
 	pub fn get_property<T: Reflect>(&self, property: MaterialProperty<T>) -> Result<&T, GetPropertyError> {
 		self.get_property_manual(property.key)
 	}
+
+	/// Gets the specified property, or computes it with `compute` if this material doesn't have it, for derived per-material data.
+	///
+	/// Computed values are cached on the material, so `compute` only runs once per material (or until the cache is cleared, see [`clear_computed_properties`](Self::clear_computed_properties)).
+	/// Because the cache is shared, computed values are cloned out of it rather than borrowed.
+	///
+	/// # Examples
+	/// ```ignore
+	/// let footsteps = generic_material.get_or_compute_property_manual("footsteps", |material| {
+	///     let sounds = material.get_property(SOUNDS).map(String::as_str).unwrap_or_default();
+	///     sounds.split(',').map(str::to_string).collect::<Vec<_>>()
+	/// });
+	/// ```
+	pub fn get_or_compute_property_manual<T: Reflect + Clone>(&self, key: &str, compute: impl FnOnce(&Self) -> T) -> Cow<'_, T> {
+		if let Ok(value) = self.get_property_manual(key) {
+			return Cow::Borrowed(value);
+		}
+
+		if let Some(value) = self.computed.read().unwrap().get(key).and_then(|value| value.downcast_ref::<T>()) {
+			return Cow::Owned(value.clone());
+		}
+
+		let value = compute(self);
+		self.computed.write().unwrap().insert(key.to_string(), Box::new(value.clone()));
+		Cow::Owned(value)
+	}
+
+	/// Gets the specified property, or computes it with `compute` if this material doesn't have it.
+	/// See [`get_or_compute_property_manual`](Self::get_or_compute_property_manual).
+	pub fn get_or_compute_property<T: Reflect + Clone>(&self, property: MaterialProperty<T>, compute: impl FnOnce(&Self) -> T) -> Cow<'_, T> {
+		self.get_or_compute_property_manual(property.key, compute)
+	}
+
+	/// Clears the values cached by [`get_or_compute_property`](Self::get_or_compute_property).
+	///
+	/// This is done by [`set_property`](Self::set_property) and [`GenericMaterialsMut::get_mut`],
+	/// but editing [`properties`](Self::properties) directly requires calling this if computed values depend on them.
+	pub fn clear_computed_properties(&self) {
+		self.computed.write().unwrap().clear();
+	}
+
+	/// Returns every `Handle<Image>` and `Option<Handle<Image>>` field of this material that holds an image, along with the field's name.
+	///
+	/// Fields of nested structs (such as in [`ExtendedMaterial`](bevy::pbr::ExtendedMaterial)) are included, their names separated with `.` (e.g. `base.base_color_texture`).
+	///
+	/// Returns an empty list if the material isn't loaded.
+	#[cfg(feature = "bevy_pbr")]
+	pub fn texture_fields(&self, world: &World) -> Vec<(String, Handle<Image>)> {
+		fn collect(prefix: &str, value: &dyn PartialReflect, out: &mut Vec<(String, Handle<Image>)>) {
+			let ReflectRef::Struct(s) = value.reflect_ref() else { return };
+
+			for (name, field) in s.iter_fields() {
+				let name = if prefix.is_empty() {
+					name.to_string()
+				} else {
+					format!("{prefix}.{name}")
+				};
+
+				if let Some(handle) = field.try_downcast_ref::<Handle<Image>>() {
+					out.push((name, handle.clone()));
+				} else if let Some(Some(handle)) = field.try_downcast_ref::<Option<Handle<Image>>>() {
+					out.push((name, handle.clone()));
+				} else {
+					collect(&name, field, out);
+				}
+			}
+		}
+
+		let mut out = Vec::new();
+		if let Some(material) = self.handle.get_from_world(world) {
+			collect("", material.as_partial_reflect(), &mut out);
+		}
+		out
+	}
+
+	/// Returns the top-level fields of this material, for building things like automatic property editors without knowing the material's type.
+	///
+	/// Returns an empty list if the material isn't loaded. If you already have the material itself, use [`MaterialField::of`].
+	#[cfg(feature = "bevy_pbr")]
+	pub fn fields<'w>(&self, world: &'w World) -> Vec<MaterialField<'w>> {
+		match self.handle.get_from_world(world) {
+			Some(material) => MaterialField::of(material.as_partial_reflect()),
+			None => Vec::new(),
+		}
+	}
+}
+
+/// Builds a [`GenericMaterial`] from code.
+///
+/// # Examples
+/// ```ignore
+/// let handle = GenericMaterialBuilder::new(StandardMaterial { perceptual_roughness: 1., ..default() })
+///     .property(GenericMaterial::VISIBILITY, Visibility::Hidden)
+///     .add(&asset_server);
+/// ```
+#[cfg(feature = "bevy_pbr")]
+pub struct GenericMaterialBuilder {
+	pub material: Box<dyn ErasedMaterial>,
+	pub properties: HashMap<String, Box<dyn Reflect>>,
+}
+#[cfg(feature = "bevy_pbr")]
+impl GenericMaterialBuilder {
+	pub fn new(material: impl Into<Box<dyn ErasedMaterial>>) -> Self {
+		Self {
+			material: material.into(),
+			properties: HashMap::default(),
+		}
+	}
+
+	/// Sets a property to `value`.
+	pub fn property<T: Reflect>(self, property: MaterialProperty<T>, value: T) -> Self {
+		self.property_manual(property.key, value)
+	}
+
+	/// Sets the property under `key` to `value`.
+	pub fn property_manual<T: Reflect>(mut self, key: impl Into<String>, value: T) -> Self {
+		self.properties.insert(key.into(), Box::new(value));
+		self
+	}
+
+	/// Adds the material to the asset server, and returns the generic material holding it.
+	pub fn build(self, asset_server: &AssetServer) -> GenericMaterial {
+		GenericMaterial {
+			handle: self.material.add_asset(asset_server),
+			properties: self.properties,
+			id: None,
+			computed: default(),
+		}
+	}
+
+	/// Builds the generic material and adds it to the asset server too.
+	pub fn add(self, asset_server: &AssetServer) -> Handle<GenericMaterial> {
+		asset_server.add(self.build(asset_server))
+	}
+}
+
+/// System parameter for looking up [`GenericMaterial`]s by their asset path as well as their id.
+///
+/// # Examples
+/// ```ignore
+/// fn system(generic_materials: GenericMaterials) {
+///     if let Some(bricks) = generic_materials.get_by_path("materials/bricks.toml") {
+///         // ...
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct GenericMaterials<'w> {
+	pub assets: Res<'w, Assets<GenericMaterial>>,
+	pub asset_server: Res<'w, AssetServer>,
+	pub ids: Res<'w, GenericMaterialIds>,
+}
+impl GenericMaterials<'_> {
+	/// Returns the generic material with the specified id, if it's loaded.
+	pub fn get(&self, id: impl Into<AssetId<GenericMaterial>>) -> Option<&GenericMaterial> {
+		self.assets.get(id)
+	}
+
+	/// Returns the generic material loaded from `path`, if it's loaded.
+	pub fn get_by_path<'a>(&self, path: impl Into<AssetPath<'a>>) -> Option<&GenericMaterial> {
+		self.assets.get(&self.asset_server.get_handle::<GenericMaterial>(path)?)
+	}
+
+	/// Returns the loaded generic material whose [`id`](GenericMaterial::id) is `material_id`.
+	pub fn get_by_material_id(&self, material_id: &str) -> Option<&GenericMaterial> {
+		self.assets.get(self.ids.get(material_id)?)
+	}
+
+	/// Returns a handle to the loaded generic material whose [`id`](GenericMaterial::id) is `material_id`, e.g. to put in a [`GenericMaterial3d`].
+	pub fn handle_by_material_id(&self, material_id: &str) -> Option<Handle<GenericMaterial>> {
+		self.asset_server.get_id_handle(self.ids.get(material_id)?)
+	}
+
+	/// Iterates over every loaded generic material that was loaded from a path, along with that path.
+	pub fn iter_with_paths(&self) -> impl Iterator<Item = (AssetPath<'static>, &GenericMaterial)> {
+		self.assets
+			.iter()
+			.filter_map(|(id, generic_material)| Some((self.asset_server.get_path(id)?.into_owned(), generic_material)))
+	}
+}
+
+/// Index of loaded generic materials by their [`id`](GenericMaterial::id), kept up to date by [`index_generic_material_ids`].
+///
+/// Only materials that have been loaded are indexed, so materials referred to by id need to be loaded some other way first,
+/// e.g. with a [`GenericMaterialSet`](crate::load::set::GenericMaterialSet) or by loading a folder.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct GenericMaterialIds {
+	pub ids: HashMap<String, AssetId<GenericMaterial>>,
+}
+impl GenericMaterialIds {
+	/// Returns the asset id of the loaded generic material whose [`id`](GenericMaterial::id) is `material_id`.
+	pub fn get(&self, material_id: &str) -> Option<AssetId<GenericMaterial>> {
+		self.ids.get(material_id).copied()
+	}
+}
+
+/// Updates [`GenericMaterialIds`] as generic materials are loaded, reloaded, and removed.
+///
+/// If multiple materials have the same id, the last one loaded is indexed. When it's removed or its id changes, one of the others takes its place.
+pub fn index_generic_material_ids(
+	mut asset_events: MessageReader<AssetEvent<GenericMaterial>>,
+	generic_materials: Res<Assets<GenericMaterial>>,
+	mut ids: ResMut<GenericMaterialIds>,
+) {
+	for event in asset_events.read() {
+		match *event {
+			AssetEvent::Added { id: asset_id } | AssetEvent::Modified { id: asset_id } => {
+				// The id may have changed or been removed when reloading.
+				unindex_generic_material_id(&mut ids, &generic_materials, asset_id);
+
+				let Some(generic_material) = generic_materials.get(asset_id) else { continue };
+				let Some(material_id) = &generic_material.id else { continue };
+
+				if let Some(previous) = ids.ids.insert(material_id.clone(), asset_id) {
+					warn!("Generic materials {previous} and {asset_id} both have the id `{material_id}`, using the latter");
+				}
+			}
+			AssetEvent::Removed { id: asset_id } => unindex_generic_material_id(&mut ids, &generic_materials, asset_id),
+			_ => {}
+		}
+	}
+}
+
+/// Removes the id of `asset_id` from the index, giving it back to another loaded material with the same id, if any.
+fn unindex_generic_material_id(ids: &mut GenericMaterialIds, generic_materials: &Assets<GenericMaterial>, asset_id: AssetId<GenericMaterial>) {
+	let Some(material_id) = ids
+		.ids
+		.iter()
+		.find(|(_, indexed)| **indexed == asset_id)
+		.map(|(material_id, _)| material_id.clone())
+	else {
+		return;
+	};
+	ids.ids.remove(&material_id);
+
+	let duplicate = generic_materials
+		.iter()
+		.find(|(other, generic_material)| *other != asset_id && generic_material.id.as_ref() == Some(&material_id));
+	if let Some((other, _)) = duplicate {
+		ids.ids.insert(material_id, other);
+	}
+}
+
+/// Mutable version of [`GenericMaterials`].
+///
+/// Materials returned by [`get_mut`](Self::get_mut) are marked as modified when mutably dereferenced,
+/// so systems reacting to reloads (e.g. re-applying properties or restarting animations) also pick up edits made here.
+///
+/// # Examples
+/// ```ignore
+/// fn system(mut generic_materials: GenericMaterialsMut) {
+///     if let Some(mut bricks) = generic_materials.get_mut_by_path("materials/bricks.toml") {
+///         bricks.set_property(GenericMaterial::VISIBILITY, Visibility::Hidden);
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct GenericMaterialsMut<'w> {
+	pub assets: ResMut<'w, Assets<GenericMaterial>>,
+	pub asset_server: Res<'w, AssetServer>,
+}
+impl GenericMaterialsMut<'_> {
+	/// Returns the generic material with the specified id, if it's loaded.
+	pub fn get(&self, id: impl Into<AssetId<GenericMaterial>>) -> Option<&GenericMaterial> {
+		self.assets.get(id)
+	}
+
+	/// Returns the generic material loaded from `path`, if it's loaded.
+	pub fn get_by_path<'a>(&self, path: impl Into<AssetPath<'a>>) -> Option<&GenericMaterial> {
+		self.assets.get(&self.asset_server.get_handle::<GenericMaterial>(path)?)
+	}
+
+	/// Returns the generic material with the specified id for editing, if it's loaded. The material is marked as modified if it's mutably dereferenced.
+	///
+	/// Values cached by [`GenericMaterial::get_or_compute_property`] are cleared, as they may depend on whatever is edited.
+	pub fn get_mut(&mut self, id: impl Into<AssetId<GenericMaterial>>) -> Option<AssetMut<'_, GenericMaterial>> {
+		let generic_material = self.assets.get_mut(id)?;
+		// Through a shared reference, so the material isn't marked as modified unless it's actually edited.
+		generic_material.clear_computed_properties();
+		Some(generic_material)
+	}
+
+	/// Returns the generic material loaded from `path` for editing, if it's loaded. The material is marked as modified if it's mutably dereferenced.
+	pub fn get_mut_by_path<'a>(&mut self, path: impl Into<AssetPath<'a>>) -> Option<AssetMut<'_, GenericMaterial>> {
+		let handle = self.asset_server.get_handle::<GenericMaterial>(path)?;
+		self.get_mut(&handle)
+	}
+}
+
+/// System parameter for reading the [`GenericMaterial`]s of entities with [`GenericMaterial3d`], and their properties.
+///
+/// # Examples
+/// ```ignore
+/// fn system(generic_materials: GenericMaterialQuery, players: Query<&FloorBelow, With<Player>>) {
+///     for floor in &players {
+///         if let Ok(sounds) = generic_materials.property_of(floor.0, SOUNDS) {
+///             // ...
+///         }
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct GenericMaterialQuery<'w, 's> {
+	pub generic_materials: GenericMaterials<'w>,
+	pub query: Query<'w, 's, &'static GenericMaterial3d>,
+}
+impl GenericMaterialQuery<'_, '_> {
+	/// Returns the generic material of `entity`, if it has one and it's loaded.
+	pub fn get(&self, entity: Entity) -> Option<&GenericMaterial> {
+		self.generic_materials.get(&self.query.get(entity).ok()?.0)
+	}
+
+	/// Gets the specified property of the generic material of `entity`.
+	pub fn property_of<T: Reflect>(&self, entity: Entity, property: MaterialProperty<T>) -> Result<&T, GetPropertyError> {
+		self.get(entity).ok_or(GetPropertyError::NoMaterial)?.get_property(property)
+	}
+}
+
+/// A reflected field of a material, see [`GenericMaterial::fields`].
+#[cfg(feature = "bevy_pbr")]
+#[derive(Debug, Clone)]
+pub struct MaterialField<'a> {
+	/// The name of the field, or its index for tuple fields.
+	pub name: Cow<'a, str>,
+	pub type_path: &'a str,
+	pub value: &'a dyn PartialReflect,
+}
+#[cfg(feature = "bevy_pbr")]
+impl<'a> MaterialField<'a> {
+	/// Returns the fields of a struct, tuple struct, or the current variant of an enum. Other kinds of values have no fields.
+	pub fn of(material: &'a dyn PartialReflect) -> Vec<Self> {
+		let field = |name: Cow<'a, str>, value: &'a dyn PartialReflect| Self {
+			name,
+			type_path: value.reflect_type_path(),
+			value,
+		};
+
+		match material.reflect_ref() {
+			ReflectRef::Struct(s) => s.iter_fields().map(|(name, value)| field(name.into(), value)).collect(),
+			ReflectRef::TupleStruct(s) => s.iter_fields().enumerate().map(|(i, value)| field(i.to_string().into(), value)).collect(),
+			ReflectRef::Enum(e) => e
+				.iter_fields()
+				.enumerate()
+				.map(|(i, variant_field)| {
+					let name = variant_field.name().map_or_else(|| i.to_string().into(), Cow::Borrowed);
+					field(name, variant_field.value())
+				})
+				.collect(),
+			_ => Vec::new(),
+		}
+	}
 }
 
 /// Stores a default value of a certain material that is cloned whenever a new copy of said material is needed to load a [`GenericMaterial`].
@@ -88,16 +522,36 @@ impl GenericMaterial {
 #[derive(Clone)]
 pub struct ReflectGenericMaterial {
 	pub(crate) default_value: Box<dyn ErasedMaterial>,
+	/// Creates handles to this material that insert the mesh material components it was registered for, such as [`MeshMaterial3d`].
+	pub(crate) new_handle: fn(UntypedHandle) -> ErasedMaterialHandle,
 }
 #[cfg(feature = "bevy_pbr")]
 impl ReflectGenericMaterial {
 	pub fn default(&self) -> Box<dyn ErasedMaterial> {
 		self.default_value.clone_erased()
 	}
+
+	/// Adds `material` as a labeled asset, producing a handle that targets every kind of mesh this material was registered for.
+	pub fn add_labeled_asset(&self, material: Box<dyn ErasedMaterial>, load_context: &mut LoadContext, label: String) -> ErasedMaterialHandle {
+		(self.new_handle)(material.add_labeled_asset(load_context, label).take_inner())
+	}
 }
 
 /// Collection of material type name shorthands for use loading by [`GenericMaterial`]s.
+///
+/// When loading, shorthands take priority over type paths, so a shorthand can never be ambiguous with a type's short path.
 #[derive(Resource, Debug, Clone, Default)]
 pub struct GenericMaterialShorthands {
 	pub values: Arc<RwLock<HashMap<String, TypeRegistration>>>,
 }
+impl GenericMaterialShorthands {
+	/// Sets `shorthand` to refer to `registration`, returning the registration it previously referred to, if any.
+	pub fn insert(&self, shorthand: impl Into<String>, registration: TypeRegistration) -> Option<TypeRegistration> {
+		self.values.write().unwrap().insert(shorthand.into(), registration)
+	}
+
+	/// Removes `shorthand`, returning the registration it referred to, if any.
+	pub fn remove(&self, shorthand: &str) -> Option<TypeRegistration> {
+		self.values.write().unwrap().remove(shorthand)
+	}
+}
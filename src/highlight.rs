@@ -0,0 +1,129 @@
+use bevy::{prelude::*, reflect::ReflectMut};
+
+use crate::{erased_material::ErasedMaterialHandle, prelude::*};
+
+impl GenericMaterial {
+	/// Material property that controls whether [`highlight_generic_material`](HighlightCommandsExt::highlight_generic_material) affects entities using this material. (Default: `true`)
+	pub const HIGHLIGHTABLE: MaterialProperty<bool> = MaterialProperty::new("highlightable");
+}
+
+/// Placed on entities highlighted via [`HighlightCommandsExt`], storing the material to restore when the highlight is cleared.
+#[derive(Component, Debug, Clone)]
+pub struct GenericMaterialHighlight {
+	/// The material the entity had before it was highlighted.
+	pub original: ErasedMaterialHandle,
+	/// The tinted copy of [`original`](Self::original) the entity was given.
+	pub highlighted: ErasedMaterialHandle,
+	pub color: Color,
+}
+
+pub trait HighlightCommandsExt {
+	/// Replaces the material of an entity with a [`GenericMaterial3d`] with a copy tinted with `color`, for selection or hover effects.
+	///
+	/// The copy's `emissive` field is set if it has one, otherwise `base_color` or `color`.
+	/// This does nothing if the material's [`HIGHLIGHTABLE`](GenericMaterial::HIGHLIGHTABLE) property is `false`.
+	///
+	/// Use [`clear_generic_material_highlight`](HighlightCommandsExt::clear_generic_material_highlight) to restore the original material.
+	/// If the entity's material is replaced while highlighted, such as when it's hot-reloaded, the new material is highlighted instead.
+	fn highlight_generic_material(&mut self, entity: Entity, color: Color);
+
+	/// Restores the material of an entity highlighted with [`highlight_generic_material`](HighlightCommandsExt::highlight_generic_material).
+	fn clear_generic_material_highlight(&mut self, entity: Entity);
+}
+impl HighlightCommandsExt for Commands<'_, '_> {
+	fn highlight_generic_material(&mut self, entity: Entity, color: Color) {
+		self.queue(move |world: &mut World| highlight_generic_material(world, entity, color));
+	}
+
+	fn clear_generic_material_highlight(&mut self, entity: Entity) {
+		self.queue(move |world: &mut World| {
+			let Ok(mut entity) = world.get_entity_mut(entity) else { return };
+			let Some(highlight) = entity.take::<GenericMaterialHighlight>() else { return };
+
+			highlight.original.insert(entity);
+		});
+	}
+}
+
+fn highlight_generic_material(world: &mut World, entity: Entity, color: Color) {
+	let Some(generic_material_3d) = world.get::<GenericMaterial3d>(entity) else { return };
+	let Some(generic_material) = world.resource::<Assets<GenericMaterial>>().get(&generic_material_3d.0) else { return };
+
+	if matches!(generic_material.get_property(GenericMaterial::HIGHLIGHTABLE), Ok(&false)) {
+		return;
+	}
+
+	// If already highlighted, tint the original again rather than stacking tints.
+	// Otherwise the entity's own material is used, which may be a unique copy of the generic material's.
+	let original = match world.get::<GenericMaterialHighlight>(entity) {
+		Some(highlight) => highlight.original.clone(),
+		None => generic_material
+			.handle
+			.get_from_entity(world, entity)
+			.unwrap_or_else(|| generic_material.handle.clone()),
+	};
+
+	let Some(mut copy) = original.clone_material(world) else {
+		error!("Failed to copy {original:?} for highlighting, is it a registered generic material?");
+		return;
+	};
+
+	if !tint(copy.as_reflect_mut(), color) {
+		warn!(
+			"Tried to highlight {}, but it has no `emissive`, `base_color`, or `color` field",
+			copy.reflect_short_type_path()
+		);
+		return;
+	}
+
+	let handle = copy.add_asset(world.resource::<AssetServer>());
+
+	let Ok(mut entity) = world.get_entity_mut(entity) else { return };
+	entity.insert(GenericMaterialHighlight {
+		original,
+		highlighted: handle.clone(),
+		color,
+	});
+	handle.insert(entity);
+}
+
+/// Highlights entities again if their material was replaced while highlighted, such as when it's hot-reloaded.
+pub fn reapply_generic_material_highlights(world: &mut World) {
+	let replaced: Vec<(Entity, Color)> = world
+		.query::<(Entity, &GenericMaterialHighlight)>()
+		.iter(world)
+		.filter(|(entity, highlight)| {
+			highlight
+				.highlighted
+				.get_from_entity(world, *entity)
+				.is_none_or(|current| current.id() != highlight.highlighted.id())
+		})
+		.map(|(entity, highlight)| (entity, highlight.color))
+		.collect();
+
+	for (entity, color) in replaced {
+		world.entity_mut(entity).remove::<GenericMaterialHighlight>();
+		highlight_generic_material(world, entity, color);
+	}
+}
+
+/// Sets the first color field of a material found to `color`, returning whether one was found.
+fn tint(material: &mut dyn Reflect, color: Color) -> bool {
+	let ReflectMut::Struct(s) = material.reflect_mut() else { return false };
+
+	if let Some(emissive) = s.field_mut("emissive")
+		&& emissive.try_apply(&color.to_linear()).is_ok()
+	{
+		return true;
+	}
+
+	for field_name in ["base_color", "color"] {
+		if let Some(field) = s.field_mut(field_name)
+			&& field.try_apply(&color).is_ok()
+		{
+			return true;
+		}
+	}
+
+	false
+}
@@ -0,0 +1,51 @@
+#import bevy_pbr::{
+	mesh_functions,
+	pbr_fragment::pbr_input_from_standard_material,
+	pbr_functions::alpha_discard,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+	prepass_io::{VertexOutput, FragmentOutput},
+	pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+	forward_io::{VertexOutput, FragmentOutput},
+	pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+
+struct InstanceTintMaterial {
+	strength: f32,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> material: InstanceTintMaterial;
+
+@fragment
+fn fragment(
+	in: VertexOutput,
+	@builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+	var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+	// The tag holds a packed linear RGBA8 tint, 0 meaning untinted.
+	let tag = mesh_functions::get_tag(in.instance_index);
+	if tag != 0u {
+		pbr_input.material.base_color *= mix(vec4(1.0), unpack4x8unorm(tag), material.strength);
+	}
+#endif
+
+	pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+	let out = deferred_output(in, pbr_input);
+#else
+	var out: FragmentOutput;
+	out.color = apply_pbr_lighting(pbr_input);
+	out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+	return out;
+}
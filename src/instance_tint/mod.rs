@@ -0,0 +1,134 @@
+use bevy::{
+	asset::embedded_asset,
+	color::{ColorToComponents, Mix},
+	mesh::MeshTag,
+	pbr::{ExtendedMaterial, MaterialExtension},
+	prelude::*,
+	render::render_resource::AsBindGroup,
+	shader::ShaderRef,
+};
+
+use crate::{MaterializeAppExt, generic_material::GenericMaterialApplied, prelude::*};
+
+/// [`StandardMaterial`] that multiplies its base color by a per-entity tint, so many entities can share one material while varying in color.
+///
+/// The tint is read from the entity's [`MeshTag`], which is written from [`InstanceTint`] or the material's
+/// [`INSTANCE_TINT`](GenericMaterial::INSTANCE_TINT) property. Since instances share a material, they still batch together.
+///
+/// Registered with the shorthand `InstanceTintedMaterial` by [`InstanceTintPlugin`].
+///
+/// # Examples
+/// ```toml
+/// type = "InstanceTintedMaterial"
+///
+/// [material.base]
+/// base_color_texture = "crate.png"
+///
+/// [properties.instance_tint]
+/// from = { Srgba = { red = 0.8, green = 0.7, blue = 0.6, alpha = 1.0 } }
+/// to = { Srgba = { red = 1.0, green = 1.0, blue = 1.0, alpha = 1.0 } }
+/// ```
+pub type InstanceTintedMaterial = ExtendedMaterial<StandardMaterial, InstanceTintExtension>;
+
+/// The extension part of [`InstanceTintedMaterial`].
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct InstanceTintExtension {
+	/// How much the tint affects the base color, from `0` to `1`. (Default: `1`)
+	#[uniform(100)]
+	pub strength: f32,
+}
+impl Default for InstanceTintExtension {
+	fn default() -> Self {
+		Self { strength: 1. }
+	}
+}
+impl MaterialExtension for InstanceTintExtension {
+	fn fragment_shader() -> ShaderRef {
+		"embedded://bevy_materialize/instance_tint/instance_tint.wgsl".into()
+	}
+}
+
+impl GenericMaterial {
+	/// Material property that gives each entity using the material a tint between two colors, chosen from the entity's id.
+	pub const INSTANCE_TINT: MaterialProperty<InstanceTintVariation> = MaterialProperty::new("instance_tint");
+}
+
+/// Range of tints for the [`INSTANCE_TINT`](GenericMaterial::INSTANCE_TINT) property.
+#[derive(Reflect, Debug, Clone, PartialEq)]
+#[reflect(Default)]
+pub struct InstanceTintVariation {
+	pub from: Color,
+	pub to: Color,
+}
+impl Default for InstanceTintVariation {
+	fn default() -> Self {
+		Self {
+			from: Color::WHITE,
+			to: Color::WHITE,
+		}
+	}
+}
+impl InstanceTintVariation {
+	/// Returns the tint for `entity`. This is stable, so an entity keeps its tint when its material is re-applied.
+	pub fn tint_for(&self, entity: Entity) -> Color {
+		// Cheap integer hash of the entity's index so neighboring entities don't get similar tints.
+		let mut x = (entity.to_bits() as u32).wrapping_mul(0x9E37_79B9);
+		x ^= x >> 16;
+		let t = x as f32 / u32::MAX as f32;
+
+		Color::from(self.from.to_linear().mix(&self.to.to_linear(), t))
+	}
+}
+
+/// Explicit tint for an entity using an [`InstanceTintedMaterial`], taking priority over the material's [`INSTANCE_TINT`](GenericMaterial::INSTANCE_TINT) property.
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+pub struct InstanceTint(pub Color);
+impl InstanceTint {
+	/// Packs the tint into a [`MeshTag`] as linear RGBA8, as read by the [`InstanceTintedMaterial`] shader.
+	pub fn to_mesh_tag(self) -> MeshTag {
+		let [r, g, b, a] = self.0.to_linear().to_f32_array().map(|c| (c.clamp(0., 1.) * 255.).round() as u8);
+		MeshTag(u32::from_le_bytes([r, g, b, a]))
+	}
+}
+
+/// Writes the [`MeshTag`] of entities with an [`InstanceTint`] or a material with the [`INSTANCE_TINT`](GenericMaterial::INSTANCE_TINT) property.
+pub fn write_instance_tints(
+	mut commands: Commands,
+	query: Query<
+		(Entity, &GenericMaterial3d, Option<&InstanceTint>),
+		(With<GenericMaterialApplied>, Or<(Added<GenericMaterialApplied>, Changed<InstanceTint>)>),
+	>,
+	generic_materials: Res<Assets<GenericMaterial>>,
+) {
+	for (entity, generic_material_3d, instance_tint) in &query {
+		let tint = match instance_tint {
+			Some(instance_tint) => *instance_tint,
+			None => {
+				let Some(generic_material) = generic_materials.get(&generic_material_3d.0) else { continue };
+				let Ok(variation) = generic_material.get_property(GenericMaterial::INSTANCE_TINT) else { continue };
+				InstanceTint(variation.tint_for(entity))
+			}
+		};
+
+		commands.entity(entity).insert(tint.to_mesh_tag());
+	}
+}
+
+/// Adds [`InstanceTintedMaterial`], registers it as a generic material with the shorthand `InstanceTintedMaterial`,
+/// and writes tints from [`InstanceTint`] and the [`INSTANCE_TINT`](GenericMaterial::INSTANCE_TINT) property.
+pub struct InstanceTintPlugin;
+impl Plugin for InstanceTintPlugin {
+	fn build(&self, app: &mut App) {
+		embedded_asset!(app, "instance_tint.wgsl");
+
+		#[rustfmt::skip]
+		app
+			.add_plugins(MaterialPlugin::<InstanceTintedMaterial>::default())
+			.register_extended_generic_material::<StandardMaterial, InstanceTintExtension>("InstanceTintedMaterial")
+			.register_type::<InstanceTintVariation>()
+			.register_material_property(GenericMaterial::INSTANCE_TINT)
+			.add_systems(PreUpdate, write_instance_tints.after(crate::insert_generic_materials))
+		;
+	}
+}
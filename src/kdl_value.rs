@@ -0,0 +1,458 @@
+//! Structured values read from [KDL](https://kdl.dev) documents, used by [`KdlMaterialDeserializer`](crate::load::deserializer::KdlMaterialDeserializer).
+
+use std::{collections::BTreeMap, fmt};
+
+use kdl::{KdlDocument, KdlNode};
+use serde::{
+	Deserialize, Deserializer,
+	de::{
+		self, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+		value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer},
+	},
+};
+use thiserror::Error;
+
+/// A KDL document or node, structured like a JSON or TOML value so it can be deserialized with serde.
+///
+/// - A document is a map of its nodes' names to their values. Later nodes with the same name replace earlier ones.
+/// - A node with a single argument is that argument, and a node with multiple is an array of them.
+/// - A node with properties or child nodes is a map of them, unless its children are all named `-`, in which case it's an array of them.
+///
+/// # Examples
+/// ```kdl
+/// type "StandardMaterial"
+/// material {
+///     base_color_texture "${name}.png"
+///     perceptual_roughness 0.8
+/// }
+/// properties {
+///     collision #true
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Value {
+	#[default]
+	Null,
+	Bool(bool),
+	Integer(i128),
+	Float(f64),
+	String(String),
+	Array(Vec<Value>),
+	Map(BTreeMap<String, Value>),
+}
+impl Value {
+	/// Parses a KDL document into a value.
+	pub fn parse(s: &str) -> Result<Self, Error> {
+		let document: KdlDocument = s.parse().map_err(|err: kdl::KdlError| Error::Parse(err.to_string()))?;
+		Self::from_document(&document)
+	}
+
+	/// Converts a document into a map of its nodes, or an array if all of them are named `-`.
+	pub fn from_document(document: &KdlDocument) -> Result<Self, Error> {
+		let nodes = document.nodes();
+
+		if !nodes.is_empty() && nodes.iter().all(|node| node.name().value() == "-") {
+			return nodes.iter().map(Self::from_node).collect::<Result<_, _>>().map(Self::Array);
+		}
+
+		nodes
+			.iter()
+			.map(|node| Ok((node.name().value().to_string(), Self::from_node(node)?)))
+			.collect::<Result<_, _>>()
+			.map(Self::Map)
+	}
+
+	/// Converts a node's arguments, properties, and children into a value.
+	pub fn from_node(node: &KdlNode) -> Result<Self, Error> {
+		let mut arguments = Vec::new();
+		let mut properties = BTreeMap::new();
+
+		for entry in node.entries() {
+			let value = Self::from(entry.value());
+			match entry.name() {
+				Some(name) => {
+					properties.insert(name.value().to_string(), value);
+				}
+				None => arguments.push(value),
+			}
+		}
+
+		let mut array_children = false;
+		if let Some(children) = node.children() {
+			match Self::from_document(children)? {
+				Self::Array(children) => {
+					arguments.extend(children);
+					array_children = true;
+				}
+				Self::Map(children) => properties.extend(children),
+				_ => unreachable!("documents are always maps or arrays"),
+			}
+		}
+
+		if !properties.is_empty() {
+			if !arguments.is_empty() {
+				return Err(Error::MixedEntries(node.name().value().to_string()));
+			}
+			return Ok(Self::Map(properties));
+		}
+
+		Ok(match arguments.len() {
+			_ if array_children => Self::Array(arguments),
+			0 if node.children().is_some() => Self::Map(properties),
+			0 => Self::Null,
+			1 => arguments.pop().unwrap(),
+			_ => Self::Array(arguments),
+		})
+	}
+
+	/// Returns the map this value holds, if it's a map.
+	pub fn as_map_mut(&mut self) -> Option<&mut BTreeMap<String, Value>> {
+		match self {
+			Self::Map(map) => Some(map),
+			_ => None,
+		}
+	}
+
+	fn unexpected(&self) -> de::Unexpected<'_> {
+		match self {
+			Self::Null => de::Unexpected::Unit,
+			Self::Bool(b) => de::Unexpected::Bool(*b),
+			Self::Integer(_) => de::Unexpected::Other("integer"),
+			Self::Float(f) => de::Unexpected::Float(*f),
+			Self::String(s) => de::Unexpected::Str(s),
+			Self::Array(_) => de::Unexpected::Seq,
+			Self::Map(_) => de::Unexpected::Map,
+		}
+	}
+}
+impl From<&kdl::KdlValue> for Value {
+	fn from(value: &kdl::KdlValue) -> Self {
+		match value {
+			kdl::KdlValue::String(s) => Self::String(s.clone()),
+			kdl::KdlValue::Integer(i) => Self::Integer(*i),
+			kdl::KdlValue::Float(f) => Self::Float(*f),
+			kdl::KdlValue::Bool(b) => Self::Bool(*b),
+			kdl::KdlValue::Null => Self::Null,
+		}
+	}
+}
+
+/// Errors that may occur when parsing or deserializing a KDL [`Value`].
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Failed to parse KDL: {0}")]
+	Parse(String),
+	#[error("Node {0} has both arguments and properties or child nodes, so it can't be read as either an array or a map")]
+	MixedEntries(String),
+	#[error("{0}")]
+	Custom(String),
+}
+impl de::Error for Error {
+	fn custom<T: fmt::Display>(msg: T) -> Self {
+		Self::Custom(msg.to_string())
+	}
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+	type Deserializer = Self;
+
+	fn into_deserializer(self) -> Self::Deserializer {
+		self
+	}
+}
+
+impl<'de> Deserializer<'de> for Value {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		match self {
+			Self::Null => visitor.visit_unit(),
+			Self::Bool(b) => visitor.visit_bool(b),
+			Self::Integer(i) => match (i64::try_from(i), u64::try_from(i)) {
+				(Ok(i), _) => visitor.visit_i64(i),
+				(_, Ok(u)) => visitor.visit_u64(u),
+				_ => visitor.visit_i128(i),
+			},
+			Self::Float(f) => visitor.visit_f64(f),
+			Self::String(s) => visitor.visit_string(s),
+			Self::Array(array) => {
+				let mut seq = SeqDeserializer::new(array.into_iter());
+				let value = visitor.visit_seq(&mut seq)?;
+				seq.end()?;
+				Ok(value)
+			}
+			Self::Map(map) => {
+				let mut map = MapDeserializer::new(map.into_iter());
+				let value = visitor.visit_map(&mut map)?;
+				map.end()?;
+				Ok(value)
+			}
+		}
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		match self {
+			Self::Null => visitor.visit_none(),
+			value => visitor.visit_some(value),
+		}
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+		match self {
+			Self::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+			Self::Map(map) => visitor.visit_enum(MapAccessDeserializer::new(MapDeserializer::new(map.into_iter()))),
+			value => Err(de::Error::invalid_type(value.unexpected(), &"a string or a map with a single key")),
+		}
+	}
+
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+	}
+}
+impl<'de> Deserialize<'de> for Value {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct ValueVisitor;
+		impl<'de> Visitor<'de> for ValueVisitor {
+			type Value = Value;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("any KDL value")
+			}
+
+			fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+				Ok(Value::Bool(v))
+			}
+			fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+				Ok(Value::Integer(v.into()))
+			}
+			fn visit_i128<E: de::Error>(self, v: i128) -> Result<Self::Value, E> {
+				Ok(Value::Integer(v))
+			}
+			fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+				Ok(Value::Integer(v.into()))
+			}
+			fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+				Ok(Value::Float(v))
+			}
+			fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+				Ok(Value::String(v.to_string()))
+			}
+			fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+				Ok(Value::String(v))
+			}
+			fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+				Ok(Value::Null)
+			}
+			fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+				Ok(Value::Null)
+			}
+			fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+				Value::deserialize(deserializer)
+			}
+			fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+				let mut array = Vec::new();
+				while let Some(value) = seq.next_element()? {
+					array.push(value);
+				}
+				Ok(Value::Array(array))
+			}
+			fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+				let mut out = BTreeMap::new();
+				while let Some((key, value)) = map.next_entry()? {
+					out.insert(key, value);
+				}
+				Ok(Value::Map(out))
+			}
+		}
+
+		deserializer.deserialize_any(ValueVisitor)
+	}
+}
+
+#[cfg(test)]
+#[derive(Deserialize, Debug, PartialEq)]
+enum TestEnum {
+	Unit,
+	Newtype(f32),
+	Struct { a: i32, b: String },
+}
+
+#[test]
+fn deserialize_nested_maps() {
+	#[derive(Deserialize, Debug, PartialEq)]
+	struct Outer {
+		name: String,
+		inner: Inner,
+	}
+	#[derive(Deserialize, Debug, PartialEq)]
+	struct Inner {
+		enabled: bool,
+		deeper: BTreeMap<String, i32>,
+	}
+
+	let value = Value::parse(
+		r#"
+		name "outer"
+		inner enabled=#true {
+			deeper a=1 b=2
+		}
+		"#,
+	)
+	.unwrap();
+
+	assert_eq!(
+		Outer::deserialize(value).unwrap(),
+		Outer {
+			name: "outer".to_string(),
+			inner: Inner {
+				enabled: true,
+				deeper: BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+			},
+		}
+	);
+}
+
+#[test]
+fn deserialize_sequences() {
+	#[derive(Deserialize, Debug, PartialEq)]
+	struct Sequences {
+		arguments: Vec<f32>,
+		children: Vec<Vec<i32>>,
+		maps: Vec<BTreeMap<String, String>>,
+		tuple: (i32, String),
+	}
+
+	let value = Value::parse(
+		r#"
+		arguments 1.0 2.5 3
+		children {
+			- 1 2
+			- 3 4
+		}
+		maps {
+			- key="a"
+			- key="b"
+		}
+		tuple 1 "two"
+		"#,
+	)
+	.unwrap();
+
+	assert_eq!(
+		Sequences::deserialize(value).unwrap(),
+		Sequences {
+			arguments: vec![1., 2.5, 3.],
+			children: vec![vec![1, 2], vec![3, 4]],
+			maps: vec![
+				BTreeMap::from([("key".to_string(), "a".to_string())]),
+				BTreeMap::from([("key".to_string(), "b".to_string())]),
+			],
+			tuple: (1, "two".to_string()),
+		}
+	);
+}
+
+#[test]
+fn deserialize_enums() {
+	let value = Value::parse(
+		r#"
+		unit "Unit"
+		newtype Newtype=0.5
+		struct {
+			Struct a=1 b="b"
+		}
+		"#,
+	)
+	.unwrap();
+
+	assert_eq!(
+		BTreeMap::<String, TestEnum>::deserialize(value).unwrap(),
+		BTreeMap::from([
+			("unit".to_string(), TestEnum::Unit),
+			("newtype".to_string(), TestEnum::Newtype(0.5)),
+			("struct".to_string(), TestEnum::Struct { a: 1, b: "b".to_string() }),
+		])
+	);
+
+	assert!(TestEnum::deserialize(Value::Integer(1)).is_err());
+	assert!(TestEnum::deserialize(Value::String("Unknown".to_string())).is_err());
+}
+
+#[test]
+fn deserialize_numbers() {
+	#[derive(Deserialize, Debug, PartialEq)]
+	struct Numbers {
+		small: i8,
+		negative: i64,
+		large: u64,
+		huge: i128,
+		float: f64,
+		integer_as_float: f32,
+	}
+
+	let value = Value::parse(&format!(
+		"small 12\nnegative -5\nlarge {}\nhuge {}\nfloat 1.5\ninteger_as_float 2",
+		u64::MAX,
+		i128::MAX
+	))
+	.unwrap();
+
+	assert_eq!(
+		Numbers::deserialize(value).unwrap(),
+		Numbers {
+			small: 12,
+			negative: -5,
+			large: u64::MAX,
+			huge: i128::MAX,
+			float: 1.5,
+			integer_as_float: 2.,
+		}
+	);
+
+	// Out of range for the type.
+	assert!(i8::deserialize(Value::Integer(300)).is_err());
+	assert!(u32::deserialize(Value::Integer(-1)).is_err());
+}
+
+#[test]
+fn deserialize_null() {
+	#[derive(Deserialize, Debug, PartialEq)]
+	struct Nullable {
+		none: Option<i32>,
+		some: Option<i32>,
+		unit: (),
+		#[serde(default)]
+		missing: Option<i32>,
+	}
+
+	let value = Value::parse("none #null\nsome 1\nunit #null\nempty").unwrap();
+	assert_eq!(value.clone().as_map_mut().unwrap()["empty"], Value::Null);
+
+	assert_eq!(
+		Nullable::deserialize(value).unwrap(),
+		Nullable {
+			none: None,
+			some: Some(1),
+			unit: (),
+			missing: None,
+		}
+	);
+}
+
+#[test]
+fn mixed_entries_are_rejected() {
+	assert!(matches!(Value::parse("node 1 key=2"), Err(Error::MixedEntries(name)) if name == "node"));
+	assert!(matches!(Value::parse("node {"), Err(Error::Parse(_))));
+}
+
+#[test]
+fn values_round_trip_through_deserialize() {
+	let value = Value::parse("a 1 2\nb key=#false\nc 1.5\nd \"text\"\ne #null").unwrap();
+
+	assert_eq!(Value::deserialize(value.clone()).unwrap(), value);
+}
@@ -0,0 +1,73 @@
+use bevy::reflect::{PartialReflect, ReflectMut, ReflectRef};
+
+/// Linearly interpolates `a` towards `b` by `t` through reflection, writing the result into `a`.
+///
+/// Floats are interpolated, and structs, tuples, lists, arrays, and enums of the same variant are interpolated field by field.
+/// Anything else (such as handles or booleans) switches from `a` to `b` halfway through.
+pub fn lerp_reflect(a: &mut dyn PartialReflect, b: &dyn PartialReflect, t: f32) {
+	if let (Some(a), Some(b)) = (a.try_downcast_mut::<f32>(), b.try_downcast_ref::<f32>()) {
+		*a += (b - *a) * t;
+		return;
+	}
+	if let (Some(a), Some(b)) = (a.try_downcast_mut::<f64>(), b.try_downcast_ref::<f64>()) {
+		*a += (b - *a) * t as f64;
+		return;
+	}
+
+	let interpolated = match (a.reflect_mut(), b.reflect_ref()) {
+		(ReflectMut::Struct(a), ReflectRef::Struct(b)) => {
+			for (name, b_field) in b.iter_fields() {
+				let Some(a_field) = a.field_mut(name) else { continue };
+				lerp_reflect(a_field, b_field, t);
+			}
+
+			true
+		}
+		(ReflectMut::TupleStruct(a), ReflectRef::TupleStruct(b)) => {
+			for (i, b_field) in b.iter_fields().enumerate() {
+				let Some(a_field) = a.field_mut(i) else { continue };
+				lerp_reflect(a_field, b_field, t);
+			}
+
+			true
+		}
+		(ReflectMut::Tuple(a), ReflectRef::Tuple(b)) => {
+			for (i, b_field) in b.iter_fields().enumerate() {
+				let Some(a_field) = a.field_mut(i) else { continue };
+				lerp_reflect(a_field, b_field, t);
+			}
+
+			true
+		}
+		(ReflectMut::List(a), ReflectRef::List(b)) if a.len() == b.len() => {
+			for (i, b_item) in b.iter().enumerate() {
+				let Some(a_item) = a.get_mut(i) else { continue };
+				lerp_reflect(a_item, b_item, t);
+			}
+
+			true
+		}
+		(ReflectMut::Array(a), ReflectRef::Array(b)) if a.len() == b.len() => {
+			for (i, b_item) in b.iter().enumerate() {
+				let Some(a_item) = a.get_mut(i) else { continue };
+				lerp_reflect(a_item, b_item, t);
+			}
+
+			true
+		}
+		(ReflectMut::Enum(a), ReflectRef::Enum(b)) if a.variant_name() == b.variant_name() => {
+			for i in 0..b.field_len() {
+				let (Some(a_field), Some(b_field)) = (a.field_at_mut(i), b.field_at(i)) else { continue };
+				lerp_reflect(a_field, b_field, t);
+			}
+
+			true
+		}
+		_ => false,
+	};
+
+	if !interpolated && t >= 0.5 {
+		// Failing here just means `a` stays as it is.
+		let _ = a.try_apply(b);
+	}
+}
@@ -1,18 +1,59 @@
 #![doc = include_str!("../readme.md")]
 
+#[cfg(feature = "animation")]
 pub mod animation;
+#[cfg(feature = "bevy_asset_loader")]
+pub mod asset_loader;
+pub mod collider;
 pub mod color_space_fix;
 #[cfg(feature = "bevy_pbr")]
+pub mod description;
+#[cfg(feature = "bevy_pbr")]
+pub mod dev;
+#[cfg(feature = "bevy_pbr")]
+pub mod diagnostics;
+pub mod embedded;
+#[cfg(feature = "bevy_pbr")]
 pub mod erased_material;
 pub mod generic_material;
+#[cfg(feature = "bevy_pbr")]
+pub mod highlight;
+#[cfg(feature = "instance_tint")]
+pub mod instance_tint;
+#[cfg(feature = "kdl")]
+pub mod kdl_value;
+pub mod lerp;
 pub mod load;
+#[cfg(feature = "bevy_pbr")]
+pub mod material_overrides;
 pub mod material_property;
+pub mod material_source;
 pub mod prelude;
+#[cfg(feature = "toml")]
+pub mod property_manifest;
+#[cfg(feature = "property_uniforms")]
+pub mod property_uniforms;
+pub mod schema;
+#[cfg(feature = "toml")]
+pub mod shorthand_manifest;
+#[cfg(feature = "skybox")]
+pub mod skybox;
+#[cfg(feature = "bevy_pbr")]
+pub mod snapshot;
+#[cfg(feature = "splat_material")]
+pub mod splat;
+pub mod sync;
+#[cfg(feature = "bevy_pbr")]
+pub mod texture_override;
+#[cfg(feature = "bevy_pbr")]
+pub mod uv_region;
 pub mod value;
 
 #[cfg(feature = "bevy_pbr")]
 use std::any::TypeId;
 use std::sync::Arc;
+#[cfg(feature = "bevy_pbr")]
+use std::time::Duration;
 
 #[cfg(feature = "bevy_pbr")]
 use bevy::{
@@ -20,59 +61,174 @@ use bevy::{
 	reflect::{GetTypeRegistration, Typed},
 };
 use color_space_fix::ColorSpaceFixPlugin;
-use generic_material::GenericMaterialShorthands;
-use material_property::MaterialPropertyRegistry;
+use embedded::EmbeddedMaterialsPlugin;
+use generic_material::{GenericMaterialIds, GenericMaterialShorthands, index_generic_material_ids};
+use material_property::{MaterialPropertyAppliers, MaterialPropertyRegistry};
+use material_source::{GenericMaterialClaims, GenericMaterialSource, clear_generic_material_claims};
 
+use bevy::asset::{AssetLoadError, AssetLoadFailedEvent, AssetPath, io::AssetSourceId};
+use bevy::platform::collections::HashMap;
+#[cfg(feature = "bevy_pbr")]
+use bevy::platform::time::Instant;
 use bevy::prelude::*;
 #[cfg(feature = "bevy_pbr")]
-use generic_material::GenericMaterialApplied;
+use erased_material::{ErasedMaterial, ErasedMaterialHandle};
+#[cfg(feature = "bevy_pbr")]
+use generic_material::{ExtractedGenericMaterial, GenericMaterialApplied};
+#[cfg(feature = "bevy_pbr")]
+use load::hooks::FieldHook;
+#[cfg(feature = "mtl")]
+use load::mtl::MtlLoader;
+#[cfg(feature = "simple_loader")]
+use load::simple::SimpleGenericMaterialLoader;
+#[cfg(feature = "wad")]
+use load::wad::WadLoader;
+#[cfg(feature = "bevy_pbr")]
+use load::wgpu_enums::CullModeDef;
 use load::{
-	GenericMaterialLoader, asset::AssetLoadingProcessor, deserializer::MaterialDeserializer, processor::MaterialProcessor,
-	simple::SimpleGenericMaterialLoader,
+	GenericMaterialLoadError, GenericMaterialLoader, SourceLoaderSettings, TextReplacements,
+	asset::AssetLoadingProcessor,
+	deserializer::MaterialDeserializer,
+	hooks::{GenericMaterialFieldHooks, GenericMaterialForeignFields},
+	library::GenericMaterialLibraryLoader,
+	metrics::MaterializeLoadMetrics,
+	processor::MaterialProcessor,
+	replacements::TextReplacementProcessor,
+	set::GenericMaterialSetLoader,
+	source::SourceProcessor,
 };
+#[cfg(feature = "bevy_pbr")]
+use material_overrides::{GenericMaterialOverrides, UniqueGenericMaterial, reapply_material_overrides};
 use prelude::*;
+#[cfg(feature = "bevy_pbr")]
+use serde::{Serialize, de::DeserializeOwned};
+#[cfg(feature = "bevy_pbr")]
+use texture_override::{GenericMaterialTextureOverride, TextureOverrideCache, reapply_texture_overrides};
+#[cfg(feature = "bevy_pbr")]
+use value::GenericValue;
 
 pub struct MaterializePlugin<D: MaterialDeserializer, P: MaterialProcessor> {
 	pub deserializer: Arc<D>,
 	/// Registers a [`GenericMaterial`] loader that loads directly from images. WARNING: This will cause conflicts if you try to load an image file without specifying what type you want to load.
+	#[cfg(feature = "simple_loader")]
 	pub simple_loader: Option<SimpleGenericMaterialLoader>,
+	/// Registers a loader for Quake and Half-Life WAD texture archives. See [`WadLoader`]. (Default: [`None`])
+	#[cfg(feature = "wad")]
+	pub wad_loader: Option<WadLoader>,
+	/// Registers a loader for Wavefront `.mtl` files. See [`MtlLoader`]. (Default: [`None`])
+	#[cfg(feature = "mtl")]
+	pub mtl_loader: Option<MtlLoader>,
 	/// Whether to add [`AnimationPlugin`](animation::AnimationPlugin), animating materials with the [`ANIMATION`](GenericMaterial::ANIMATION) property. (Default: `true`)
+	#[cfg(feature = "animation")]
 	pub animated_materials: bool,
 	// Whether to replace special patterns in text, such as replacing `${name}` with the name of the material loading. (Default: `true`)
 	pub do_text_replacements: bool,
+	/// Which replacements are applied when text replacements are enabled. (Default: all of them)
+	pub text_replacements: TextReplacements,
+	/// Overrides loader behavior for materials loaded from specific asset sources. (Default: empty)
+	pub source_settings: HashMap<AssetSourceId<'static>, SourceLoaderSettings>,
+	/// Functions run on every material file's deserialized value before it's processed, in order. (Default: empty)
+	pub value_transforms: Vec<fn(&mut D::Value)>,
 	/// Whether to automatically set maps in [`StandardMaterial`] that aren't supposed to be to sRGB to linear if necessary.
 	pub standard_material_color_space_fix: bool,
+	/// Declares and registers every allowed property up-front. See [`PropertyManifest`](property_manifest::PropertyManifest).
+	/// (Default: [`None`], reading [`PropertyManifest::DEFAULT_PATH`](property_manifest::PropertyManifest::DEFAULT_PATH) if it exists)
+	#[cfg(feature = "toml")]
+	pub property_manifest: Option<property_manifest::PropertyManifest>,
+	/// Declares material type shorthands in data. See [`ShorthandManifest`](shorthand_manifest::ShorthandManifest). (Default: [`None`])
+	#[cfg(feature = "toml")]
+	pub shorthand_manifest: Option<shorthand_manifest::ShorthandManifest>,
+	/// Properties given to every loaded material that doesn't specify them. (Default: [`None`])
+	#[allow(clippy::type_complexity)]
+	pub default_properties: Option<fn() -> HashMap<String, Box<dyn Reflect>>>,
+	/// A material file every loaded material implicitly inherits from, before anything in its own `inherits`. See [`with_default_inherits`](Self::with_default_inherits). (Default: [`None`])
+	pub default_inherits: Option<AssetPath<'static>>,
+	/// Material used in place of generic materials that fail to load. See [`with_error_material`](Self::with_error_material). (Default: [`None`])
+	#[cfg(feature = "bevy_pbr")]
+	pub error_material: Option<Box<dyn ErasedMaterial>>,
 	pub processor: P,
 }
-impl<D: MaterialDeserializer, P: MaterialProcessor + Clone> Plugin for MaterializePlugin<D, P> {
+impl<D: MaterialDeserializer, P: MaterialProcessor> Plugin for MaterializePlugin<D, P> {
 	fn build(&self, app: &mut App) {
 		let type_registry = app.world().resource::<AppTypeRegistry>().clone();
 
+		#[cfg(feature = "simple_loader")]
 		if let Some(simple_loader) = self.simple_loader.clone() {
 			app.register_asset_loader(simple_loader);
 		}
 
+		#[cfg(feature = "wad")]
+		if let Some(wad_loader) = self.wad_loader.clone() {
+			app.register_asset_loader(wad_loader);
+		}
+
+		#[cfg(feature = "mtl")]
+		if let Some(mtl_loader) = self.mtl_loader.clone() {
+			app.register_asset_loader(mtl_loader);
+		}
+
 		let shorthands = GenericMaterialShorthands::default();
 		let property_registry = MaterialPropertyRegistry::default();
+		if let Some(default_properties) = self.default_properties {
+			property_registry.defaults.write().unwrap().extend(default_properties());
+		}
+		let metrics = MaterializeLoadMetrics::default();
+		let field_hooks = GenericMaterialFieldHooks::default();
+		let foreign_fields = GenericMaterialForeignFields::default();
+		let text_replacement_processor = TextReplacementProcessor::default();
+
+		let loader = GenericMaterialLoader {
+			type_registry,
+			shorthands: shorthands.clone(),
+			property_registry: property_registry.clone(),
+			deserializer: self.deserializer.clone(),
+			do_text_replacements: self.do_text_replacements,
+			text_replacements: self.text_replacements,
+			text_replacement_processor: text_replacement_processor.clone(),
+			source_settings: self.source_settings.clone(),
+			value_transforms: self.value_transforms.clone(),
+			metrics: metrics.clone(),
+			field_hooks: field_hooks.clone(),
+			foreign_fields: foreign_fields.clone(),
+			default_inherits: self.default_inherits.clone(),
+			default_inherits_cache: default(),
+			processor: self.processor.clone(),
+		};
 
 		#[rustfmt::skip]
 		app
-			.add_plugins(MaterializeMarkerPlugin)
-			.insert_resource(shorthands.clone())
-			.insert_resource(property_registry.clone())
+			.add_plugins((MaterializeMarkerPlugin, EmbeddedMaterialsPlugin))
+			.insert_resource(shorthands)
+			.insert_resource(property_registry)
+			.insert_resource(metrics)
+			.insert_resource(field_hooks)
+			.insert_resource(foreign_fields)
+			.insert_resource(text_replacement_processor)
+			.init_resource::<MaterialPropertyAppliers>()
+			.init_resource::<GenericMaterialIds>()
+			.init_resource::<GenericMaterialClaims>()
+			.register_type::<GenericMaterialSource>()
+			.add_systems(Last, clear_generic_material_claims)
 			.register_type::<GenericMaterial3d>()
 			.init_asset::<GenericMaterial>()
+			.add_message::<GenericMaterialLoadFailed>()
+			.add_message::<GenericMaterialPropertyChanged>()
+			.add_systems(PreUpdate, (report_generic_material_load_failures, index_generic_material_ids, detect_generic_material_property_changes))
 			.register_generic_material_sub_asset::<GenericMaterial>()
-			.register_asset_loader(GenericMaterialLoader {
-				type_registry,
-				shorthands,
-				property_registry,
+			.init_asset::<GenericMaterialSet>()
+			.register_generic_material_sub_asset::<GenericMaterialSet>()
+			.register_asset_loader(GenericMaterialSetLoader {
 				deserializer: self.deserializer.clone(),
-				do_text_replacements: self.do_text_replacements,
-				processor: self.processor.clone(),
 			})
+			.init_asset::<GenericMaterialLibrary>()
+			.register_generic_material_sub_asset::<GenericMaterialLibrary>()
+			.register_asset_loader(GenericMaterialLibraryLoader {
+				loader: Arc::new(loader.clone()),
+			})
+			.register_asset_loader(loader)
 		;
 
+		#[cfg(feature = "animation")]
 		if self.animated_materials {
 			app.add_plugins(animation::AnimationPlugin);
 		}
@@ -84,18 +240,85 @@ impl<D: MaterialDeserializer, P: MaterialProcessor + Clone> Plugin for Materiali
 		#[cfg(feature = "bevy_image")]
 		app.register_generic_material_sub_asset::<Image>();
 
+		#[cfg(feature = "bevy_sprite_render")]
+		#[rustfmt::skip]
+		app
+			.register_type::<GenericMaterial2d>()
+			.register_generic_material_2d::<ColorMaterial>()
+		;
+
 		#[cfg(feature = "bevy_pbr")]
 		#[rustfmt::skip]
 		app
 			.register_material_property(GenericMaterial::VISIBILITY)
+			.register_material_property(GenericMaterial::HIGHLIGHTABLE)
+			.register_material_property(GenericMaterial::UV_REGION)
+			.register_material_property(GenericMaterial::RANDOM_UV_OFFSET)
+			.register_material_property_applier(apply_visibility_property)
+			.register_type::<UniqueGenericMaterial>()
 			.register_generic_material::<StandardMaterial>()
+			.register_generic_material_foreign_field::<StandardMaterial, CullModeDef>(
+				"cull_mode",
+				|material| material.cull_mode.into(),
+				|material, cull_mode| material.cull_mode = cull_mode.into(),
+			)
+			.init_resource::<GenericMaterialApplyBudget>()
+			.init_resource::<TextureOverrideCache>()
 			.add_systems(PreUpdate, (
+				patch_reloaded_generic_materials,
+				uv_region::apply_uv_region_property,
 				reload_generic_materials,
+				reapply_texture_overrides,
+				reapply_material_overrides,
 				visibility_material_property, // Must be before `insert_generic_materials`
 				insert_generic_materials,
+				highlight::reapply_generic_material_highlights,
 			).chain())
 		;
 	}
+
+	fn finish(&self, app: &mut App) {
+		// Registered here rather than in `build` so shorthands can refer to materials registered after this plugin is added.
+		#[cfg(feature = "toml")]
+		if let Some(manifest) = &self.shorthand_manifest
+			&& let Err(err) = manifest.register(app)
+		{
+			panic!("Failed to register shorthand manifest: {err}");
+		}
+
+		// Likewise, so declared types can be registered after this plugin is added.
+		#[cfg(feature = "toml")]
+		{
+			use property_manifest::PropertyManifest;
+
+			let manifest = match &self.property_manifest {
+				Some(manifest) => Ok(Some(manifest.clone())),
+				None => PropertyManifest::read_if_exists(PropertyManifest::DEFAULT_PATH),
+			};
+			match manifest {
+				Ok(Some(manifest)) => {
+					if let Err(err) = manifest.register(app) {
+						error!("Failed to register property manifest: {err}");
+					}
+				}
+				Ok(None) => {}
+				Err(err) => error!("Failed to read property manifest {}: {err}", PropertyManifest::DEFAULT_PATH),
+			}
+		}
+
+		#[cfg(feature = "bevy_pbr")]
+		if let Some(error_material) = &self.error_material {
+			let handle = error_material.clone_erased().add_asset(app.world().resource::<AssetServer>());
+			let error_material = app
+				.world_mut()
+				.resource_mut::<Assets<GenericMaterial>>()
+				.add(GenericMaterial::new(handle));
+			app.insert_resource(GenericMaterialLoadFallback(error_material));
+		}
+
+		#[cfg(not(any(feature = "toml", feature = "bevy_pbr")))]
+		let _ = app;
+	}
 }
 impl<D: MaterialDeserializer> MaterializePlugin<D, AssetLoadingProcessor<()>> {
 	/// Creates a new [`MaterializePlugin`] with an [`AssetLoadingProcessor`].
@@ -109,15 +332,33 @@ impl<D: MaterialDeserializer, P: MaterialProcessor> MaterializePlugin<D, P> {
 	pub fn new_with_processor(deserializer: D, processor: P) -> Self {
 		Self {
 			deserializer: Arc::new(deserializer),
+			#[cfg(feature = "simple_loader")]
 			simple_loader: None,
+			#[cfg(feature = "wad")]
+			wad_loader: None,
+			#[cfg(feature = "mtl")]
+			mtl_loader: None,
+			#[cfg(feature = "animation")]
 			animated_materials: true,
 			do_text_replacements: true,
+			text_replacements: TextReplacements::default(),
+			source_settings: HashMap::default(),
+			value_transforms: Vec::new(),
 			standard_material_color_space_fix: true,
+			#[cfg(feature = "toml")]
+			property_manifest: None,
+			#[cfg(feature = "toml")]
+			shorthand_manifest: None,
+			default_properties: None,
+			default_inherits: None,
+			#[cfg(feature = "bevy_pbr")]
+			error_material: None,
 			processor,
 		}
 	}
 
 	/// Registers a [`GenericMaterial`] loader that loads directly from images. WARNING: This will cause conflicts if you try to load an image file without specifying what type you want to load.
+	#[cfg(feature = "simple_loader")]
 	pub fn with_simple_loader(self, loader: SimpleGenericMaterialLoader) -> Self {
 		Self {
 			simple_loader: Some(loader),
@@ -125,7 +366,28 @@ impl<D: MaterialDeserializer, P: MaterialProcessor> MaterializePlugin<D, P> {
 		}
 	}
 
+	/// Registers a loader for Quake and Half-Life WAD texture archives, which makes a [`GenericMaterial`] for each texture. See [`WadLoader`].
+	#[cfg(feature = "wad")]
+	pub fn with_wad_loader(self, loader: WadLoader) -> Self {
+		Self {
+			wad_loader: Some(loader),
+			..self
+		}
+	}
+
+	/// Registers a loader for Wavefront `.mtl` files, which makes a [`GenericMaterial`] for each material in the file. See [`MtlLoader`].
+	#[cfg(feature = "mtl")]
+	pub fn with_mtl_loader(self, loader: MtlLoader) -> Self {
+		Self {
+			mtl_loader: Some(loader),
+			..self
+		}
+	}
+
 	/// Whether to replace special patterns in text, such as replacing `${name}` with the name of the material loading. (Default: `true`)
+	///
+	/// Individual replacements can be toggled with [`with_name_replacement`](Self::with_name_replacement), [`with_settings_replacements`](Self::with_settings_replacements),
+	/// and [`with_provider_replacements`](Self::with_provider_replacements).
 	pub fn with_text_replacements(self, value: bool) -> Self {
 		Self {
 			do_text_replacements: value,
@@ -133,7 +395,79 @@ impl<D: MaterialDeserializer, P: MaterialProcessor> MaterializePlugin<D, P> {
 		}
 	}
 
+	/// Whether to replace `${name}` with the name of the material loading. (Default: `true`)
+	pub fn with_name_replacement(self, value: bool) -> Self {
+		Self {
+			text_replacements: TextReplacements {
+				name: value,
+				..self.text_replacements
+			},
+			..self
+		}
+	}
+
+	/// Whether to replace `${key}` with the [`replacements`](load::GenericMaterialLoaderSettings::replacements) in a material's loader settings. (Default: `true`)
+	pub fn with_settings_replacements(self, value: bool) -> Self {
+		Self {
+			text_replacements: TextReplacements {
+				settings: value,
+				..self.text_replacements
+			},
+			..self
+		}
+	}
+
+	/// Whether to replace `${key}` with the values of the providers in the [`TextReplacementProcessor`] resource, other than `${name}`. (Default: `true`)
+	pub fn with_provider_replacements(self, value: bool) -> Self {
+		Self {
+			text_replacements: TextReplacements {
+				providers: value,
+				..self.text_replacements
+			},
+			..self
+		}
+	}
+
+	/// Overrides loader behavior for materials loaded from a specific asset source.
+	///
+	/// # Examples
+	/// ```ignore
+	/// MaterializePlugin::new(TomlMaterialDeserializer).with_source_settings("generated", SourceLoaderSettings {
+	///     allow_inheritance: false,
+	///     ..default()
+	/// })
+	/// ```
+	///
+	/// For user-generated content like mods, [`allow_external_paths`](SourceLoaderSettings::allow_external_paths) keeps materials
+	/// from referencing assets by absolute path or in other asset sources.
+	pub fn with_source_settings(mut self, source: impl Into<AssetSourceId<'static>>, settings: SourceLoaderSettings) -> Self {
+		self.source_settings.insert(source.into(), settings);
+		self
+	}
+
+	/// Adds a function to run on every material file's deserialized value before it's processed.
+	/// This is a good place to adapt files, such as renaming keys or converting legacy formats.
+	///
+	/// Transforms run in the order they're added, and also run on inherited materials.
+	///
+	/// # Examples
+	/// ```ignore
+	/// MaterializePlugin::new(TomlMaterialDeserializer).with_value_transform(|value| {
+	///     // Rename the legacy `props` table to `properties`.
+	///     if let Some(table) = value.as_table_mut()
+	///         && let Some(props) = table.remove("props")
+	///     {
+	///         table.insert("properties".into(), props);
+	///     }
+	/// })
+	/// ```
+	pub fn with_value_transform(mut self, transform: fn(&mut D::Value)) -> Self {
+		self.value_transforms.push(transform);
+		self
+	}
+
 	/// Whether to add [`AnimationPlugin`](animation::AnimationPlugin), animating materials with the [`ANIMATION`](GenericMaterial::ANIMATION) property.
+	#[cfg(feature = "animation")]
 	pub fn with_animated_materials(self, value: bool) -> Self {
 		Self {
 			animated_materials: value,
@@ -149,19 +483,136 @@ impl<D: MaterialDeserializer, P: MaterialProcessor> MaterializePlugin<D, P> {
 		}
 	}
 
+	/// Declares and registers every allowed property up-front. See [`PropertyManifest`](property_manifest::PropertyManifest).
+	#[cfg(feature = "toml")]
+	pub fn with_property_manifest(self, manifest: property_manifest::PropertyManifest) -> Self {
+		Self {
+			property_manifest: Some(manifest),
+			..self
+		}
+	}
+
+	/// Declares material type shorthands in data. See [`ShorthandManifest`](shorthand_manifest::ShorthandManifest).
+	#[cfg(feature = "toml")]
+	pub fn with_shorthand_manifest(self, manifest: shorthand_manifest::ShorthandManifest) -> Self {
+		Self {
+			shorthand_manifest: Some(manifest),
+			..self
+		}
+	}
+
+	/// Sets properties given to every loaded material that doesn't specify them, like `SimpleGenericMaterialLoader::properties` but for material files.
+	///
+	/// # Examples
+	/// ```ignore
+	/// MaterializePlugin::new(TomlMaterialDeserializer).with_default_properties(|| {
+	///     HashMap::from([("collision".to_string(), Box::new(true) as Box<dyn Reflect>)])
+	/// })
+	/// ```
+	pub fn with_default_properties(self, properties: fn() -> HashMap<String, Box<dyn Reflect>>) -> Self {
+		Self {
+			default_properties: Some(properties),
+			..self
+		}
+	}
+
+	/// Sets a material file every loaded material implicitly inherits from, giving one place to set project-wide defaults for material fields and properties.
+	///
+	/// The default material has the lowest precedence, below everything in a material's own `inherits`.
+	/// Like materials in the same `inherits` list, its fields are only merged into materials of the same type.
+	/// The path is relative to the asset root, and the default material itself doesn't inherit from it.
+	///
+	/// # Examples
+	/// ```ignore
+	/// MaterializePlugin::new(TomlMaterialDeserializer).with_default_inherits("materials/base.toml")
+	/// ```
+	pub fn with_default_inherits(self, path: impl Into<AssetPath<'static>>) -> Self {
+		Self {
+			default_inherits: Some(path.into()),
+			..self
+		}
+	}
+
+	/// Sets a material to use in place of generic materials that fail to load, such as ones with a type that isn't registered,
+	/// so broken materials stand out instead of silently rendering nothing.
+	///
+	/// Once the app is built, this adds the material and sets it as the [`GenericMaterialLoadFallback`].
+	///
+	/// # Examples
+	/// ```ignore
+	/// MaterializePlugin::new(TomlMaterialDeserializer).with_error_material(StandardMaterial {
+	///     base_color: Color::srgb(1., 0., 1.),
+	///     unlit: true,
+	///     ..default()
+	/// })
+	/// ```
+	#[cfg(feature = "bevy_pbr")]
+	pub fn with_error_material(self, material: impl Into<Box<dyn ErasedMaterial>>) -> Self {
+		Self {
+			error_material: Some(material.into()),
+			..self
+		}
+	}
+
 	/// Adds a new processor to the processor stack. The function specified takes in the old processor and produces a new one.
 	///
 	/// Zero-sized processors are usually tuples, meaning you can just put their type name (e.g. `.with_processor(MyProcessor)`).
+	///
+	/// Processors added later get a chance to deserialize a value only after every processor before them gave it up.
+	/// See [`MaterialProcessor`] for more details.
 	pub fn with_processor<NewP: MaterialProcessor>(self, f: impl FnOnce(P) -> NewP) -> MaterializePlugin<D, NewP> {
 		MaterializePlugin {
 			deserializer: self.deserializer,
+			#[cfg(feature = "simple_loader")]
 			simple_loader: self.simple_loader,
+			#[cfg(feature = "wad")]
+			wad_loader: self.wad_loader,
+			#[cfg(feature = "mtl")]
+			mtl_loader: self.mtl_loader,
+			#[cfg(feature = "animation")]
 			animated_materials: self.animated_materials,
 			do_text_replacements: self.do_text_replacements,
+			text_replacements: self.text_replacements,
+			source_settings: self.source_settings,
+			value_transforms: self.value_transforms,
 			standard_material_color_space_fix: self.standard_material_color_space_fix,
+			#[cfg(feature = "toml")]
+			property_manifest: self.property_manifest,
+			#[cfg(feature = "toml")]
+			shorthand_manifest: self.shorthand_manifest,
+			default_properties: self.default_properties,
+			default_inherits: self.default_inherits,
+			#[cfg(feature = "bevy_pbr")]
+			error_material: self.error_material,
 			processor: f(self.processor),
 		}
 	}
+
+	/// Adds `processor` to the processor stack, only running it for materials loaded from the asset source `source`. See [`SourceProcessor`].
+	///
+	/// # Examples
+	/// ```ignore
+	/// MaterializePlugin::new(TomlMaterialDeserializer).with_source_processor("generated", ColorProcessor(()))
+	/// ```
+	pub fn with_source_processor<S: MaterialProcessor>(
+		self,
+		source: impl Into<AssetSourceId<'static>>,
+		processor: S,
+	) -> MaterializePlugin<D, SourceProcessor<P, S>> {
+		let source = source.into();
+		self.with_processor(|child| SourceProcessor { child, source, processor })
+	}
+
+	/// Replaces the whole processor stack with a fully composed one.
+	///
+	/// The innermost processor gets the first chance to deserialize a value, so in
+	/// `ColorProcessor<FlattenExtendedMaterialProcessor<AssetLoadingProcessor<()>>>`,
+	/// [`AssetLoadingProcessor`] runs first, then `FlattenExtendedMaterialProcessor`, then `ColorProcessor`.
+	///
+	/// NOTE: This drops the current stack, so if you still want assets to be loaded from paths, make sure to include an [`AssetLoadingProcessor`].
+	pub fn with_processor_chain<NewP: MaterialProcessor>(self, processor: NewP) -> MaterializePlugin<D, NewP> {
+		self.with_processor(|_| processor)
+	}
 }
 impl<D: MaterialDeserializer + Default, P: MaterialProcessor + Default> Default for MaterializePlugin<D, P> {
 	fn default() -> Self {
@@ -180,20 +631,145 @@ impl Plugin for MaterializeMarkerPlugin {
 // // SYSTEMS
 // ////////////////////////////////////////////////////////////////////////////////
 
+/// Limits how much work [`insert_generic_materials`] does per frame, spreading the rest over subsequent frames.
+///
+/// This avoids frame spikes when lots of materials load or hot reload at once. (Default: unlimited)
+#[cfg(feature = "bevy_pbr")]
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct GenericMaterialApplyBudget {
+	/// Maximum number of entities to apply materials to per frame.
+	pub max_entities: Option<usize>,
+	/// Maximum time to spend applying materials per frame.
+	pub max_time: Option<Duration>,
+}
+
 #[cfg(feature = "bevy_pbr")]
 pub fn insert_generic_materials(
 	mut commands: Commands,
 	query: Query<(Entity, &GenericMaterial3d), Without<GenericMaterialApplied>>,
+	#[cfg(feature = "bevy_sprite_render")] query_2d: Query<(Entity, &GenericMaterial2d), Without<GenericMaterialApplied>>,
 	generic_materials: Res<Assets<GenericMaterial>>,
+	asset_server: Res<AssetServer>,
+	fallback: Option<Res<GenericMaterialLoadFallback>>,
+	budget: Res<GenericMaterialApplyBudget>,
 ) {
-	for (entity, holder) in &query {
-		let Some(generic_material) = generic_materials.get(&holder.0) else { continue };
+	let holders = query.iter().map(|(entity, holder)| (entity, &holder.0));
+	#[cfg(feature = "bevy_sprite_render")]
+	let holders = holders.chain(query_2d.iter().map(|(entity, holder)| (entity, &holder.0)));
+
+	let pending: Vec<_> = holders
+		.filter_map(|(entity, handle)| {
+			let handle = match generic_materials.contains(handle) {
+				true => handle,
+				// The entity keeps its handle to the broken material, so it's reapplied if that's fixed and reloaded.
+				false if asset_server.load_state(handle).is_failed() => &fallback.as_ref()?.0,
+				false => return None,
+			};
+			let generic_material = generic_materials.get(handle)?;
+			let random_uv_offset = generic_material.get_property(GenericMaterial::RANDOM_UV_OFFSET).is_ok_and(|value| *value);
+			Some((entity, handle.id(), generic_material.handle.clone(), random_uv_offset))
+		})
+		.take(budget.max_entities.unwrap_or(usize::MAX))
+		.collect();
+
+	if pending.is_empty() {
+		return;
+	}
+
+	// Entities we don't get to this frame don't get `GenericMaterialApplied`, so they'll be picked up next frame.
+	let max_time = budget.max_time;
+	commands.queue(move |world: &mut World| {
+		let start = Instant::now();
+
+		for (i, (entity, generic_material, material, random_uv_offset)) in pending.into_iter().enumerate() {
+			// At least one entity is always applied, so entities can't be starved by a frame that's already over budget.
+			if i > 0 && max_time.is_some_and(|max_time| start.elapsed() >= max_time) {
+				break;
+			}
+
+			let material = match world.get::<GenericMaterialTextureOverride>(entity).cloned() {
+				Some(texture_override) => texture_override.instantiate(world, &material).unwrap_or(material),
+				None => material,
+			};
+			let overrides = world.get::<GenericMaterialOverrides>(entity);
+			// Overrides and random UV offsets already make a copy for the entity.
+			let unique = world.get::<UniqueGenericMaterial>(entity).is_some() && overrides.is_none() && !random_uv_offset;
+			let material = match overrides {
+				Some(overrides) => overrides.instantiate(world, &material).unwrap_or(material),
+				None => material,
+			};
+			let material = if random_uv_offset {
+				uv_region::instantiate_random_uv_offset(world, &material, entity).unwrap_or(material)
+			} else {
+				material
+			};
+			let material = if unique {
+				material.make_unique(world).unwrap_or(material)
+			} else {
+				material
+			};
+			// Entities given the material while it's cross-fading into its next one use the same blend as the others.
+			#[cfg(feature = "animation")]
+			let material = animation::next_transition_blend(world, generic_material).unwrap_or(material);
+
+			let Ok(mut entity) = world.get_entity_mut(entity) else { continue };
+			entity.insert((
+				GenericMaterialApplied,
+				ExtractedGenericMaterial {
+					generic_material,
+					material: material.id(),
+				},
+			));
+			material.insert(entity);
+		}
+	});
+}
+
+/// When a [`GenericMaterial`] is modified to hold a different material, this patches the changed fields of its new material into the material it was originally loaded with,
+/// then points the [`GenericMaterial`] back at the original.
+///
+/// Reloading a file usually keeps the ids of its labeled sub-assets, in which case the material is already replaced in place and nothing needs patching.
+/// This keeps material handles held elsewhere valid when that isn't the case, such as when the label of the inner material changes.
+/// If the material's type changed, the new material is kept as-is.
+#[cfg(feature = "bevy_pbr")]
+pub fn patch_reloaded_generic_materials(
+	mut commands: Commands,
+	mut asset_events: MessageReader<AssetEvent<GenericMaterial>>,
+	mut generic_materials: ResMut<Assets<GenericMaterial>>,
+	mut original_handles: Local<HashMap<AssetId<GenericMaterial>, ErasedMaterialHandle>>,
+) {
+	for event in asset_events.read() {
+		match event {
+			AssetEvent::Added { id } => {
+				let Some(generic_material) = generic_materials.get(*id) else { continue };
+				original_handles.insert(*id, generic_material.handle.clone());
+			}
+			AssetEvent::Modified { id } => {
+				let Some(original) = original_handles.get(id).cloned() else { continue };
+				let Some(generic_material) = generic_materials.get(*id) else { continue };
+
+				if generic_material.handle.id() == original.id() {
+					continue;
+				}
+				if generic_material.handle.inner().type_id() != original.inner().type_id() {
+					original_handles.insert(*id, generic_material.handle.clone());
+					continue;
+				}
+
+				// Untracked, as this event is already being handled, and another one would make every other system handle the reload twice.
+				let Some(generic_material) = generic_materials.get_mut_untracked(*id) else { continue };
+				let new = std::mem::replace(&mut generic_material.handle, original.clone());
 
-		let material = generic_material.handle.clone();
-		commands
-			.entity(entity)
-			.queue(move |entity: EntityWorldMut<'_>| material.insert(entity))
-			.insert(GenericMaterialApplied);
+				commands.queue(move |world: &mut World| {
+					let Some(new_material) = new.get_from_world(world).and_then(|material| material.reflect_clone().ok()) else { return };
+					original.patch(world, new_material);
+				});
+			}
+			AssetEvent::Removed { id } => {
+				original_handles.remove(id);
+			}
+			_ => {}
+		}
 	}
 }
 
@@ -202,37 +778,208 @@ pub fn reload_generic_materials(
 	mut commands: Commands,
 	mut asset_events: MessageReader<AssetEvent<GenericMaterial>>,
 	query: Query<(Entity, &GenericMaterial3d), With<GenericMaterialApplied>>,
+	#[cfg(feature = "bevy_sprite_render")] query_2d: Query<(Entity, &GenericMaterial2d), With<GenericMaterialApplied>>,
+	generic_materials: Res<Assets<GenericMaterial>>,
+	fallback: Option<Res<GenericMaterialLoadFallback>>,
 ) {
+	let fallback_material = fallback
+		.and_then(|fallback| generic_materials.get(&fallback.0))
+		.map(|generic_material| generic_material.handle.clone());
+
 	for event in asset_events.read() {
-		let AssetEvent::Modified { id } = event else { continue };
+		// Entities can have a material applied before theirs is added if it failed to load and they were given the fallback.
+		let (AssetEvent::Modified { id } | AssetEvent::Added { id }) = event else { continue };
+
+		let holders = query.iter().map(|(entity, holder)| (entity, &holder.0));
+		#[cfg(feature = "bevy_sprite_render")]
+		let holders = holders.chain(query_2d.iter().map(|(entity, holder)| (entity, &holder.0)));
+
+		for (entity, handle) in holders {
+			if *id != handle.id() {
+				continue;
+			}
+
+			let mut entity = commands.entity(entity);
+			entity.remove::<GenericMaterialApplied>();
 
-		for (entity, holder) in &query {
-			if *id == holder.0.id() {
-				commands.entity(entity).remove::<GenericMaterialApplied>();
+			// The fallback's material may be of a different type than the fixed material, in which case inserting that wouldn't replace it.
+			if let (AssetEvent::Added { .. }, Some(fallback_material)) = (event, &fallback_material) {
+				let fallback_material = fallback_material.clone();
+				entity.queue(move |entity: EntityWorldMut| fallback_material.remove(entity));
 			}
 		}
 	}
 }
 
+#[cfg(feature = "bevy_pbr")]
+pub trait GenericMaterialCommandsExt {
+	/// Re-applies a [`GenericMaterial`] to every entity using it, as if it was hot reloaded.
+	///
+	/// Use this after mutating a generic material or its inner material in a way that doesn't send [`AssetEvent::Modified`].
+	fn reload_generic_material(&mut self, id: impl Into<AssetId<GenericMaterial>>);
+}
+#[cfg(feature = "bevy_pbr")]
+impl GenericMaterialCommandsExt for Commands<'_, '_> {
+	fn reload_generic_material(&mut self, id: impl Into<AssetId<GenericMaterial>>) {
+		let id = id.into();
+
+		self.queue(move |world: &mut World| {
+			#[allow(unused_mut)]
+			let mut entities: Vec<Entity> = world
+				.query_filtered::<(Entity, &GenericMaterial3d), With<GenericMaterialApplied>>()
+				.iter(world)
+				.filter(|(_, holder)| holder.id() == id)
+				.map(|(entity, _)| entity)
+				.collect();
+
+			#[cfg(feature = "bevy_sprite_render")]
+			entities.extend(
+				world
+					.query_filtered::<(Entity, &GenericMaterial2d), With<GenericMaterialApplied>>()
+					.iter(world)
+					.filter(|(_, holder)| holder.id() == id)
+					.map(|(entity, _)| entity),
+			);
+
+			// `insert_generic_materials` picks these back up.
+			for entity in entities {
+				world.entity_mut(entity).remove::<GenericMaterialApplied>();
+			}
+		});
+	}
+}
+
 impl GenericMaterial {
 	/// Material property that sets the visibility of the mesh it's applied to.
 	#[cfg(feature = "bevy_pbr")]
 	pub const VISIBILITY: MaterialProperty<Visibility> = MaterialProperty::new("visibility");
 }
 
+/// [`MaterialPropertyAppliers`](material_property::MaterialPropertyAppliers) version of [`visibility_material_property`].
+#[cfg(feature = "bevy_pbr")]
+pub fn apply_visibility_property(generic_material: &GenericMaterial, entity: &mut EntityWorldMut) {
+	let Ok(new_visibility) = generic_material.get_property(GenericMaterial::VISIBILITY) else { return };
+	let Some(mut visibility) = entity.get_mut::<Visibility>() else { return };
+
+	*visibility = *new_visibility;
+}
+
 #[cfg(feature = "bevy_pbr")]
 pub fn visibility_material_property(
 	mut query: Query<(&GenericMaterial3d, &mut Visibility), Without<GenericMaterialApplied>>,
-	generic_materials: Res<Assets<GenericMaterial>>,
+	#[cfg(feature = "bevy_sprite_render")] mut query_2d: Query<
+		(&GenericMaterial2d, &mut Visibility),
+		(Without<GenericMaterialApplied>, Without<GenericMaterial3d>),
+	>,
+	generic_materials: GenericMaterials,
 ) {
-	for (generic_material_holder, mut visibility) in &mut query {
-		let Some(generic_material) = generic_materials.get(&generic_material_holder.0) else { continue };
+	let holders = query.iter_mut().map(|(holder, visibility)| (&holder.0, visibility));
+	#[cfg(feature = "bevy_sprite_render")]
+	let holders = holders.chain(query_2d.iter_mut().map(|(holder, visibility)| (&holder.0, visibility)));
+
+	for (handle, mut visibility) in holders {
+		let Some(generic_material) = generic_materials.get(handle) else { continue };
 		let Ok(new_visibility) = generic_material.get_property(GenericMaterial::VISIBILITY) else { continue };
 
 		*visibility = *new_visibility;
 	}
 }
 
+/// Sent when a [`GenericMaterial`] fails to load, such as from an unknown field or missing type, so games can react to broken materials in-world.
+#[derive(Message, Debug, Clone)]
+pub struct GenericMaterialLoadFailed {
+	pub id: AssetId<GenericMaterial>,
+	pub path: AssetPath<'static>,
+	pub error: AssetLoadError,
+}
+impl GenericMaterialLoadFailed {
+	/// Returns the error the material loader produced, if the failure came from parsing or processing the material file rather than reading it.
+	pub fn loader_error(&self) -> Option<&GenericMaterialLoadError> {
+		match &self.error {
+			AssetLoadError::AssetLoaderError(err) => err.error().downcast_ref(),
+			_ => None,
+		}
+	}
+}
+
+/// If present, entities whose [`GenericMaterial`] fails to load are given this material's material instead, such as a "missing material" checkerboard.
+///
+/// Their [`GenericMaterial3d`] keeps pointing at the broken material, so once it's fixed and hot reloaded, they're given it instead.
+///
+/// # Examples
+/// ```ignore
+/// let missing = asset_server.load(EmbeddedMaterialsPlugin::MISSING);
+/// commands.insert_resource(GenericMaterialLoadFallback(missing));
+/// ```
+#[derive(Resource, Debug, Clone)]
+pub struct GenericMaterialLoadFallback(pub Handle<GenericMaterial>);
+
+/// Sends [`GenericMaterialLoadFailed`] for every generic material that fails to load.
+pub fn report_generic_material_load_failures(
+	mut failed_events: MessageReader<AssetLoadFailedEvent<GenericMaterial>>,
+	mut load_failed: MessageWriter<GenericMaterialLoadFailed>,
+) {
+	for event in failed_events.read() {
+		load_failed.write(GenericMaterialLoadFailed {
+			id: event.id,
+			path: event.path.clone(),
+			error: event.error.clone(),
+		});
+	}
+}
+
+/// Sent when a property of a [`GenericMaterial`] is added, removed, or changes value when the material is modified, such as from hot-reloading,
+/// so systems that cached a property (e.g. collision flags) can re-read only what changed.
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct GenericMaterialPropertyChanged {
+	pub id: AssetId<GenericMaterial>,
+	pub key: String,
+}
+
+/// Sends [`GenericMaterialPropertyChanged`] for each property that differs from the last time the material was seen.
+#[allow(clippy::type_complexity)]
+pub fn detect_generic_material_property_changes(
+	mut asset_events: MessageReader<AssetEvent<GenericMaterial>>,
+	mut property_changed: MessageWriter<GenericMaterialPropertyChanged>,
+	generic_materials: Res<Assets<GenericMaterial>>,
+	// Copies of each material's properties. Values that can't be cloned are stored as `None`, and always count as changed.
+	mut previous: Local<HashMap<AssetId<GenericMaterial>, HashMap<String, Option<Box<dyn Reflect>>>>>,
+) {
+	for event in asset_events.read() {
+		match *event {
+			AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+				let Some(generic_material) = generic_materials.get(id) else { continue };
+				let old_properties = previous.remove(&id);
+
+				if let Some(old_properties) = &old_properties {
+					let removed = old_properties.keys().filter(|key| !generic_material.properties.contains_key(*key));
+					let changed = generic_material.properties.iter().filter(|(key, value)| match old_properties.get(*key) {
+						Some(Some(old_value)) => old_value.reflect_partial_eq(value.as_partial_reflect()) != Some(true),
+						_ => true,
+					});
+
+					property_changed.write_batch(
+						removed
+							.chain(changed.map(|(key, _)| key))
+							.map(|key| GenericMaterialPropertyChanged { id, key: key.clone() }),
+					);
+				}
+
+				let properties = generic_material
+					.properties
+					.iter()
+					.map(|(key, value)| (key.clone(), value.reflect_clone().ok()))
+					.collect();
+				previous.insert(id, properties);
+			}
+			AssetEvent::Removed { id } => {
+				previous.remove(&id);
+			}
+			_ => {}
+		}
+	}
+}
+
 #[cfg(feature = "bevy_pbr")]
 pub trait MaterializeAppExt {
 	/// Register a material to be able to be created via [`GenericMaterial`].
@@ -242,15 +989,16 @@ pub trait MaterializeAppExt {
 	/// NOTES:
 	/// - [`from_world`](FromWorld::from_world) is only called once when the material is registered, then that value is cloned each time a new instance is required.
 	/// - If you're registering an [`ExtendedMaterial`] that requires [`FromWorld`], you should use [`register_extended_generic_material(...)`](MaterializeAppExt::register_extended_generic_material).
-	fn register_generic_material<M: Material + Reflect + Struct + FromWorld + GetTypeRegistration>(&mut self) -> &mut Self;
+	/// - Enum and tuple struct materials are supported, but since they don't have named fields, features that modify single fields (like image animations) won't affect them.
+	fn register_generic_material<M: Material + Reflect + FromWorld + GetTypeRegistration>(&mut self) -> &mut Self;
 
 	/// Registers an [`ExtendedMaterial`] using [`FromWorld`], and sets a shorthand to it.
 	///
 	/// This function is necessary if either the base or extension requires [`FromWorld`], as [`ExtendedMaterial`] has a [`Default`] impl that would conflict with a [`FromWorld`] impl.
 	/// This is also why the base and extension are separate generic parameters.
 	fn register_extended_generic_material<
-		Base: Material + FromReflect + Typed + Struct + FromWorld + GetTypeRegistration,
-		Ext: MaterialExtension + FromReflect + Typed + Struct + FromWorld + GetTypeRegistration,
+		Base: Material + FromReflect + Typed + FromWorld + GetTypeRegistration,
+		Ext: MaterialExtension + FromReflect + Typed + FromWorld + GetTypeRegistration,
 	>(
 		&mut self,
 		shorthand: impl Into<String>,
@@ -259,7 +1007,35 @@ pub trait MaterializeAppExt {
 	/// Same as [`register_generic_material`](MaterializeAppExt::register_generic_material), but with a provided default value.
 	///
 	/// This main use of this is for extended materials, allowing you to specify defaults for the base material that you wouldn't be able to otherwise.
-	fn register_generic_material_with_default<M: Material + Reflect + Struct + GetTypeRegistration>(&mut self, default_value: M) -> &mut Self;
+	fn register_generic_material_with_default<M: Material + Reflect + GetTypeRegistration>(&mut self, default_value: M) -> &mut Self;
+
+	/// Same as [`register_generic_material`](MaterializeAppExt::register_generic_material), but with the default value produced by `constructor`.
+	///
+	/// Useful for materials that need the world to construct, but can't implement [`FromWorld`]. Like [`FromWorld`], `constructor` is only called once.
+	///
+	/// # Examples
+	/// ```ignore
+	/// app.register_generic_material_with(|world| MyMaterial {
+	///     quality: world.resource::<GraphicsSettings>().quality,
+	///     ..default()
+	/// });
+	/// ```
+	fn register_generic_material_with<M: Material + Reflect + GetTypeRegistration>(&mut self, constructor: impl FnOnce(&mut World) -> M)
+	-> &mut Self;
+
+	/// Same as [`register_generic_material`](MaterializeAppExt::register_generic_material), but for materials that implement both [`Material`] and [`Material2d`](bevy::sprite_render::Material2d).
+	///
+	/// [`GenericMaterial3d`]s using this material will insert [`MeshMaterial2d`](bevy::sprite_render::MeshMaterial2d) on entities with [`Mesh2d`], and [`MeshMaterial3d`] otherwise.
+	#[cfg(feature = "bevy_sprite_render")]
+	fn register_generic_material_2d_and_3d<M: Material + bevy::sprite_render::Material2d + Reflect + FromWorld + GetTypeRegistration>(
+		&mut self,
+	) -> &mut Self;
+
+	/// Registers a material that only implements [`Material2d`](bevy::sprite_render::Material2d), such as [`ColorMaterial`], to be able to be created via [`GenericMaterial`].
+	///
+	/// Apply these with [`GenericMaterial2d`], which inserts [`MeshMaterial2d`](bevy::sprite_render::MeshMaterial2d). [`ColorMaterial`] is registered by default.
+	#[cfg(feature = "bevy_sprite_render")]
+	fn register_generic_material_2d<M: bevy::sprite_render::Material2d + Reflect + FromWorld + GetTypeRegistration>(&mut self) -> &mut Self;
 
 	/// If your material name is really long, you can use this to register a shorthand that can be used in place of it.
 	///
@@ -278,18 +1054,65 @@ pub trait MaterializeAppExt {
 	/// ```toml
 	/// type = "ShortName"
 	/// ```
+	///
+	/// If `shorthand` is already registered for a different type, it's overwritten with a warning.
 	fn register_generic_material_shorthand<M: GetTypeRegistration>(&mut self, shorthand: impl Into<String>) -> &mut Self;
+
+	/// Removes a shorthand registered with [`register_generic_material_shorthand`](MaterializeAppExt::register_generic_material_shorthand).
+	fn unregister_generic_material_shorthand(&mut self, shorthand: &str) -> &mut Self;
+
+	/// Registers a function that rewrites the value of `field` in materials of type `M` before it's deserialized.
+	/// Nested fields can be accessed with `.` (e.g. `extension.layer_0`).
+	///
+	/// This is a lighter-weight alternative to writing a [`MaterialProcessor`] when you only care about a single field.
+	/// `V` is the value type of the [`MaterialDeserializer`] in use, for example [`toml::Value`].
+	///
+	/// # Examples
+	/// ```ignore
+	/// app.register_generic_material_field_hook::<StandardMaterial, toml::Value>("base_color_texture", |value, _ctx| {
+	///     match value.as_str() {
+	///         Some(path) => toml::Value::String(path.replace("red", "blue")),
+	///         None => value,
+	///     }
+	/// });
+	/// ```
+	fn register_generic_material_field_hook<M: Material, V: GenericValue + 'static>(
+		&mut self,
+		field: impl Into<String>,
+		hook: FieldHook<V>,
+	) -> &mut Self;
+
+	/// Makes `field` of materials of type `M` readable and writable in files, even though it can't be accessed through reflection, usually because it's a foreign type ignored by reflection.
+	///
+	/// When loading, the field's value is deserialized as `V`, then given to `set`. When [serializing](GenericMaterial::serialize_with), `get` provides the value to write.
+	/// This also applies to materials of type `M` nested in other materials, such as the base material of an [`ExtendedMaterial`](bevy::pbr::ExtendedMaterial).
+	/// The [`wgpu_enums`](load::wgpu_enums) module has serializable versions of common `wgpu` types.
+	///
+	/// # Examples
+	/// ```ignore
+	/// app.register_generic_material_foreign_field::<MyMaterial, CompareFunctionDef>(
+	///     "depth_compare",
+	///     |material| material.depth_compare.into(),
+	///     |material, depth_compare| material.depth_compare = depth_compare.into(),
+	/// );
+	/// ```
+	fn register_generic_material_foreign_field<M: Material + Reflect, V: Serialize + DeserializeOwned + PartialEq + 'static>(
+		&mut self,
+		field: impl Into<String>,
+		get: fn(&M) -> V,
+		set: fn(&mut M, V),
+	) -> &mut Self;
 }
 #[cfg(feature = "bevy_pbr")]
 impl MaterializeAppExt for App {
-	fn register_generic_material<M: Material + Reflect + Struct + FromWorld + GetTypeRegistration>(&mut self) -> &mut Self {
+	fn register_generic_material<M: Material + Reflect + FromWorld + GetTypeRegistration>(&mut self) -> &mut Self {
 		let default_value = M::from_world(self.world_mut());
 		self.register_generic_material_with_default(default_value)
 	}
 
 	fn register_extended_generic_material<
-		Base: Material + FromReflect + Typed + Struct + FromWorld + GetTypeRegistration,
-		Ext: MaterialExtension + FromReflect + Typed + Struct + FromWorld + GetTypeRegistration,
+		Base: Material + FromReflect + Typed + FromWorld + GetTypeRegistration,
+		Ext: MaterialExtension + FromReflect + Typed + FromWorld + GetTypeRegistration,
 	>(
 		&mut self,
 		shorthand: impl Into<String>,
@@ -300,7 +1123,50 @@ impl MaterializeAppExt for App {
 			.register_generic_material_shorthand::<ExtendedMaterial<Base, Ext>>(shorthand)
 	}
 
-	fn register_generic_material_with_default<M: Material + Reflect + Struct + GetTypeRegistration>(&mut self, default_value: M) -> &mut Self {
+	fn register_generic_material_with_default<M: Material + Reflect + GetTypeRegistration>(&mut self, default_value: M) -> &mut Self {
+		let mut type_registry = self.world().resource::<AppTypeRegistry>().write();
+		if type_registry.get(TypeId::of::<M>()).is_none() {
+			type_registry.register::<M>();
+		}
+
+		type_registry.get_mut(TypeId::of::<M>()).unwrap().insert(ReflectGenericMaterial {
+			default_value: Box::new(default_value),
+			new_handle: |handle| ErasedMaterialHandle::new::<M>(handle.typed_debug_checked()),
+		});
+
+		drop(type_registry);
+
+		self
+	}
+
+	fn register_generic_material_with<M: Material + Reflect + GetTypeRegistration>(
+		&mut self,
+		constructor: impl FnOnce(&mut World) -> M,
+	) -> &mut Self {
+		let default_value = constructor(self.world_mut());
+		self.register_generic_material_with_default(default_value)
+	}
+
+	#[cfg(feature = "bevy_sprite_render")]
+	fn register_generic_material_2d_and_3d<M: Material + bevy::sprite_render::Material2d + Reflect + FromWorld + GetTypeRegistration>(
+		&mut self,
+	) -> &mut Self {
+		self.register_generic_material::<M>();
+
+		let mut type_registry = self.world().resource::<AppTypeRegistry>().write();
+		type_registry
+			.get_type_data_mut::<ReflectGenericMaterial>(TypeId::of::<M>())
+			.unwrap()
+			.new_handle = |handle| ErasedMaterialHandle::new_2d_and_3d::<M>(handle.typed_debug_checked());
+		drop(type_registry);
+
+		self
+	}
+
+	#[cfg(feature = "bevy_sprite_render")]
+	fn register_generic_material_2d<M: bevy::sprite_render::Material2d + Reflect + FromWorld + GetTypeRegistration>(&mut self) -> &mut Self {
+		let default_value = M::from_world(self.world_mut());
+
 		let mut type_registry = self.world().resource::<AppTypeRegistry>().write();
 		if type_registry.get(TypeId::of::<M>()).is_none() {
 			type_registry.register::<M>();
@@ -308,6 +1174,7 @@ impl MaterializeAppExt for App {
 
 		type_registry.get_mut(TypeId::of::<M>()).unwrap().insert(ReflectGenericMaterial {
 			default_value: Box::new(default_value),
+			new_handle: |handle| ErasedMaterialHandle::new_2d::<M>(handle.typed_debug_checked()),
 		});
 
 		drop(type_registry);
@@ -316,12 +1183,87 @@ impl MaterializeAppExt for App {
 	}
 
 	fn register_generic_material_shorthand<M: GetTypeRegistration>(&mut self, shorthand: impl Into<String>) -> &mut Self {
-		self.world()
+		let shorthand = shorthand.into();
+		let registration = M::get_type_registration();
+		let new_type_path = registration.type_info().type_path();
+
+		let previous = self
+			.world()
 			.resource::<GenericMaterialShorthands>()
-			.values
-			.write()
-			.unwrap()
-			.insert(shorthand.into(), M::get_type_registration());
+			.insert(shorthand.clone(), registration);
+
+		if let Some(previous) = previous
+			&& previous.type_id() != TypeId::of::<M>()
+		{
+			warn!(
+				"Generic material shorthand {shorthand} was registered for {}, overwriting it with {new_type_path}",
+				previous.type_info().type_path()
+			);
+		}
+
+		self
+	}
+
+	fn unregister_generic_material_shorthand(&mut self, shorthand: &str) -> &mut Self {
+		self.world().resource::<GenericMaterialShorthands>().remove(shorthand);
+		self
+	}
+
+	fn register_generic_material_field_hook<M: Material, V: GenericValue + 'static>(
+		&mut self,
+		field: impl Into<String>,
+		hook: FieldHook<V>,
+	) -> &mut Self {
+		self.world()
+			.resource::<GenericMaterialFieldHooks>()
+			.insert(TypeId::of::<M>(), field, hook);
+		self
+	}
+
+	fn register_generic_material_foreign_field<M: Material + Reflect, V: Serialize + DeserializeOwned + PartialEq + 'static>(
+		&mut self,
+		field: impl Into<String>,
+		get: fn(&M) -> V,
+		set: fn(&mut M, V),
+	) -> &mut Self {
+		self.world().resource::<GenericMaterialForeignFields>().insert(field, get, set);
 		self
 	}
 }
+
+/// Adds [`MaterialPlugin<M>`] if it hasn't been added yet, and registers `M` as a generic material with its short type name as a shorthand.
+///
+/// Using this in place of [`MaterialPlugin`] means you can't forget to call [`register_generic_material`](MaterializeAppExt::register_generic_material).
+///
+/// # Examples
+/// ```ignore
+/// app.add_plugins(GenericMaterialPlugin::<MyMaterial>::default());
+/// ```
+#[cfg(feature = "bevy_pbr")]
+pub struct GenericMaterialPlugin<M>(std::marker::PhantomData<fn() -> M>);
+#[cfg(feature = "bevy_pbr")]
+impl<M> Default for GenericMaterialPlugin<M> {
+	fn default() -> Self {
+		Self(std::marker::PhantomData)
+	}
+}
+#[cfg(feature = "bevy_pbr")]
+impl<M> Plugin for GenericMaterialPlugin<M>
+where
+	M: Material + Reflect + FromWorld + GetTypeRegistration,
+	MaterialPlugin<M>: Plugin + Default,
+{
+	fn build(&self, app: &mut App) {
+		if !app.is_plugin_added::<MaterialPlugin<M>>() {
+			app.add_plugins(MaterialPlugin::<M>::default());
+		}
+
+		let shorthand = M::get_type_registration().type_info().type_path_table().short_path().to_string();
+
+		#[rustfmt::skip]
+		app
+			.register_generic_material::<M>()
+			.register_generic_material_shorthand::<M>(shorthand)
+		;
+	}
+}
@@ -1,5 +1,7 @@
 use std::any::TypeId;
 
+#[cfg(feature = "bevy_image")]
+use bevy::image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor};
 use bevy::{
 	asset::{AssetPath, ParseAssetPathError, io::AssetSourceId},
 	prelude::*,
@@ -7,9 +9,23 @@ use bevy::{
 };
 use serde::Deserialize;
 
+use super::GenericMaterialLoadError;
+#[cfg(feature = "bevy_pbr")]
+use super::channels::{ChannelSwizzle, PendingChannelSwizzle};
 use super::processor::{MaterialProcessor, MaterialProcessorContext};
 
 /// Material processor that loads assets from paths.
+///
+/// Handles of assets that weren't registered with [`register_generic_material_sub_asset`](GenericMaterialSubAssetAppExt::register_generic_material_sub_asset)
+/// produce an error naming the missing registration.
+///
+/// Images can also be written as a table to set the sampler of just that image, e.g.
+/// `base_color_texture = { path = "bricks.png", sampler = "nearest", address_mode = "repeat" }`.
+///
+/// The table can also rearrange the image's channels with a [`ChannelSwizzle`](super::channels::ChannelSwizzle), for textures packed differently than the field expects,
+/// e.g. `metallic_roughness_texture = { path = "rma.png", channels = "brg" }` for a texture with roughness in red and metallic in green.
+/// The rearranged image is added as a sub-asset of the material.
+/// NOTE: Bevy caches assets by path, so an image loaded with different sampler settings elsewhere keeps whichever settings it was loaded with first.
 #[derive(TypePath, Clone)]
 pub struct AssetLoadingProcessor<P: MaterialProcessor>(pub P);
 impl<P: MaterialProcessor> MaterialProcessor for AssetLoadingProcessor<P> {
@@ -26,17 +42,162 @@ impl<P: MaterialProcessor> MaterialProcessor for AssetLoadingProcessor<P> {
 		deserializer: D,
 	) -> Result<Result<Box<dyn PartialReflect>, D>, D::Error> {
 		if let Some(loader) = registration.data::<ReflectGenericMaterialSubAsset>() {
-			let path = String::deserialize(deserializer)?;
+			#[allow(clippy::infallible_destructuring_match)] // Only infallible without `bevy_image`.
+			let path = match SubAssetPath::deserialize(deserializer)? {
+				SubAssetPath::Path(path) => path,
+				#[cfg(feature = "bevy_image")]
+				SubAssetPath::Image(image) => {
+					if registration.type_id() != TypeId::of::<Handle<Image>>() {
+						return Err(serde::de::Error::custom(format!(
+							"Sampler settings can only be given for images, not {}",
+							registration.type_info().type_path()
+						)));
+					}
+
+					if !ctx.allow_external_paths && is_external_asset_path(&image.path) {
+						return Err(serde::de::Error::custom(GenericMaterialLoadError::ExternalPath(image.path)));
+					}
+
+					let path = relative_asset_path(ctx.load_context.path(), &image.path).map_err(serde::de::Error::custom)?;
+					let descriptor = image.sampler_descriptor();
+
+					ctx.sub_assets_queued += 1;
+
+					// The image is read and rearranged after the material is deserialized, as that has to happen asynchronously.
+					#[cfg(feature = "bevy_pbr")]
+					if let Some(swizzle) = image.channels {
+						// Includes the sampler, as the same image may be rearranged the same way for fields with different samplers.
+						let label = format!("Channels/{swizzle}/{}/{path}", image.sampler_label());
+						let handle = ctx.load_context.get_label_handle::<Image>(&label);
+						ctx.channel_swizzles.push(PendingChannelSwizzle {
+							label,
+							path,
+							swizzle,
+							sampler: (image.sampler.is_some() || image.address_mode.is_some()).then_some(descriptor),
+						});
+
+						return Ok(Ok(Box::new(handle)));
+					}
+
+					let handle: Handle<Image> = ctx
+						.load_context
+						.load_builder()
+						.with_settings(move |settings: &mut ImageLoaderSettings| settings.sampler = ImageSampler::Descriptor(descriptor.clone()))
+						.load(path);
+
+					return Ok(Ok(Box::new(handle)));
+				}
+			};
+
+			if !ctx.allow_external_paths && is_external_asset_path(&path) {
+				return Err(serde::de::Error::custom(GenericMaterialLoadError::ExternalPath(path)));
+			}
 
 			let path = relative_asset_path(ctx.load_context.path(), &path).map_err(serde::de::Error::custom)?;
 
 			return Ok(Ok(loader.load(ctx, path)));
 		}
 
+		// Without this, handles of unregistered assets would fail to deserialize in much less helpful ways.
+		let type_path_table = registration.type_info().type_path_table();
+		if type_path_table.crate_name() == Some("bevy_asset") && type_path_table.ident() == Some("Handle") {
+			let asset = registration
+				.type_info()
+				.generics()
+				.iter()
+				.next()
+				.map_or(type_path_table.path(), |generic| generic.type_path());
+
+			return Err(serde::de::Error::custom(format!(
+				"{asset} isn't registered as a generic material sub-asset, so it can't be loaded from a path. \
+				Register it with `app.register_generic_material_sub_asset::<{asset}>()`"
+			)));
+		}
+
 		Ok(Err(deserializer))
 	}
 }
 
+/// How a sub-asset is written in a material file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SubAssetPath {
+	Path(String),
+	#[cfg(feature = "bevy_image")]
+	Image(ImageWithSampler),
+}
+
+/// Table form of an image path, overriding the sampler for just that image.
+#[cfg(feature = "bevy_image")]
+#[derive(Deserialize)]
+struct ImageWithSampler {
+	path: String,
+	sampler: Option<SamplerFilter>,
+	address_mode: Option<SamplerAddressMode>,
+	#[cfg(feature = "bevy_pbr")]
+	channels: Option<ChannelSwizzle>,
+}
+#[cfg(feature = "bevy_image")]
+impl ImageWithSampler {
+	fn sampler_descriptor(&self) -> ImageSamplerDescriptor {
+		// Filtering defaults to linear, like `ImagePlugin::default`.
+		let mut descriptor = match self.sampler {
+			Some(SamplerFilter::Nearest) => ImageSamplerDescriptor::nearest(),
+			Some(SamplerFilter::Linear) | None => ImageSamplerDescriptor::linear(),
+		};
+
+		if let Some(address_mode) = self.address_mode {
+			let address_mode = address_mode.into();
+			descriptor.address_mode_u = address_mode;
+			descriptor.address_mode_v = address_mode;
+			descriptor.address_mode_w = address_mode;
+		}
+
+		descriptor
+	}
+
+	/// The sampler settings as part of a sub-asset label, e.g. `Nearest-Repeat`, or `Default-Default` if none are set.
+	#[cfg(feature = "bevy_pbr")]
+	fn sampler_label(&self) -> String {
+		let sampler = self.sampler.map_or(String::from("Default"), |sampler| format!("{sampler:?}"));
+		let address_mode = self
+			.address_mode
+			.map_or(String::from("Default"), |address_mode| format!("{address_mode:?}"));
+		format!("{sampler}-{address_mode}")
+	}
+}
+
+/// The `sampler` of an image written as a table in a material file.
+#[cfg(feature = "bevy_image")]
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum SamplerFilter {
+	Nearest,
+	Linear,
+}
+
+/// The `address_mode` of an image written as a table in a material file, applied to every axis.
+#[cfg(feature = "bevy_image")]
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum SamplerAddressMode {
+	ClampToEdge,
+	Repeat,
+	MirrorRepeat,
+	ClampToBorder,
+}
+#[cfg(feature = "bevy_image")]
+impl From<SamplerAddressMode> for ImageAddressMode {
+	fn from(value: SamplerAddressMode) -> Self {
+		match value {
+			SamplerAddressMode::ClampToEdge => Self::ClampToEdge,
+			SamplerAddressMode::Repeat => Self::Repeat,
+			SamplerAddressMode::MirrorRepeat => Self::MirrorRepeat,
+			SamplerAddressMode::ClampToBorder => Self::ClampToBorder,
+		}
+	}
+}
+
 /// Reflected function that loads an asset. Used for asset loading from paths in generic materials.
 #[derive(Debug, Clone)]
 pub struct ReflectGenericMaterialSubAsset {
@@ -44,6 +205,7 @@ pub struct ReflectGenericMaterialSubAsset {
 }
 impl ReflectGenericMaterialSubAsset {
 	pub fn load(&self, ctx: &mut MaterialProcessorContext, path: AssetPath<'static>) -> Box<dyn PartialReflect> {
+		ctx.sub_assets_queued += 1;
 		(self.load)(ctx, path)
 	}
 }
@@ -52,16 +214,24 @@ pub trait GenericMaterialSubAssetAppExt {
 	/// Registers an asset to be able to be loaded within a [`GenericMaterial`](crate::GenericMaterial).
 	///
 	/// Specifically, it allows loading of [`Handle<A>`] by simply providing a path relative to the material's directory.
+	///
+	/// [`Handle<A>`] is registered in the type registry if it isn't already.
 	fn register_generic_material_sub_asset<A: Asset>(&mut self) -> &mut Self;
 }
 impl GenericMaterialSubAssetAppExt for App {
 	#[track_caller]
 	fn register_generic_material_sub_asset<A: Asset>(&mut self) -> &mut Self {
-		let mut type_registry = self.world().resource::<AppTypeRegistry>().write();
-		let registration = match type_registry.get_mut(TypeId::of::<Handle<A>>()) {
-			Some(x) => x,
-			None => panic!("Asset handle not registered: {}", std::any::type_name::<A>()),
+		let Some(type_registry) = self.world().get_resource::<AppTypeRegistry>() else {
+			panic!(
+				"Can't register {} as a generic material sub-asset, because the app has no type registry",
+				std::any::type_name::<A>()
+			);
 		};
+		let mut type_registry = type_registry.write();
+
+		// Registering the handle here rather than requiring it be registered already means this doesn't depend on plugin order.
+		type_registry.register::<Handle<A>>();
+		let registration = type_registry.get_mut(TypeId::of::<Handle<A>>()).unwrap();
 
 		registration.insert(ReflectGenericMaterialSubAsset {
 			load: |processor, path| Box::new(processor.load_context.load::<A>(path)),
@@ -73,6 +243,19 @@ impl GenericMaterialSubAssetAppExt for App {
 	}
 }
 
+/// Returns whether a path written in a material file starts at the root of an asset source, or names an asset source, instead of being relative to the material.
+///
+/// # Examples
+/// ```
+/// # use bevy_materialize::load::asset::is_external_asset_path;
+/// assert!(!is_external_asset_path("textures/foo.png"));
+/// assert!(is_external_asset_path("/textures/foo.png"));
+/// assert!(is_external_asset_path("other://textures/foo.png"));
+/// ```
+pub fn is_external_asset_path(path: &str) -> bool {
+	path.starts_with(['/', '\\']) || path.contains("://")
+}
+
 /// Produces an asset path relative to another for use in generic material loading.
 ///
 /// # Examples
@@ -81,6 +264,7 @@ impl GenericMaterialSubAssetAppExt for App {
 /// assert_eq!(relative_asset_path(&"materials/foo.toml".into(), "foo.png").unwrap(), "materials/foo.png".into());
 /// assert_eq!(relative_asset_path(&"materials/foo.toml".into(), "textures/foo.png").unwrap(), "materials/textures/foo.png".into());
 /// assert_eq!(relative_asset_path(&"materials/foo.toml".into(), "/textures/foo.png").unwrap(), "textures/foo.png".into());
+/// assert_eq!(relative_asset_path(&"materials/foo.toml".into(), "/textures/../foo.png").unwrap(), "foo.png".into());
 /// assert_eq!(relative_asset_path(&"materials/foo.toml".into(), "\\textures\\foo.png").unwrap(), "textures\\foo.png".into());
 /// ```
 pub fn relative_asset_path(relative_to: &AssetPath<'static>, path: &str) -> Result<AssetPath<'static>, ParseAssetPathError> {
@@ -90,7 +274,8 @@ pub fn relative_asset_path(relative_to: &AssetPath<'static>, path: &str) -> Resu
 	let root_pattern = ['/', '\\'];
 
 	if path.starts_with(root_pattern) {
-		let mut asset_path = AssetPath::try_parse(path.trim_start_matches(root_pattern))?.into_owned();
+		// Resolving against the root normalizes the path, so it matches the paths of file change events when used as a loader dependency.
+		let mut asset_path = AssetPath::default().resolve(&AssetPath::try_parse(path.trim_start_matches(root_pattern))?);
 		if let AssetSourceId::Default = asset_path.source() {
 			asset_path = asset_path.with_source(relative_to.source().clone_owned());
 		}
@@ -0,0 +1,210 @@
+//! Rearranging the channels of images, for textures packed differently than materials expect (e.g. roughness, metallic, and occlusion in one image).
+
+use std::fmt;
+
+use bevy::asset::AssetPath;
+#[cfg(feature = "bevy_image")]
+use bevy::image::ImageSamplerDescriptor;
+#[cfg(feature = "bevy_pbr")]
+use bevy::prelude::*;
+#[cfg(feature = "bevy_pbr")]
+use bevy::render::render_resource::TextureFormat;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Where each channel of an image is taken from, written like a shader swizzle.
+///
+/// The characters of the string are the sources of the red, green, blue, and alpha channels in order, each one of `r`, `g`, `b`, `a`, `0`, or `1`.
+/// Channels past the end of the string are left as they are.
+///
+/// # Examples
+/// ```
+/// # use bevy_materialize::load::channels::{ChannelSwizzle, ChannelSource};
+/// // Moves roughness and metallic from red and green to green and blue, where `metallic_roughness_texture` reads them.
+/// let swizzle: ChannelSwizzle = "brg".parse().unwrap();
+/// assert_eq!(swizzle.0, [ChannelSource::B, ChannelSource::R, ChannelSource::G, ChannelSource::A]);
+/// ```
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(try_from = "String")]
+pub struct ChannelSwizzle(pub [ChannelSource; 4]);
+impl ChannelSwizzle {
+	pub const IDENTITY: Self = Self([ChannelSource::R, ChannelSource::G, ChannelSource::B, ChannelSource::A]);
+
+	/// Rearranges the channels of every pixel of an uncompressed RGBA image in-place.
+	///
+	/// Supports 8 and 16 bit normalized, and 16 and 32 bit float formats.
+	#[cfg(feature = "bevy_pbr")]
+	pub fn apply(&self, image: &mut Image) -> Result<(), ChannelSwizzleError> {
+		let format = image.texture_descriptor.format;
+		// The bytes of a channel at its maximum, written for `ChannelSource::One`.
+		let one: &[u8] = match format {
+			TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => &[u8::MAX],
+			TextureFormat::Rgba16Unorm => &u16::MAX.to_ne_bytes(),
+			// 1.0 as a half-precision float.
+			TextureFormat::Rgba16Float => &0x3c00_u16.to_ne_bytes(),
+			TextureFormat::Rgba32Float => &1_f32.to_ne_bytes(),
+			_ => return Err(ChannelSwizzleError::UnsupportedFormat(format!("{format:?}"))),
+		};
+		let channel_size = one.len();
+		let Some(data) = &mut image.data else { return Err(ChannelSwizzleError::NoData) };
+
+		let mut original = [0; 16];
+		for pixel in data.chunks_exact_mut(channel_size * 4) {
+			original[..pixel.len()].copy_from_slice(pixel);
+
+			for (channel, source) in pixel.chunks_exact_mut(channel_size).zip(self.0) {
+				match source {
+					ChannelSource::Zero => channel.fill(0),
+					ChannelSource::One => channel.copy_from_slice(one),
+					source => {
+						let start = source as usize * channel_size;
+						channel.copy_from_slice(&original[start..start + channel_size]);
+					}
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+impl std::str::FromStr for ChannelSwizzle {
+	type Err = ChannelSwizzleError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.is_empty() || s.chars().count() > 4 {
+			return Err(ChannelSwizzleError::WrongLength(s.to_string()));
+		}
+
+		let mut swizzle = Self::IDENTITY;
+		for (i, c) in s.chars().enumerate() {
+			swizzle.0[i] = match c.to_ascii_lowercase() {
+				'r' => ChannelSource::R,
+				'g' => ChannelSource::G,
+				'b' => ChannelSource::B,
+				'a' => ChannelSource::A,
+				'0' => ChannelSource::Zero,
+				'1' => ChannelSource::One,
+				c => return Err(ChannelSwizzleError::InvalidChannel(c)),
+			};
+		}
+
+		Ok(swizzle)
+	}
+}
+impl TryFrom<String> for ChannelSwizzle {
+	type Error = ChannelSwizzleError;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+impl fmt::Display for ChannelSwizzle {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for source in self.0 {
+			let c = match source {
+				ChannelSource::R => 'r',
+				ChannelSource::G => 'g',
+				ChannelSource::B => 'b',
+				ChannelSource::A => 'a',
+				ChannelSource::Zero => '0',
+				ChannelSource::One => '1',
+			};
+			write!(f, "{c}")?;
+		}
+		Ok(())
+	}
+}
+
+/// Where a channel of a [`ChannelSwizzle`]d image is taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelSource {
+	R,
+	G,
+	B,
+	A,
+	Zero,
+	One,
+}
+
+/// An image queued by [`AssetLoadingProcessor`](super::asset::AssetLoadingProcessor) to have its channels rearranged,
+/// which is then read and added as a sub-asset labeled `label` once the material is deserialized.
+#[derive(Debug, Clone)]
+pub struct PendingChannelSwizzle {
+	pub label: String,
+	pub path: AssetPath<'static>,
+	pub swizzle: ChannelSwizzle,
+	#[cfg(feature = "bevy_image")]
+	pub sampler: Option<ImageSamplerDescriptor>,
+}
+
+/// Errors that may occur when parsing or applying a [`ChannelSwizzle`].
+#[derive(Error, Debug, Clone)]
+pub enum ChannelSwizzleError {
+	#[error("Channel swizzles must have 1 to 4 channels, `{0}` doesn't")]
+	WrongLength(String),
+	#[error("`{0}` isn't a channel, channels are one of `r`, `g`, `b`, `a`, `0`, or `1`")]
+	InvalidChannel(char),
+	#[error("Can't rearrange the channels of images with format {0}, only uncompressed 8, 16, and 32 bit RGBA images are supported")]
+	UnsupportedFormat(String),
+	#[error("The image has no data on the CPU")]
+	NoData,
+}
+
+#[cfg(feature = "bevy_pbr")]
+#[cfg(test)]
+fn test_image(format: TextureFormat, data: Vec<u8>) -> Image {
+	use bevy::asset::RenderAssetUsages;
+	use bevy::render::render_resource::{Extent3d, TextureDimension};
+
+	let width = (data.len() / format.pixel_size().unwrap()) as u32;
+	Image::new(
+		Extent3d {
+			width,
+			height: 1,
+			depth_or_array_layers: 1,
+		},
+		TextureDimension::D2,
+		data,
+		format,
+		RenderAssetUsages::default(),
+	)
+}
+
+#[cfg(feature = "bevy_pbr")]
+#[test]
+fn swizzle_rgba8() {
+	let mut image = test_image(TextureFormat::Rgba8Unorm, vec![10, 20, 30, 40, 50, 60, 70, 80]);
+	"brg".parse::<ChannelSwizzle>().unwrap().apply(&mut image).unwrap();
+	assert_eq!(image.data.unwrap(), [30, 10, 20, 40, 70, 50, 60, 80]);
+
+	let mut image = test_image(TextureFormat::Rgba8UnormSrgb, vec![10, 20, 30, 40]);
+	"01ar".parse::<ChannelSwizzle>().unwrap().apply(&mut image).unwrap();
+	assert_eq!(image.data.unwrap(), [0, 255, 40, 10]);
+}
+
+#[cfg(feature = "bevy_pbr")]
+#[test]
+fn swizzle_wide_formats() {
+	let pixel = |channels: [u16; 4]| channels.iter().flat_map(|channel| channel.to_ne_bytes()).collect::<Vec<_>>();
+	let mut image = test_image(TextureFormat::Rgba16Unorm, pixel([1, 2, 3, 4]));
+	"g1r".parse::<ChannelSwizzle>().unwrap().apply(&mut image).unwrap();
+	assert_eq!(image.data.unwrap(), pixel([2, u16::MAX, 1, 4]));
+
+	let pixel = |channels: [f32; 4]| channels.iter().flat_map(|channel| channel.to_ne_bytes()).collect::<Vec<_>>();
+	let mut image = test_image(TextureFormat::Rgba32Float, pixel([0.1, 0.2, 0.3, 0.4]));
+	"a1g0".parse::<ChannelSwizzle>().unwrap().apply(&mut image).unwrap();
+	assert_eq!(image.data.unwrap(), pixel([0.4, 1.0, 0.2, 0.0]));
+}
+
+#[cfg(feature = "bevy_pbr")]
+#[test]
+fn swizzle_unsupported_images() {
+	let swizzle = ChannelSwizzle::IDENTITY;
+
+	let mut image = test_image(TextureFormat::R8Unorm, vec![10]);
+	assert!(matches!(swizzle.apply(&mut image), Err(ChannelSwizzleError::UnsupportedFormat(_))));
+
+	let mut image = test_image(TextureFormat::Rgba8Unorm, vec![10, 20, 30, 40]);
+	image.data = None;
+	assert!(matches!(swizzle.apply(&mut image), Err(ChannelSwizzleError::NoData)));
+}
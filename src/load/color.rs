@@ -0,0 +1,191 @@
+use std::{any::TypeId, fmt};
+
+use bevy::{
+	color::ColorToComponents,
+	prelude::*,
+	reflect::{TypeRegistration, TypeRegistry, serde::TypedReflectDeserializer},
+};
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor, value::MapAccessDeserializer};
+
+use super::processor::{ColorSpace, MaterialProcessor, MaterialProcessorContext};
+
+/// Material processor that lets [`Color`], [`Srgba`], and [`LinearRgba`] be written as component arrays with an explicit color space, or as CSS-like strings.
+///
+/// Arrays of 3 or 4 components are interpreted in the file's `color_space`, or the type's own color space if it isn't set.
+/// A single field can override this by wrapping its components like `emissive = { srgb = [1.0, 0.5, 0.0] }`.
+/// Strings can be hex codes (`"#ff8040"`, `"#ff8040cc"`, `"#f84"`), `"rgb(255, 128, 64)"`, or `"rgba(255, 128, 64, 0.5)"`, and are always sRGB.
+/// Any other representation is deserialized as usual.
+///
+/// # Examples
+/// ```toml
+/// color_space = "srgb"
+///
+/// [material]
+/// base_color = [1.0, 0.5, 0.0]
+/// emissive = { linear = [33.0, 0.3, 33.0, 3.6] }
+/// attenuation_color = "#ff8040"
+/// ```
+#[derive(TypePath, Clone)]
+pub struct ColorProcessor<P: MaterialProcessor>(pub P);
+impl<P: MaterialProcessor> MaterialProcessor for ColorProcessor<P> {
+	type Child = P;
+	fn child(&self) -> Option<&Self::Child> {
+		Some(&self.0)
+	}
+
+	fn try_deserialize<'de, D: serde::Deserializer<'de>>(
+		&self,
+		ctx: &mut MaterialProcessorContext,
+		registration: &TypeRegistration,
+		registry: &TypeRegistry,
+		deserializer: D,
+	) -> Result<Result<Box<dyn PartialReflect>, D>, D::Error> {
+		let ty = match registration.type_id() {
+			id if id == TypeId::of::<Color>() => ColorType::Color,
+			id if id == TypeId::of::<Srgba>() => ColorType::Srgba,
+			id if id == TypeId::of::<LinearRgba>() => ColorType::LinearRgba,
+			_ => return Ok(Err(deserializer)),
+		};
+
+		deserializer
+			.deserialize_any(ColorVisitor {
+				ty,
+				file_color_space: ctx.color_space,
+				registration,
+				registry,
+			})
+			.map(Ok)
+	}
+}
+
+#[derive(Clone, Copy)]
+enum ColorType {
+	Color,
+	Srgba,
+	LinearRgba,
+}
+impl ColorType {
+	fn native_color_space(self) -> ColorSpace {
+		match self {
+			Self::Color | Self::Srgba => ColorSpace::Srgb,
+			Self::LinearRgba => ColorSpace::Linear,
+		}
+	}
+
+	fn with_components(self, components: &[f32], color_space: Option<ColorSpace>) -> Result<Box<dyn PartialReflect>, String> {
+		let [red, green, blue, alpha] = match *components {
+			[red, green, blue] => [red, green, blue, 1.],
+			[red, green, blue, alpha] => [red, green, blue, alpha],
+			_ => return Err(format!("Expected 3 or 4 color components, found {}", components.len())),
+		};
+
+		let color = match color_space.unwrap_or(self.native_color_space()) {
+			ColorSpace::Srgb => Color::srgba(red, green, blue, alpha),
+			ColorSpace::Linear => Color::linear_rgba(red, green, blue, alpha),
+		};
+
+		Ok(match self {
+			Self::Color => Box::new(color),
+			Self::Srgba => Box::new(color.to_srgba()),
+			Self::LinearRgba => Box::new(color.to_linear()),
+		})
+	}
+}
+
+struct ColorVisitor<'a> {
+	ty: ColorType,
+	file_color_space: Option<ColorSpace>,
+	registration: &'a TypeRegistration,
+	registry: &'a TypeRegistry,
+}
+impl<'de> Visitor<'de> for ColorVisitor<'_> {
+	type Value = Box<dyn PartialReflect>;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "color components or {}", self.registration.type_info().type_path())
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+		let mut components = Vec::with_capacity(4);
+		while let Some(component) = seq.next_element::<f32>()? {
+			components.push(component);
+		}
+
+		self.ty.with_components(&components, self.file_color_space).map_err(de::Error::custom)
+	}
+
+	fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+		let components = parse_color_string(v).ok_or_else(|| E::custom(format!("`{v}` isn't a hex color, `rgb(...)`, or `rgba(...)`")))?;
+
+		self.ty.with_components(&components, Some(ColorSpace::Srgb)).map_err(E::custom)
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+		let Some(key) = map.next_key::<String>()? else {
+			return Err(de::Error::invalid_length(0, &self));
+		};
+
+		let color_space = match key.as_str() {
+			"srgb" => ColorSpace::Srgb,
+			"linear" => ColorSpace::Linear,
+			// Not an explicit color space, give the map back to regular deserialization.
+			_ => {
+				return TypedReflectDeserializer::new(self.registration, self.registry)
+					.deserialize(MapAccessDeserializer::new(PrependedKeyMap { key: Some(key), map }));
+			}
+		};
+
+		let components: Vec<f32> = map.next_value()?;
+		if let Some(key) = map.next_key::<String>()? {
+			return Err(de::Error::custom(format!("Unexpected key `{key}` after color components")));
+		}
+
+		self.ty.with_components(&components, Some(color_space)).map_err(de::Error::custom)
+	}
+}
+
+/// Parses a hex color, `rgb(r, g, b)`, or `rgba(r, g, b, a)` into sRGB components. RGB channels are from `0` to `255`, alpha is from `0` to `1`.
+fn parse_color_string(s: &str) -> Option<[f32; 4]> {
+	let s = s.trim();
+
+	if s.starts_with('#') {
+		return Srgba::hex(s).ok().map(|color| color.to_f32_array());
+	}
+
+	let (args, has_alpha) = if let Some(args) = s.strip_prefix("rgba(") {
+		(args, true)
+	} else {
+		(s.strip_prefix("rgb(")?, false)
+	};
+	let args: Vec<f32> = args
+		.strip_suffix(')')?
+		.split(',')
+		.map(|arg| arg.trim().parse().ok())
+		.collect::<Option<_>>()?;
+
+	match (args.as_slice(), has_alpha) {
+		(&[red, green, blue], false) => Some([red / 255., green / 255., blue / 255., 1.]),
+		(&[red, green, blue, alpha], true) => Some([red / 255., green / 255., blue / 255., alpha]),
+		_ => None,
+	}
+}
+
+/// Yields a key that was already read before the rest of the map.
+struct PrependedKeyMap<A> {
+	key: Option<String>,
+	map: A,
+}
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for PrependedKeyMap<A> {
+	type Error = A::Error;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+		match self.key.take() {
+			Some(key) => seed.deserialize(de::value::StringDeserializer::new(key)).map(Some),
+			None => self.map.next_key_seed(seed),
+		}
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+		self.map.next_value_seed(seed)
+	}
+}
@@ -3,12 +3,16 @@ use serde::de::DeserializeOwned;
 
 use super::*;
 
-/// Main trait for file format implementation of generic materials. See [`TomlMaterialDeserializer`] and [`JsonMaterialDeserializer`] for built-in/example implementations.
+/// Main trait for file format implementation of generic materials. See [`TomlMaterialDeserializer`], [`JsonMaterialDeserializer`], and `KdlMaterialDeserializer` for built-in/example implementations.
 pub trait MaterialDeserializer: TypePath + Send + Sync + 'static {
 	type Value: GenericValue + DeserializeOwned;
 	type Error: serde::de::Error + Send + Sync;
 	/// The asset loader's file extensions.
 	const EXTENSIONS: &[&str];
+	/// File extensions of [`GenericMaterialSet`](super::set::GenericMaterialSet)s in this format. (Default: none)
+	const SET_EXTENSIONS: &[&str] = &[];
+	/// File extensions of [`GenericMaterialLibrary`](super::library::GenericMaterialLibrary)s in this format. (Default: none)
+	const LIBRARY_EXTENSIONS: &[&str] = &[];
 
 	/// Deserializes raw bytes into a value.
 	fn deserialize<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, Self::Error>;
@@ -17,6 +21,57 @@ pub trait MaterialDeserializer: TypePath + Send + Sync + 'static {
 	///
 	/// Implementors should recursively merge maps, and overwrite everything else.
 	fn merge_value(&self, value: &mut Self::Value, other: Self::Value);
+
+	/// Returns the value of a field in a map value, used for [field hooks](crate::MaterializeAppExt::register_generic_material_field_hook).
+	///
+	/// Field hooks won't run for this format if this isn't implemented.
+	fn field_mut<'a>(&self, value: &'a mut Self::Value, field: &str) -> Option<&'a mut Self::Value> {
+		let _ = (value, field);
+		None
+	}
+
+	/// Removes a field from a map value and returns it, used for [foreign fields](crate::MaterializeAppExt::register_generic_material_foreign_field).
+	///
+	/// Foreign fields can't be set from files of this format if this isn't implemented.
+	fn remove_field(&self, value: &mut Self::Value, field: &str) -> Option<Self::Value> {
+		let _ = (value, field);
+		None
+	}
+
+	/// Removes the `defs` table from a file's value, and replaces every string of the form `@name` in it with the definition of `name`.
+	/// Strings starting with `@@` are unescaped to start with a single `@` instead.
+	///
+	/// Definitions can't be used in files of this format if this isn't implemented.
+	fn resolve_defs(&self, value: &mut Self::Value) -> Result<(), Self::Error> {
+		let _ = value;
+		Ok(())
+	}
+}
+
+/// The key of the table of named values that can be referenced elsewhere in the same file with `@name`. See [`MaterialDeserializer::resolve_defs`].
+pub const DEFS_KEY: &str = "defs";
+
+/// A string referring to the `defs` of a file, see [`MaterialDeserializer::resolve_defs`].
+pub enum DefReference<'a> {
+	/// A `@name` reference to a definition.
+	Def(&'a str),
+	/// A string starting with `@@`, standing for itself without the first `@`.
+	Escaped(&'a str),
+}
+impl<'a> DefReference<'a> {
+	/// Returns [`None`] if `s` doesn't start with `@`.
+	pub fn parse(s: &'a str) -> Option<Self> {
+		let rest = s.strip_prefix('@')?;
+		Some(if rest.starts_with('@') { Self::Escaped(rest) } else { Self::Def(rest) })
+	}
+
+	/// Returns the value this string should be replaced with, given a function to look up definitions and one to create string values.
+	pub fn resolve<V, E: serde::de::Error>(self, get_def: impl FnOnce(&str) -> Option<V>, string: impl FnOnce(String) -> V) -> Result<V, E> {
+		match self {
+			Self::Def(name) => get_def(name).ok_or_else(|| E::custom(format!("No definition named `{name}` in `{DEFS_KEY}`"))),
+			Self::Escaped(s) => Ok(string(s.to_string())),
+		}
+	}
 }
 
 #[cfg(feature = "toml")]
@@ -27,6 +82,8 @@ impl MaterialDeserializer for TomlMaterialDeserializer {
 	type Value = toml::Value;
 	type Error = toml::de::Error;
 	const EXTENSIONS: &[&str] = &["toml", "mat", "mat.toml", "material", "material.toml"];
+	const SET_EXTENSIONS: &[&str] = &["materialset.toml"];
+	const LIBRARY_EXTENSIONS: &[&str] = &["materiallib.toml"];
 
 	fn deserialize<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, Self::Error> {
 		let s = str::from_utf8(input).map_err(serde::de::Error::custom)?;
@@ -48,6 +105,35 @@ impl MaterialDeserializer for TomlMaterialDeserializer {
 			(value, other) => *value = other,
 		}
 	}
+
+	fn field_mut<'a>(&self, value: &'a mut Self::Value, field: &str) -> Option<&'a mut Self::Value> {
+		value.as_table_mut()?.get_mut(field)
+	}
+
+	fn remove_field(&self, value: &mut Self::Value, field: &str) -> Option<Self::Value> {
+		value.as_table_mut()?.remove(field)
+	}
+
+	fn resolve_defs(&self, value: &mut Self::Value) -> Result<(), Self::Error> {
+		fn resolve(value: &mut toml::Value, defs: &toml::Table) -> Result<(), toml::de::Error> {
+			match value {
+				toml::Value::String(s) => {
+					let Some(reference) = DefReference::parse(s) else { return Ok(()) };
+					*value = reference.resolve(|name| defs.get(name).cloned(), toml::Value::String)?;
+				}
+				toml::Value::Array(array) => array.iter_mut().try_for_each(|value| resolve(value, defs))?,
+				toml::Value::Table(table) => table.iter_mut().try_for_each(|(_, value)| resolve(value, defs))?,
+				_ => {}
+			}
+			Ok(())
+		}
+
+		let Some(defs) = value.as_table_mut().and_then(|table| table.remove(DEFS_KEY)) else { return Ok(()) };
+		let toml::Value::Table(defs) = defs else {
+			return Err(serde::de::Error::custom(format!("`{DEFS_KEY}` must be a table")));
+		};
+		resolve(value, &defs)
+	}
 }
 
 #[cfg(feature = "json")]
@@ -58,10 +144,12 @@ impl MaterialDeserializer for JsonMaterialDeserializer {
 	type Value = serde_json::Value;
 	type Error = serde_json::Error;
 	const EXTENSIONS: &[&str] = &["json", "mat", "mat.json", "material", "material.json"];
+	const SET_EXTENSIONS: &[&str] = &["materialset.json"];
+	const LIBRARY_EXTENSIONS: &[&str] = &["materiallib.json"];
 
 	fn deserialize<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, Self::Error> {
-		let s = str::from_utf8(input).map_err(serde::de::Error::custom)?;
-		serde_json::from_str(s)
+		// Parses straight from the bytes, validating UTF-8 as it goes.
+		serde_json::from_slice(input)
 	}
 
 	fn merge_value(&self, value: &mut Self::Value, other: Self::Value) {
@@ -79,4 +167,98 @@ impl MaterialDeserializer for JsonMaterialDeserializer {
 			(value, other) => *value = other,
 		}
 	}
+
+	fn field_mut<'a>(&self, value: &'a mut Self::Value, field: &str) -> Option<&'a mut Self::Value> {
+		value.as_object_mut()?.get_mut(field)
+	}
+
+	fn remove_field(&self, value: &mut Self::Value, field: &str) -> Option<Self::Value> {
+		value.as_object_mut()?.remove(field)
+	}
+
+	fn resolve_defs(&self, value: &mut Self::Value) -> Result<(), Self::Error> {
+		fn resolve(value: &mut serde_json::Value, defs: &serde_json::Map<String, serde_json::Value>) -> Result<(), serde_json::Error> {
+			match value {
+				serde_json::Value::String(s) => {
+					let Some(reference) = DefReference::parse(s) else { return Ok(()) };
+					*value = reference.resolve(|name| defs.get(name).cloned(), serde_json::Value::String)?;
+				}
+				serde_json::Value::Array(array) => array.iter_mut().try_for_each(|value| resolve(value, defs))?,
+				serde_json::Value::Object(object) => object.values_mut().try_for_each(|value| resolve(value, defs))?,
+				_ => {}
+			}
+			Ok(())
+		}
+
+		let Some(defs) = value.as_object_mut().and_then(|object| object.remove(DEFS_KEY)) else { return Ok(()) };
+		let serde_json::Value::Object(defs) = defs else {
+			return Err(serde::de::Error::custom(format!("`{DEFS_KEY}` must be an object")));
+		};
+		resolve(value, &defs)
+	}
+}
+
+/// Reads materials from [KDL](https://kdl.dev) documents. See [`kdl_value::Value`](crate::kdl_value::Value) for how documents are structured.
+#[cfg(feature = "kdl")]
+#[derive(TypePath, Debug, Clone, Default)]
+pub struct KdlMaterialDeserializer;
+#[cfg(feature = "kdl")]
+impl MaterialDeserializer for KdlMaterialDeserializer {
+	type Value = crate::kdl_value::Value;
+	type Error = crate::kdl_value::Error;
+	const EXTENSIONS: &[&str] = &["kdl", "mat.kdl", "material.kdl"];
+	const SET_EXTENSIONS: &[&str] = &["materialset.kdl"];
+	const LIBRARY_EXTENSIONS: &[&str] = &["materiallib.kdl"];
+
+	fn deserialize<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, Self::Error> {
+		let s = str::from_utf8(input).map_err(serde::de::Error::custom)?;
+		T::deserialize(crate::kdl_value::Value::parse(s)?)
+	}
+
+	fn merge_value(&self, value: &mut Self::Value, other: Self::Value) {
+		match (value, other) {
+			(crate::kdl_value::Value::Map(value), crate::kdl_value::Value::Map(other)) => {
+				for (key, other_value) in other {
+					match value.get_mut(&key) {
+						Some(value) => self.merge_value(value, other_value),
+						None => {
+							value.insert(key, other_value);
+						}
+					}
+				}
+			}
+			(value, other) => *value = other,
+		}
+	}
+
+	fn field_mut<'a>(&self, value: &'a mut Self::Value, field: &str) -> Option<&'a mut Self::Value> {
+		value.as_map_mut()?.get_mut(field)
+	}
+
+	fn remove_field(&self, value: &mut Self::Value, field: &str) -> Option<Self::Value> {
+		value.as_map_mut()?.remove(field)
+	}
+
+	fn resolve_defs(&self, value: &mut Self::Value) -> Result<(), Self::Error> {
+		use crate::kdl_value::Value;
+
+		fn resolve(value: &mut Value, defs: &BTreeMap<String, Value>) -> Result<(), crate::kdl_value::Error> {
+			match value {
+				Value::String(s) => {
+					let Some(reference) = DefReference::parse(s) else { return Ok(()) };
+					*value = reference.resolve(|name| defs.get(name).cloned(), Value::String)?;
+				}
+				Value::Array(array) => array.iter_mut().try_for_each(|value| resolve(value, defs))?,
+				Value::Map(map) => map.values_mut().try_for_each(|value| resolve(value, defs))?,
+				_ => {}
+			}
+			Ok(())
+		}
+
+		let Some(defs) = value.as_map_mut().and_then(|map| map.remove(DEFS_KEY)) else { return Ok(()) };
+		let Value::Map(defs) = defs else {
+			return Err(serde::de::Error::custom(format!("`{DEFS_KEY}` must be a node with children")));
+		};
+		resolve(value, &defs)
+	}
 }
@@ -1,8 +1,13 @@
 use std::{error::Error, io};
 
+#[cfg(feature = "bevy_pbr")]
+use bevy::asset::AssetLoadError;
+use bevy::asset::{AssetPath, io::AssetSourceId};
 use bevy::reflect::{ApplyError, TypeInfo};
 use thiserror::Error;
 
+use super::channels::ChannelSwizzleError;
+
 /// Various errors that may occur when loading a [`GenericMaterial`](crate::GenericMaterial).
 #[derive(Error, Debug)]
 pub enum GenericMaterialLoadError {
@@ -12,6 +17,10 @@ pub enum GenericMaterialLoadError {
 	Deserialize(Box<dyn Error + Send + Sync>),
 	#[error("No registered material found for type {0}")]
 	MaterialTypeNotFound(String),
+	#[error(
+		"{0} isn't a registered generic material. Register it with `App::register_generic_material`, or `App::register_generic_material_2d` for 2D materials"
+	)]
+	NotRegisteredGenericMaterial(&'static str),
 	#[error("Too many type candidates found for `{0}`: {1:?}")]
 	TooManyTypeCandidates(String, Vec<String>),
 	#[error("field {field} is of type {expected}, but {found} was provided")]
@@ -38,4 +47,82 @@ pub enum GenericMaterialLoadError {
 
 	#[error("in super-material {0} - {1}")]
 	InSuperMaterial(String, Box<Self>),
+	#[error("Super-material {0} doesn't exist")]
+	SuperMaterialNotFound(AssetPath<'static>),
+
+	#[cfg(feature = "animation")]
+	#[error("Next material {0} of the animation doesn't exist")]
+	NextMaterialNotFound(AssetPath<'static>),
+
+	#[error("in library material {0} - {1}")]
+	InLibraryMaterial(String, Box<Self>),
+
+	#[error("Couldn't read image {0} to rearrange its channels - {1}")]
+	ReadChannelSwizzleImage(AssetPath<'static>, Box<dyn Error + Send + Sync>),
+	#[error("{0}")]
+	ChannelSwizzle(#[from] ChannelSwizzleError),
+
+	#[error("Inherited materials have conflicting types {0} and {1}, set `type` to pick one")]
+	ConflictingInheritedTypes(String, String),
+	#[error("Inheritance cycle: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(" -> "))]
+	InheritanceCycle(Vec<AssetPath<'static>>),
+	#[error("No local material by the name of {0} found in the `materials` table")]
+	LocalMaterialNotFound(String),
+	#[error("Materials from asset source {0} aren't allowed to use inheritance")]
+	InheritanceNotAllowed(AssetSourceId<'static>),
+	#[error("Path {0} starts at the root of an asset source or names one, which materials from this source aren't allowed to do")]
+	ExternalPath(String),
+}
+
+/// Errors that may occur when loading a WAD archive with [`WadLoader`](super::wad::WadLoader).
+#[cfg(feature = "wad")]
+#[derive(Error, Debug)]
+pub enum WadLoadError {
+	#[error("{0}")]
+	Io(#[from] io::Error),
+	#[error("Not a WAD2 or WAD3 archive")]
+	NotAWad,
+	#[error("The archive ended unexpectedly")]
+	UnexpectedEnd,
+	#[error("Texture {0} is compressed, which isn't supported")]
+	Compressed(String),
+	#[error("WAD2 archives don't contain a palette, set one with `WadLoader::with_palette_lmp`")]
+	NoPalette,
+	#[error("Palettes must be at least 768 bytes, 256 RGB colors")]
+	InvalidPalette,
+}
+
+/// Errors that may occur when loading a Wavefront `.mtl` file with [`MtlLoader`](super::mtl::MtlLoader).
+#[cfg(feature = "mtl")]
+#[derive(Error, Debug)]
+pub enum MtlLoadError {
+	#[error("{0}")]
+	Io(#[from] io::Error),
+	#[error("Line {0}: `newmtl` needs a name")]
+	MissingName(usize),
+	#[error("Line {0}: Statement is missing a value")]
+	MissingValue(usize),
+	#[error("Line {0}: `{1}` isn't a number")]
+	InvalidNumber(usize, String),
+	#[error("Line {0}: Statement comes before the first `newmtl`")]
+	OutsideMaterial(usize),
+}
+
+/// Errors that may occur in [`load_generic_material_blocking`](super::load_generic_material_blocking).
+#[cfg(feature = "bevy_pbr")]
+#[derive(Error, Debug)]
+pub enum LoadGenericMaterialBlockingError {
+	#[error("{0}")]
+	Load(#[from] AssetLoadError),
+	#[error("{0} didn't load as a generic material")]
+	NotAGenericMaterial(AssetPath<'static>),
+}
+
+/// Errors that may occur when writing a [`GenericMaterial`](crate::GenericMaterial) with [`serialize_with`](crate::GenericMaterial::serialize_with).
+#[derive(Error, Debug)]
+pub enum SerializeMaterialError {
+	#[error("Serialize error: {0}")]
+	Serialize(Box<dyn Error + Send + Sync>),
+	#[error("The material isn't loaded")]
+	NotLoaded,
 }
@@ -0,0 +1,203 @@
+use std::fmt;
+
+use bevy::{
+	prelude::*,
+	reflect::{
+		TypeInfo, TypePathTable, TypeRegistration, TypeRegistry,
+		serde::{ReflectDeserializerProcessor, TypedReflectDeserializer},
+		structs::{DynamicStruct, StructInfo},
+	},
+};
+use serde::de::{self, MapAccess, Visitor};
+
+use super::UnknownFieldPolicy;
+use super::processor::{MaterialProcessor, MaterialProcessorContext, skip_unknown_field, try_deserialize_skipping_unknown_fields};
+
+/// Material processor that lets the fields of [`ExtendedMaterial`](bevy::pbr::ExtendedMaterial)s' base material be written at the top level of the material,
+/// rather than nested in `base`.
+///
+/// Top-level keys other than `base` and `extension` are routed to the base material, unless only the extension has a field with that name.
+/// The base material can't be given both nested and flattened.
+///
+/// Fields are deserialized through the processor stack starting at this processor, so add it after any other processors (e.g. `.with_processor(FlattenExtendedMaterialProcessor)`).
+///
+/// # Examples
+/// ```toml
+/// type = "ExtendedMaterial<StandardMaterial, MyExtension>"
+///
+/// [material]
+/// base_color_texture = "bricks.png"
+/// cull_mode = "None"
+///
+/// [material.extension]
+/// quantize_steps = 3
+/// ```
+#[derive(TypePath, Clone)]
+pub struct FlattenExtendedMaterialProcessor<P: MaterialProcessor>(pub P);
+impl<P: MaterialProcessor> MaterialProcessor for FlattenExtendedMaterialProcessor<P> {
+	type Child = P;
+	fn child(&self) -> Option<&Self::Child> {
+		Some(&self.0)
+	}
+
+	fn try_deserialize<'de, D: serde::Deserializer<'de>>(
+		&self,
+		ctx: &mut MaterialProcessorContext,
+		registration: &TypeRegistration,
+		registry: &TypeRegistry,
+		deserializer: D,
+	) -> Result<Result<Box<dyn PartialReflect>, D>, D::Error> {
+		let TypeInfo::Struct(info) = registration.type_info() else { return Ok(Err(deserializer)) };
+		if !is_extended_material(info.type_path_table()) {
+			return Ok(Err(deserializer));
+		}
+		let (Some(base), Some(extension)) = (info.field("base"), info.field("extension")) else { return Ok(Err(deserializer)) };
+		let Some(TypeInfo::Struct(base)) = registry.get_type_info(base.type_id()) else { return Ok(Err(deserializer)) };
+		let extension = match registry.get_type_info(extension.type_id()) {
+			Some(TypeInfo::Struct(extension)) => Some(extension),
+			_ => None,
+		};
+
+		deserializer
+			.deserialize_map(FlattenVisitor {
+				info,
+				base,
+				extension,
+				registry,
+				processor: &mut NestedProcessor { ctx, processor: self },
+			})
+			.map(Ok)
+	}
+
+	fn flattens_extended_materials(&self) -> bool {
+		true
+	}
+}
+
+/// Returns `true` if `path` is the path of an [`ExtendedMaterial`](bevy::pbr::ExtendedMaterial), with any base material and extension.
+#[cfg(feature = "bevy_pbr")]
+pub(crate) fn is_extended_material(path: &TypePathTable) -> bool {
+	use bevy::pbr::{ExtendedMaterial, MaterialExtension};
+	use bevy::render::render_resource::AsBindGroup;
+
+	/// Only used to name an [`ExtendedMaterial`] type, as type paths don't include generics.
+	#[derive(Asset, AsBindGroup, TypePath, Clone)]
+	#[allow(dead_code)]
+	struct AnyExtension {}
+	impl MaterialExtension for AnyExtension {}
+
+	type AnyExtendedMaterial = ExtendedMaterial<StandardMaterial, AnyExtension>;
+	path.module_path() == AnyExtendedMaterial::module_path() && path.ident() == AnyExtendedMaterial::type_ident()
+}
+// Extended materials don't exist without `bevy_pbr`.
+#[cfg(not(feature = "bevy_pbr"))]
+pub(crate) fn is_extended_material(_path: &TypePathTable) -> bool {
+	false
+}
+
+/// Deserializes nested fields through the whole processor stack below and including `processor`.
+struct NestedProcessor<'a, 'w, 'l, P: MaterialProcessor> {
+	ctx: &'a mut MaterialProcessorContext<'w, 'l>,
+	processor: &'a P,
+}
+impl<P: MaterialProcessor> ReflectDeserializerProcessor for NestedProcessor<'_, '_, '_, P> {
+	fn try_deserialize<'de, D: serde::Deserializer<'de>>(
+		&mut self,
+		registration: &TypeRegistration,
+		registry: &TypeRegistry,
+		deserializer: D,
+	) -> Result<Result<Box<dyn PartialReflect>, D>, D::Error> {
+		match self.processor.try_deserialize_recursive(self.ctx, registration, registry, deserializer)? {
+			Err(deserializer) if self.ctx.unknown_fields != UnknownFieldPolicy::Error => {
+				let (policy, path) = (self.ctx.unknown_fields, self.ctx.load_context.path().clone_owned());
+				try_deserialize_skipping_unknown_fields(policy, path, registration, registry, deserializer, self)
+			}
+			out => Ok(out),
+		}
+	}
+}
+
+struct FlattenVisitor<'a, 'p, 'w, 'l, P: MaterialProcessor> {
+	info: &'a StructInfo,
+	base: &'a StructInfo,
+	extension: Option<&'a StructInfo>,
+	registry: &'a TypeRegistry,
+	processor: &'a mut NestedProcessor<'p, 'w, 'l, P>,
+}
+impl<P: MaterialProcessor> FlattenVisitor<'_, '_, '_, '_, P> {
+	/// Deserializes the value of field `key` of `info`, returning [`None`] if the field doesn't exist and was skipped.
+	fn deserialize_field<'de, A: MapAccess<'de>>(
+		&mut self,
+		map: &mut A,
+		info: &StructInfo,
+		key: &str,
+	) -> Result<Option<Box<dyn PartialReflect>>, A::Error> {
+		let Some(field) = info.field(key) else {
+			let ctx = &self.processor.ctx;
+			skip_unknown_field(map, ctx.unknown_fields, ctx.load_context.path(), key, info.type_path())?;
+			return Ok(None);
+		};
+		let Some(registration) = self.registry.get(field.type_id()) else {
+			return Err(de::Error::custom(format!("Type {} of field `{key}` isn't registered", field.type_path())));
+		};
+
+		map.next_value_seed(TypedReflectDeserializer::with_processor(
+			registration,
+			self.registry,
+			&mut *self.processor,
+		))
+		.map(Some)
+	}
+}
+impl<'de, P: MaterialProcessor> Visitor<'de> for FlattenVisitor<'_, '_, '_, '_, P> {
+	type Value = Box<dyn PartialReflect>;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "a map of {} fields, or of its base material's fields", self.info.type_path())
+	}
+
+	fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<Self::Value, A::Error> {
+		let mut output = DynamicStruct::default();
+		let mut flattened_base = DynamicStruct::default();
+		let mut flattened_extension = DynamicStruct::default();
+
+		while let Some(key) = map.next_key::<String>()? {
+			match key.as_str() {
+				"base" | "extension" => {
+					if let Some(value) = self.deserialize_field(&mut map, self.info, &key)? {
+						output.insert_boxed(key, value);
+					}
+				}
+				_ => match self
+					.extension
+					.filter(|extension| self.base.field(&key).is_none() && extension.field(&key).is_some())
+				{
+					Some(extension) => {
+						if let Some(value) = self.deserialize_field(&mut map, extension, &key)? {
+							flattened_extension.insert_boxed(key, value);
+						}
+					}
+					None => {
+						if let Some(value) = self.deserialize_field(&mut map, self.base, &key)? {
+							flattened_base.insert_boxed(key, value);
+						}
+					}
+				},
+			}
+		}
+
+		for (name, flattened) in [("base", flattened_base), ("extension", flattened_extension)] {
+			if flattened.field_len() == 0 {
+				continue;
+			}
+			if output.field(name).is_some() {
+				return Err(de::Error::custom(format!(
+					"Fields of `{name}` were given both in `{name}` and at the top level of the material"
+				)));
+			}
+			output.insert(name, flattened);
+		}
+
+		Ok(Box::new(output))
+	}
+}
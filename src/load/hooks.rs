@@ -0,0 +1,181 @@
+use std::any::{Any, TypeId};
+use std::sync::{Arc, RwLock};
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+#[cfg(feature = "bevy_pbr")]
+use bevy::reflect::ReflectMut;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+#[cfg(feature = "bevy_pbr")]
+use super::GenericMaterialLoadError;
+#[cfg(feature = "bevy_pbr")]
+use super::deserializer::MaterialDeserializer;
+#[cfg(feature = "bevy_pbr")]
+use super::flatten::is_extended_material;
+use super::processor::MaterialProcessorContext;
+
+/// Function that rewrites the value of a material field before it's deserialized.
+pub type FieldHook<V> = fn(V, &mut MaterialProcessorContext) -> V;
+
+/// Hooks registered with [`register_generic_material_field_hook`](crate::MaterializeAppExt::register_generic_material_field_hook),
+/// keyed by material type and field path.
+#[derive(Resource, Debug, Clone, Default)]
+#[allow(clippy::type_complexity)]
+pub struct GenericMaterialFieldHooks {
+	/// Values are [`FieldHook`]s of the value type of whichever [`MaterialDeserializer`] they were registered for.
+	pub inner: Arc<RwLock<HashMap<(TypeId, String), Vec<Box<dyn Any + Send + Sync>>>>>,
+}
+impl GenericMaterialFieldHooks {
+	/// Adds a hook for `field` of the material with the type id `material_type`.
+	pub fn insert<V: 'static>(&self, material_type: TypeId, field: impl Into<String>, hook: FieldHook<V>) {
+		self.inner
+			.write()
+			.unwrap()
+			.entry((material_type, field.into()))
+			.or_default()
+			.push(Box::new(hook));
+	}
+
+	/// Runs every hook registered for `material_type` on the matching fields of `material`.
+	#[cfg(feature = "bevy_pbr")]
+	pub(crate) fn apply<D: MaterialDeserializer>(
+		&self,
+		deserializer: &D,
+		material_type: TypeId,
+		material: &mut D::Value,
+		ctx: &mut MaterialProcessorContext,
+	) {
+		let hooks = self.inner.read().unwrap();
+
+		for ((type_id, field), field_hooks) in hooks.iter() {
+			if *type_id != material_type {
+				continue;
+			}
+			let Some(value) = field
+				.split('.')
+				.try_fold(&mut *material, |value, name| deserializer.field_mut(value, name))
+			else {
+				continue;
+			};
+
+			for hook in field_hooks {
+				let Some(hook) = hook.downcast_ref::<FieldHook<D::Value>>() else { continue };
+				*value = hook(value.clone(), ctx);
+			}
+		}
+	}
+}
+
+/// Function that deserializes a value and sets a field of a material with it, see [`ForeignField`].
+pub type ForeignFieldSetter = Arc<dyn Fn(&mut dyn Reflect, &mut dyn erased_serde::Deserializer) -> Result<(), erased_serde::Error> + Send + Sync>;
+/// Function that reads a field of a material to write it to a file, returning [`None`] if it's the same as in the (default) material given second, see [`ForeignField`].
+pub type ForeignFieldGetter = Arc<dyn Fn(&dyn Reflect, Option<&dyn Reflect>) -> Option<Box<dyn erased_serde::Serialize>> + Send + Sync>;
+
+/// How to read and write a material field that can't be accessed through reflection, see [`GenericMaterialForeignFields`].
+#[derive(Clone)]
+pub struct ForeignField {
+	pub get: ForeignFieldGetter,
+	pub set: ForeignFieldSetter,
+}
+
+/// Material fields that can't be accessed through reflection, such as fields of foreign types that are ignored by reflection (e.g. [`StandardMaterial::cull_mode`]),
+/// keyed by material type and field name.
+///
+/// These apply wherever a material of the type is found, including nested in other materials, such as the base material of an [`ExtendedMaterial`](bevy::pbr::ExtendedMaterial).
+///
+/// Registered with [`register_generic_material_foreign_field`](crate::MaterializeAppExt::register_generic_material_foreign_field).
+#[derive(Resource, Clone, Default)]
+pub struct GenericMaterialForeignFields {
+	pub inner: Arc<RwLock<HashMap<(TypeId, String), ForeignField>>>,
+}
+impl GenericMaterialForeignFields {
+	/// Makes `field` of materials of type `M` be read with `get` and set with `set`, going through `V` to be serialized and deserialized.
+	pub fn insert<M: Reflect, V: Serialize + DeserializeOwned + PartialEq + 'static>(
+		&self,
+		field: impl Into<String>,
+		get: fn(&M) -> V,
+		set: fn(&mut M, V),
+	) {
+		let foreign_field = ForeignField {
+			get: Arc::new(
+				move |material: &dyn Reflect, default: Option<&dyn Reflect>| -> Option<Box<dyn erased_serde::Serialize>> {
+					let value = get(material.downcast_ref::<M>()?);
+					if default
+						.and_then(|default| default.downcast_ref::<M>())
+						.is_some_and(|default| get(default) == value)
+					{
+						return None;
+					}
+					Some(Box::new(value))
+				},
+			),
+			set: Arc::new(move |material: &mut dyn Reflect, deserializer: &mut dyn erased_serde::Deserializer| {
+				let Some(material) = material.downcast_mut::<M>() else { return Ok(()) };
+				set(material, erased_serde::deserialize(deserializer)?);
+				Ok(())
+			}),
+		};
+
+		self.inner.write().unwrap().insert((TypeId::of::<M>(), field.into()), foreign_field);
+	}
+
+	/// Removes every foreign field of `material` and the structs nested in it from `value`, and sets them on `material`.
+	///
+	/// If `flatten` is `true`, foreign fields of the base material of [`ExtendedMaterial`](bevy::pbr::ExtendedMaterial)s are also taken from the top level of their value,
+	/// see [`FlattenExtendedMaterialProcessor`](super::flatten::FlattenExtendedMaterialProcessor).
+	#[cfg(feature = "bevy_pbr")]
+	pub(crate) fn apply<D: MaterialDeserializer>(
+		&self,
+		deserializer: &D,
+		value: &mut D::Value,
+		material: &mut dyn Reflect,
+		flatten: bool,
+	) -> Result<(), GenericMaterialLoadError> {
+		apply_foreign_fields(&self.inner.read().unwrap(), deserializer, value, material, flatten)
+	}
+}
+
+#[cfg(feature = "bevy_pbr")]
+fn apply_foreign_fields<D: MaterialDeserializer>(
+	foreign_fields: &HashMap<(TypeId, String), ForeignField>,
+	deserializer: &D,
+	value: &mut D::Value,
+	material: &mut dyn Reflect,
+	flatten: bool,
+) -> Result<(), GenericMaterialLoadError> {
+	let material_type = Reflect::as_any(material).type_id();
+
+	for ((type_id, field), foreign_field) in foreign_fields {
+		if *type_id != material_type {
+			continue;
+		}
+		let Some(field_value) = deserializer.remove_field(value, field) else { continue };
+
+		(foreign_field.set)(material, &mut <dyn erased_serde::Deserializer>::erase(field_value))
+			.map_err(|err| GenericMaterialLoadError::InField(field.clone(), Box::new(GenericMaterialLoadError::Deserialize(Box::new(err)))))?;
+	}
+
+	let is_extended_material = material
+		.get_represented_type_info()
+		.is_some_and(|info| is_extended_material(info.type_path_table()));
+	let ReflectMut::Struct(material) = material.reflect_mut() else { return Ok(()) };
+
+	for i in 0..material.field_len() {
+		let Some(name) = material.name_at(i).map(str::to_owned) else { continue };
+		let Some(field_value) = deserializer.field_mut(value, &name) else { continue };
+		let Some(field) = material.field_at_mut(i).and_then(PartialReflect::try_as_reflect_mut) else { continue };
+
+		apply_foreign_fields(foreign_fields, deserializer, field_value, field, flatten)
+			.map_err(|err| GenericMaterialLoadError::InField(name, Box::new(err)))?;
+	}
+
+	// The base material's fields can also be at the top level.
+	if flatten && is_extended_material {
+		let Some(base) = material.field_mut("base").and_then(PartialReflect::try_as_reflect_mut) else { return Ok(()) };
+		apply_foreign_fields(foreign_fields, deserializer, value, base, flatten)?;
+	}
+
+	Ok(())
+}
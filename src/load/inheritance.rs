@@ -1,11 +1,14 @@
 use std::io;
+use std::sync::{Arc, Mutex};
 
-use bevy::asset::{AssetPath, LoadContext};
+use bevy::asset::io::AssetReaderError;
+use bevy::asset::{AssetPath, LoadContext, ReadAssetBytesError};
+use bevy::platform::collections::HashSet;
 use bevy::prelude::*;
 
 use crate::load::ParsedGenericMaterial;
 
-use super::asset::relative_asset_path;
+use super::asset::{is_external_asset_path, relative_asset_path};
 use super::deserializer::MaterialDeserializer;
 use super::*;
 
@@ -13,78 +16,386 @@ use super::*;
 async fn read_path<D: MaterialDeserializer, P: MaterialProcessor>(
 	loader: &GenericMaterialLoader<D, P>,
 	load_context: &mut LoadContext<'_>,
-	path: impl Into<AssetPath<'_>>,
+	settings: &GenericMaterialLoaderSettings,
+	path: AssetPath<'_>,
 ) -> Result<ParsedGenericMaterial<D::Value>, GenericMaterialLoadError> {
-	let mut bytes = load_context.read_asset_bytes(path).await.map_err(io::Error::other)?;
-	if loader.do_text_replacements {
-		bytes = loader.try_apply_replacements(load_context, bytes);
+	let bytes = read_bytes(loader, load_context, settings, path).await?;
+	loader.parse(&bytes)
+}
+
+/// Helper function to read a generic material file, applying text replacements to it.
+async fn read_bytes<D: MaterialDeserializer, P: MaterialProcessor>(
+	loader: &GenericMaterialLoader<D, P>,
+	load_context: &mut LoadContext<'_>,
+	settings: &GenericMaterialLoaderSettings,
+	path: AssetPath<'_>,
+) -> Result<Vec<u8>, GenericMaterialLoadError> {
+	// Reading the file registers it as a loader dependency, so editing it hot-reloads this material.
+	// The dependency is matched against file change events, so it must not have a label.
+	let mut bytes = match load_context.read_asset_bytes(path.without_label()).await {
+		Ok(bytes) => bytes,
+		Err(ReadAssetBytesError::AssetReaderError(AssetReaderError::NotFound(_))) => {
+			return Err(GenericMaterialLoadError::SuperMaterialNotFound(path.into_owned()));
+		}
+		Err(err) => return Err(io::Error::other(err).into()),
+	};
+	let source_settings = loader.settings_for(load_context);
+	if source_settings.do_text_replacements {
+		// `${name}` refers to the material being loaded, not the material being inherited from, unless configured otherwise.
+		let name_path = if source_settings.inherited_name_from_parent {
+			&path
+		} else {
+			load_context.path()
+		};
+		bytes = loader.try_apply_replacements(load_context, name_path, settings, bytes);
 	}
 
-	loader
-		.deserializer
-		.deserialize(&bytes)
-		.map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))
+	Ok(bytes)
 }
 
-/// Applies inheritance to a parsed generic material by repeatedly reading the `inherits` field until it finds the top-most material,
-/// then iteratively merging the material below into it until the final material is produced.
+/// Whether missing super-materials of the material being loaded should be skipped rather than failing, see [`MissingParent`].
+fn skip_missing_parents<D: MaterialDeserializer, P: MaterialProcessor>(loader: &GenericMaterialLoader<D, P>, load_context: &LoadContext) -> bool {
+	loader.settings_for(load_context).missing_parent == MissingParent::WarnAndSkip
+}
+
+/// A material whose super-materials are still being resolved.
+struct InheritanceFrame<Value: GenericValue> {
+	material: ParsedGenericMaterial<Value>,
+	/// The path of the file this material was read from, or [`None`] for local materials.
+	path: Option<AssetPath<'static>>,
+	/// Super-materials that haven't been read yet, in declared order.
+	remaining: std::vec::IntoIter<String>,
+	/// Local materials of the file this material is in.
+	local_materials: LocalMaterials<Value>,
+	/// Whether this material is a local material, and so shares its local materials with the material that inherited it.
+	local: bool,
+	/// The super-materials resolved so far, merged together.
+	inherited: Option<ParsedGenericMaterial<Value>>,
+}
+
+/// The materials a material can inherit from with `#name`.
+pub(super) struct LocalMaterials<Value: GenericValue> {
+	/// The `materials` table of the file the material is in. These are removed when used, so each can only be inherited once, preventing cycles.
+	materials: BTreeMap<String, ParsedGenericMaterial<Value>>,
+	/// The materials of the library being loaded, shared between all of its materials rather than copied into each.
+	library: Option<Arc<BTreeMap<String, ParsedGenericMaterial<Value>>>>,
+	/// Names of the library materials that can't be inherited anymore, like used materials of the file.
+	used: HashSet<String>,
+}
+impl<Value: GenericValue> LocalMaterials<Value> {
+	/// The materials of a library, for its material called `name`, which can't inherit from itself.
+	pub(super) fn library(materials: Arc<BTreeMap<String, ParsedGenericMaterial<Value>>>, name: &str) -> Self {
+		Self {
+			materials: BTreeMap::new(),
+			library: Some(materials),
+			used: HashSet::from_iter([name.to_string()]),
+		}
+	}
+
+	/// Takes the material called `name` for inheriting from, materials of the file taking precedence over ones of the library.
+	fn take(&mut self, name: &str) -> Option<ParsedGenericMaterial<Value>> {
+		if let Some(material) = self.materials.remove(name) {
+			return Some(material);
+		}
+
+		let material = self.library.as_ref()?.get(name)?;
+		self.used.insert(name.to_string()).then(|| material.clone())
+	}
+}
+impl<Value: GenericValue> Default for LocalMaterials<Value> {
+	fn default() -> Self {
+		Self {
+			materials: BTreeMap::new(),
+			library: None,
+			used: HashSet::default(),
+		}
+	}
+}
+impl<Value: GenericValue> From<BTreeMap<String, ParsedGenericMaterial<Value>>> for LocalMaterials<Value> {
+	fn from(materials: BTreeMap<String, ParsedGenericMaterial<Value>>) -> Self {
+		Self { materials, ..default() }
+	}
+}
+
+/// Applies inheritance to a parsed generic material by reading the materials in its `inherits` field (and the ones they inherit from),
+/// then merging them in declared order, with later materials taking precedence over earlier ones, and the material itself taking precedence over all of them.
+///
+/// If two materials in the same `inherits` list end up with different types, the material must set its own `type`, otherwise it's ambiguous which to use.
+///
+/// If an element of `inherits` starts with `#`, the rest is the name of a local material in the `materials` table of the file currently being read,
+/// or in `local_materials`, such as the other materials of a library.
+///
+/// Each file is only read and parsed once per call, even if it's inherited from through several materials.
+/// Files aren't cached between loads, as reading them is what registers them as dependencies for hot-reloading.
 pub(super) async fn apply_inheritance<D: MaterialDeserializer, P: MaterialProcessor>(
 	loader: &GenericMaterialLoader<D, P>,
 	load_context: &mut LoadContext<'_>,
-	sub_material: ParsedGenericMaterial<D::Value>,
+	settings: &GenericMaterialLoaderSettings,
+	mut sub_material: ParsedGenericMaterial<D::Value>,
+	mut local_materials: LocalMaterials<D::Value>,
 ) -> Result<ParsedGenericMaterial<D::Value>, GenericMaterialLoadError> {
-	// We do a queue-based solution because async functions can't recurse
-	let mut application_queue: Vec<ParsedGenericMaterial<D::Value>> = Vec::new();
+	// Parsed files, cloned rather than read again if they're inherited from more than once.
+	let mut files: HashMap<AssetPath<'static>, ParsedGenericMaterial<D::Value>> = default();
 
-	// Build the queue
-	application_queue.push(sub_material);
+	// We do a stack-based solution because async functions can't recurse
+	local_materials.materials = sub_material.materials.take().unwrap_or_default();
+	let mut stack = vec![InheritanceFrame::new(
+		sub_material,
+		Some(load_context.path().clone_owned()),
+		local_materials,
+		false,
+	)];
 
-	while let Some(inherits) = &application_queue.last().unwrap().inherits {
-		let path = relative_asset_path(load_context.path(), inherits).map_err(io::Error::other)?;
+	loop {
+		if let Some(inherits) = stack.last_mut().unwrap().remaining.next() {
+			let frame = match inherits.strip_prefix('#') {
+				// Local materials can't be inherited twice, preventing cycles.
+				Some(name) => {
+					let sub_frame = stack.last_mut().unwrap();
+					let super_material = sub_frame
+						.local_materials
+						.take(name)
+						.ok_or_else(|| GenericMaterialLoadError::LocalMaterialNotFound(name.to_string()))?;
 
-		application_queue.push(
-			read_path(loader, load_context, path)
-				.await
-				.map_err(|err| GenericMaterialLoadError::InSuperMaterial(inherits.clone(), Box::new(err)))?,
-		);
-	}
+					InheritanceFrame::new(super_material, None, std::mem::take(&mut sub_frame.local_materials), true)
+				}
+				None => {
+					if !loader.settings_for(load_context).allow_external_paths && is_external_asset_path(&inherits) {
+						return Err(GenericMaterialLoadError::ExternalPath(inherits));
+					}
 
-	// Apply the queue
+					let path = relative_asset_path(load_context.path(), &inherits).map_err(io::Error::other)?;
 
-	// We are guaranteed to have at least 1 element. This is the highest super-material.
-	let mut final_material = application_queue.pop().unwrap();
+					// Only the materials currently being resolved count, a material may be inherited from twice through different parents.
+					if stack.iter().any(|frame| frame.path.as_ref() == Some(&path)) {
+						let mut chain: Vec<_> = stack.iter().filter_map(|frame| frame.path.clone()).collect();
+						chain.push(path);
+						return Err(GenericMaterialLoadError::InheritanceCycle(chain));
+					}
 
-	// This goes through the queue from highest super-material to the one we started at, and merges them in that order.
-	while let Some(sub_material) = application_queue.pop() {
-		match (&mut final_material.properties, sub_material.properties) {
-			(Some(final_material_properties), Some(sub_properties)) => {
-				for (key, sub_value) in sub_properties {
-					match final_material_properties.get_mut(&key) {
-						Some(value) => loader.deserializer.merge_value(value, sub_value),
-						None => {
-							final_material_properties.insert(key, sub_value);
+					let read = match files.get(&path) {
+						Some(super_material) => Ok(super_material.clone()),
+						None => read_path(loader, load_context, settings, path.clone()).await.inspect(|super_material| {
+							files.insert(path.clone(), super_material.clone());
+						}),
+					};
+					let mut super_material = match read {
+						Ok(super_material) => super_material,
+						Err(GenericMaterialLoadError::SuperMaterialNotFound(_)) if skip_missing_parents(loader, load_context) => {
+							warn!("{}: Skipping super-material {inherits}, as it doesn't exist", load_context.path());
+							continue;
 						}
-					}
+						Err(err) => return Err(GenericMaterialLoadError::InSuperMaterial(inherits.clone(), Box::new(err))),
+					};
+					let local_materials = super_material.materials.take().unwrap_or_default();
+
+					InheritanceFrame::new(super_material, Some(path), local_materials.into(), false)
 				}
+			};
+
+			stack.push(frame);
+			continue;
+		}
+
+		// Every super-material of this material is resolved, so we can merge it into them.
+		let frame = stack.pop().unwrap();
+		let material = match frame.inherited {
+			Some(mut inherited) => {
+				merge(loader, &mut inherited, frame.material);
+				inherited
 			}
-			(None, Some(applicator_properties)) => final_material.properties = Some(applicator_properties),
-			_ => {}
+			None => frame.material,
+		};
+
+		let Some(sub_frame) = stack.last_mut() else { return Ok(material) };
+
+		if frame.local {
+			sub_frame.local_materials = frame.local_materials;
 		}
 
-		#[cfg(feature = "bevy_pbr")]
-		if sub_material.ty.is_some() {
-			final_material.ty = sub_material.ty;
-			final_material.material = sub_material.material;
-		} else {
-			match (&mut final_material.material, sub_material.material) {
-				(Some(final_material_mat), Some(sub_material_mat)) => {
-					loader.deserializer.merge_value(final_material_mat, sub_material_mat);
+		sub_frame.inherited = Some(match sub_frame.inherited.take() {
+			Some(mut inherited) => {
+				#[cfg(feature = "bevy_pbr")]
+				if sub_frame.material.ty.is_none()
+					&& let (Some(a), Some(b)) = (&inherited.ty, &material.ty)
+					&& a != b
+				{
+					return Err(GenericMaterialLoadError::ConflictingInheritedTypes(a.clone(), b.clone()));
+				}
+
+				merge_sibling(loader, &mut inherited, material);
+				inherited
+			}
+			None => material,
+		});
+	}
+}
+
+/// Merges the [`overrides`](OVERRIDES_KEY) of each of the [`current_platforms`] into the material, in order.
+#[cfg(feature = "bevy_pbr")]
+pub(super) fn apply_platform_overrides<D: MaterialDeserializer, P: MaterialProcessor>(
+	loader: &GenericMaterialLoader<D, P>,
+	mut parsed: ParsedGenericMaterial<D::Value>,
+) -> ParsedGenericMaterial<D::Value> {
+	let Some(material) = &mut parsed.material else { return parsed };
+	let Some(mut overrides) = loader.deserializer.remove_field(material, OVERRIDES_KEY) else { return parsed };
+
+	for platform in current_platforms() {
+		let Some(platform_override) = loader.deserializer.remove_field(&mut overrides, platform) else { continue };
+		loader.deserializer.merge_value(material, platform_override);
+	}
+
+	parsed
+}
+
+/// Merges `material` into the loader's [`default_inherits`](GenericMaterialLoader::default_inherits) material (with inheritance applied), with `material` taking precedence.
+///
+/// Does nothing if there is no default material, or if the default material is the one being loaded.
+pub(super) async fn apply_default_inherits<D: MaterialDeserializer, P: MaterialProcessor>(
+	loader: &GenericMaterialLoader<D, P>,
+	load_context: &mut LoadContext<'_>,
+	settings: &GenericMaterialLoaderSettings,
+	material: ParsedGenericMaterial<D::Value>,
+) -> Result<ParsedGenericMaterial<D::Value>, GenericMaterialLoadError> {
+	let Some(path) = &loader.default_inherits else { return Ok(material) };
+	if load_context.path().without_label() == path.without_label() {
+		return Ok(material);
+	}
+
+	let in_default = |err| GenericMaterialLoadError::InSuperMaterial(path.to_string(), Box::new(err));
+	// Still read every time, as reading it registers it as a dependency, so editing it hot-reloads this material.
+	let bytes = match read_bytes(loader, load_context, settings, path.clone()).await {
+		Ok(bytes) => bytes,
+		Err(GenericMaterialLoadError::SuperMaterialNotFound(_)) if skip_missing_parents(loader, load_context) => {
+			warn!("{}: Skipping default material {path}, as it doesn't exist", load_context.path());
+			return Ok(material);
+		}
+		Err(err) => return Err(in_default(err)),
+	};
+	let default_material = loader
+		.default_inherits_cache
+		.get_or_parse(bytes, |bytes| loader.parse(bytes))
+		.map_err(in_default)?;
+	let mut default_material = apply_inheritance(loader, load_context, settings, default_material, default())
+		.await
+		.map_err(in_default)?;
+
+	merge_sibling(loader, &mut default_material, material);
+	Ok(default_material)
+}
+
+/// The [`default_inherits`](GenericMaterialLoader::default_inherits) material most recently parsed, so it isn't parsed again for every material loaded.
+///
+/// It's keyed by the contents of the file after text replacements, so it's parsed again if the file changes,
+/// or contains replacements that differ between materials, such as `${name}`.
+#[allow(clippy::type_complexity)]
+pub struct DefaultInheritsCache<Value: GenericValue> {
+	last: Arc<Mutex<Option<(Vec<u8>, ParsedGenericMaterial<Value>)>>>,
+}
+impl<Value: GenericValue> DefaultInheritsCache<Value> {
+	/// Returns the material parsed from `bytes`, only calling `parse` if they differ from the last ones.
+	fn get_or_parse(
+		&self,
+		bytes: Vec<u8>,
+		parse: impl FnOnce(&[u8]) -> Result<ParsedGenericMaterial<Value>, GenericMaterialLoadError>,
+	) -> Result<ParsedGenericMaterial<Value>, GenericMaterialLoadError> {
+		let mut last = self.last.lock().unwrap();
+
+		if let Some((last_bytes, material)) = &*last
+			&& *last_bytes == bytes
+		{
+			return Ok(material.clone());
+		}
+
+		let material = parse(&bytes)?;
+		*last = Some((bytes, material.clone()));
+		Ok(material)
+	}
+}
+// Not derived, as that would require `Value: Default`.
+impl<Value: GenericValue> Default for DefaultInheritsCache<Value> {
+	fn default() -> Self {
+		Self { last: default() }
+	}
+}
+impl<Value: GenericValue> Clone for DefaultInheritsCache<Value> {
+	fn clone(&self) -> Self {
+		Self { last: self.last.clone() }
+	}
+}
+
+impl<Value: GenericValue> InheritanceFrame<Value> {
+	fn new(
+		mut material: ParsedGenericMaterial<Value>,
+		path: Option<AssetPath<'static>>,
+		local_materials: LocalMaterials<Value>,
+		local: bool,
+	) -> Self {
+		let remaining = material.inherits.take().map(Inherits::into_vec).unwrap_or_default().into_iter();
+
+		Self {
+			material,
+			path,
+			remaining,
+			local_materials,
+			local,
+			inherited: None,
+		}
+	}
+}
+
+/// Merges `sub_material` into `final_material`, with `sub_material` taking precedence.
+///
+/// If `sub_material` has a type, it replaces the material of `final_material` as a whole.
+pub(super) fn merge<D: MaterialDeserializer, P: MaterialProcessor>(
+	loader: &GenericMaterialLoader<D, P>,
+	final_material: &mut ParsedGenericMaterial<D::Value>,
+	sub_material: ParsedGenericMaterial<D::Value>,
+) {
+	match (&mut final_material.properties, sub_material.properties) {
+		(Some(final_material_properties), Some(sub_properties)) => {
+			for (key, sub_value) in sub_properties {
+				match final_material_properties.get_mut(&key) {
+					Some(value) => loader.deserializer.merge_value(value, sub_value),
+					None => {
+						final_material_properties.insert(key, sub_value);
+					}
 				}
-				(None, Some(sub_material_mat)) => final_material.material = Some(sub_material_mat),
-				_ => {}
 			}
 		}
+		(None, Some(applicator_properties)) => final_material.properties = Some(applicator_properties),
+		_ => {}
+	}
+
+	if sub_material.color_space.is_some() {
+		final_material.color_space = sub_material.color_space;
+	}
+
+	#[cfg(feature = "bevy_pbr")]
+	if sub_material.ty.is_some() {
+		final_material.ty = sub_material.ty;
+		final_material.material = sub_material.material;
+	} else {
+		match (&mut final_material.material, sub_material.material) {
+			(Some(final_material_mat), Some(sub_material_mat)) => {
+				loader.deserializer.merge_value(final_material_mat, sub_material_mat);
+			}
+			(None, Some(sub_material_mat)) => final_material.material = Some(sub_material_mat),
+			_ => {}
+		}
+	}
+}
+
+/// Like [`merge`], but for materials next to each other in an `inherits` list, where materials of the same type are merged field by field rather than replaced.
+fn merge_sibling<D: MaterialDeserializer, P: MaterialProcessor>(
+	loader: &GenericMaterialLoader<D, P>,
+	final_material: &mut ParsedGenericMaterial<D::Value>,
+	#[allow(unused_mut)] mut sub_material: ParsedGenericMaterial<D::Value>,
+) {
+	#[cfg(feature = "bevy_pbr")]
+	if sub_material.ty == final_material.ty {
+		sub_material.ty = None;
 	}
 
-	Ok(final_material)
+	merge(loader, final_material, sub_material);
 }
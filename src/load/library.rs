@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::platform::collections::HashMap;
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use bevy::tasks::ConditionalSendFuture;
+use serde::Deserialize;
+
+use super::deserializer::MaterialDeserializer;
+use super::inheritance::LocalMaterials;
+use super::metrics::MaterialLoadMetrics;
+use super::processor::MaterialProcessor;
+use super::{GenericMaterialLoadError, GenericMaterialLoader, GenericMaterialLoaderSettings};
+use crate::generic_material::GenericMaterial;
+
+/// Many [`GenericMaterial`]s defined in a single file, each added as a sub-asset labeled with its name.
+///
+/// Unlike a [`GenericMaterialSet`](super::set::GenericMaterialSet), which refers to material files, the materials are defined in the library itself.
+/// They can inherit from each other with `inherits = "#name"`.
+///
+/// # Examples
+/// ```toml
+/// # tiles.materiallib.toml
+/// [materials.base.material]
+/// perceptual_roughness = 0.9
+///
+/// [materials.bricks]
+/// inherits = "#base"
+/// material.base_color_texture = "bricks.png"
+///
+/// [materials.grass]
+/// inherits = "#base"
+/// material.base_color_texture = "grass.png"
+/// ```
+/// Then `asset_server.load("tiles.materiallib.toml#bricks")` loads the bricks material on its own.
+#[derive(Asset, TypePath, Debug, Clone, Default)]
+pub struct GenericMaterialLibrary {
+	pub materials: HashMap<String, Handle<GenericMaterial>>,
+}
+impl GenericMaterialLibrary {
+	/// Returns the handle of the material called `name`.
+	pub fn get(&self, name: &str) -> Option<&Handle<GenericMaterial>> {
+		self.materials.get(name)
+	}
+}
+
+/// Loads a [`GenericMaterialLibrary`], loading each material like [`GenericMaterialLoader`] would if it were in its own file.
+///
+/// Uses the [`LIBRARY_EXTENSIONS`](MaterialDeserializer::LIBRARY_EXTENSIONS) of `D`.
+#[derive(TypePath)]
+pub struct GenericMaterialLibraryLoader<D: MaterialDeserializer, P: MaterialProcessor> {
+	pub loader: Arc<GenericMaterialLoader<D, P>>,
+}
+impl<D: MaterialDeserializer, P: MaterialProcessor> AssetLoader for GenericMaterialLibraryLoader<D, P> {
+	type Asset = GenericMaterialLibrary;
+	type Settings = GenericMaterialLoaderSettings;
+	type Error = GenericMaterialLoadError;
+
+	fn load(
+		&self,
+		reader: &mut dyn bevy::asset::io::Reader,
+		settings: &Self::Settings,
+		load_context: &mut LoadContext,
+	) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+		Box::pin(async {
+			let loader = &*self.loader;
+
+			let mut input = Vec::new();
+			reader.read_to_end(&mut input).await?;
+
+			let parse_start = Instant::now();
+
+			let source_settings = loader.settings_for(load_context);
+
+			if source_settings.do_text_replacements {
+				input = loader.try_apply_replacements(load_context, load_context.path(), settings, input);
+			}
+
+			let mut value: D::Value = loader
+				.deserializer
+				.deserialize(&input)
+				.map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))?;
+			loader
+				.deserializer
+				.resolve_defs(&mut value)
+				.map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))?;
+
+			let parsed =
+				ParsedGenericMaterialLibrary::<D::Value>::deserialize(value).map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))?;
+
+			let mut materials = BTreeMap::new();
+			for (name, value) in parsed.materials {
+				let material = loader
+					.parse_value(value)
+					.map_err(|err| GenericMaterialLoadError::InLibraryMaterial(name.clone(), Box::new(err)))?;
+				materials.insert(name, material);
+			}
+
+			let mut metrics = MaterialLoadMetrics::new(load_context.path().clone_owned());
+			metrics.parse_time = parse_start.elapsed();
+
+			let materials = Arc::new(materials);
+			let mut handles = HashMap::with_capacity(materials.len());
+			for (name, material) in materials.iter() {
+				// Every other material of the library can be inherited from as a local material.
+				let local_materials = LocalMaterials::library(materials.clone(), name);
+
+				let generic_material = loader
+					.load_parsed(
+						load_context,
+						settings,
+						&source_settings,
+						material.clone(),
+						local_materials,
+						&format!("{name}/"),
+						&mut metrics,
+					)
+					.await
+					.map_err(|err| GenericMaterialLoadError::InLibraryMaterial(name.clone(), Box::new(err)))?;
+
+				handles.insert(name.clone(), load_context.add_labeled_asset(name.clone(), generic_material));
+			}
+
+			loader.metrics.record(metrics);
+
+			Ok(GenericMaterialLibrary { materials: handles })
+		})
+	}
+
+	fn extensions(&self) -> &[&str] {
+		D::LIBRARY_EXTENSIONS
+	}
+}
+
+#[derive(Deserialize)]
+struct ParsedGenericMaterialLibrary<Value> {
+	#[serde(default = "BTreeMap::new")]
+	materials: BTreeMap<String, Value>,
+}
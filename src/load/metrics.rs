@@ -0,0 +1,66 @@
+use std::{
+	collections::VecDeque,
+	sync::{Arc, RwLock},
+	time::Duration,
+};
+
+use bevy::{asset::AssetPath, prelude::*};
+
+/// Records timing metrics of the most recently loaded [`GenericMaterial`](crate::GenericMaterial)s in a ring buffer.
+///
+/// Unlike tracing, this is always available, making it useful for profiling big asset libraries in production builds.
+#[derive(Resource, Debug, Clone)]
+pub struct MaterializeLoadMetrics {
+	pub inner: Arc<RwLock<VecDeque<MaterialLoadMetrics>>>,
+	/// How many entries are kept before the oldest are discarded. (Default: 256)
+	pub capacity: usize,
+}
+impl MaterializeLoadMetrics {
+	/// Records metrics for a loaded material, discarding the oldest entry if at capacity.
+	pub fn record(&self, metrics: MaterialLoadMetrics) {
+		let mut inner = self.inner.write().unwrap();
+		while inner.len() >= self.capacity.max(1) {
+			inner.pop_front();
+		}
+		inner.push_back(metrics);
+	}
+
+	/// Returns a copy of the currently recorded metrics, oldest first.
+	pub fn entries(&self) -> Vec<MaterialLoadMetrics> {
+		self.inner.read().unwrap().iter().cloned().collect()
+	}
+}
+impl Default for MaterializeLoadMetrics {
+	fn default() -> Self {
+		Self {
+			inner: default(),
+			capacity: 256,
+		}
+	}
+}
+
+/// Timing metrics of a single material load.
+#[derive(Debug, Clone)]
+pub struct MaterialLoadMetrics {
+	pub path: AssetPath<'static>,
+	/// Time spent applying text replacements and deserializing the file.
+	pub parse_time: Duration,
+	/// Time spent reading and merging inherited materials.
+	pub inheritance_time: Duration,
+	/// Time spent deserializing the material and properties into concrete types.
+	pub reflect_time: Duration,
+	/// How many sub-assets (such as images) were queued for loading.
+	pub sub_assets_queued: usize,
+}
+impl MaterialLoadMetrics {
+	/// Creates metrics for a load of `path` with nothing recorded yet.
+	pub fn new(path: AssetPath<'static>) -> Self {
+		Self {
+			path,
+			parse_time: Duration::ZERO,
+			inheritance_time: Duration::ZERO,
+			reflect_time: Duration::ZERO,
+			sub_assets_queued: 0,
+		}
+	}
+}
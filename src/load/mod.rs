@@ -1,27 +1,60 @@
 pub mod asset;
+pub mod channels;
+#[cfg(feature = "bevy_image")]
+pub mod color;
 pub mod deserializer;
+pub mod flatten;
+pub mod hooks;
 pub mod inheritance;
+pub mod library;
+pub mod metrics;
+#[cfg(feature = "mtl")]
+pub mod mtl;
 pub mod processor;
+pub mod replacements;
+pub mod serializer;
+pub mod set;
+#[cfg(feature = "simple_loader")]
 pub mod simple;
+pub mod source;
+#[cfg(feature = "wad")]
+pub mod wad;
+#[cfg(feature = "bevy_pbr")]
+pub mod wgpu_enums;
 
 mod error;
 pub use error::*;
+#[cfg(all(test, feature = "bevy_pbr"))]
+mod tests;
 
-use std::ffi::OsStr;
+use std::collections::BTreeMap;
 use std::str;
 use std::sync::Arc;
 
 use ::serde;
 use bevy::asset::AssetLoader;
+#[cfg(feature = "animation")]
+use bevy::asset::ReadAssetBytesError;
+#[cfg(feature = "animation")]
+use bevy::asset::io::AssetReaderError;
+use bevy::asset::io::AssetSourceId;
 use bevy::platform::collections::HashMap;
+use bevy::platform::time::Instant;
 use bevy::reflect::{serde::*, *};
 use bevy::tasks::ConditionalSendFuture;
-use bevy::{asset::LoadContext, prelude::*};
-use inheritance::apply_inheritance;
-use processor::{MaterialDeserializerProcessor, MaterialProcessor, MaterialProcessorContext};
+use bevy::{
+	asset::{AssetPath, LoadContext},
+	prelude::*,
+};
+use channels::PendingChannelSwizzle;
+use hooks::{GenericMaterialFieldHooks, GenericMaterialForeignFields};
+use inheritance::{DefaultInheritsCache, LocalMaterials, apply_default_inherits, apply_inheritance};
+use metrics::{MaterialLoadMetrics, MaterializeLoadMetrics};
+use processor::{ColorSpace, MaterialDeserializerProcessor, MaterialProcessor, MaterialProcessorContext};
+use replacements::{TextReplacementContext, TextReplacementProcessor};
 use serde::Deserialize;
 
-use crate::material_property::MaterialPropertyRegistry;
+use crate::material_property::{MaterialPropertyRegistry, ReflectPropertySubAssets};
 use crate::{GenericMaterialShorthands, prelude::*, value::GenericValue};
 
 #[cfg(feature = "bevy_pbr")]
@@ -36,182 +69,683 @@ pub struct GenericMaterialLoader<D: MaterialDeserializer, P: MaterialProcessor>
 	pub property_registry: MaterialPropertyRegistry,
 	pub deserializer: Arc<D>,
 	pub do_text_replacements: bool,
+	/// Which replacements are applied when text replacements are enabled.
+	pub text_replacements: TextReplacements,
+	/// The `${key}` patterns that can be replaced, shared with the [`TextReplacementProcessor`] resource.
+	pub text_replacement_processor: TextReplacementProcessor,
+	/// Overrides loader behavior for materials loaded from specific asset sources.
+	pub source_settings: HashMap<AssetSourceId<'static>, SourceLoaderSettings>,
+	/// Functions run on every material file's deserialized value (including inherited ones) before it's processed, in order.
+	pub value_transforms: Vec<fn(&mut D::Value)>,
+	pub metrics: MaterializeLoadMetrics,
+	pub field_hooks: GenericMaterialFieldHooks,
+	pub foreign_fields: GenericMaterialForeignFields,
+	/// A material file every loaded material implicitly inherits from, with the lowest precedence.
+	pub default_inherits: Option<AssetPath<'static>>,
+	pub default_inherits_cache: DefaultInheritsCache<D::Value>,
 	pub processor: P,
 }
+// Not derived, as that would require `D: Clone`.
+impl<D: MaterialDeserializer, P: MaterialProcessor> Clone for GenericMaterialLoader<D, P> {
+	fn clone(&self) -> Self {
+		Self {
+			type_registry: self.type_registry.clone(),
+			shorthands: self.shorthands.clone(),
+			property_registry: self.property_registry.clone(),
+			deserializer: self.deserializer.clone(),
+			do_text_replacements: self.do_text_replacements,
+			text_replacements: self.text_replacements,
+			text_replacement_processor: self.text_replacement_processor.clone(),
+			source_settings: self.source_settings.clone(),
+			value_transforms: self.value_transforms.clone(),
+			metrics: self.metrics.clone(),
+			field_hooks: self.field_hooks.clone(),
+			foreign_fields: self.foreign_fields.clone(),
+			default_inherits: self.default_inherits.clone(),
+			default_inherits_cache: self.default_inherits_cache.clone(),
+			processor: self.processor.clone(),
+		}
+	}
+}
 impl<D: MaterialDeserializer, P: MaterialProcessor> GenericMaterialLoader<D, P> {
-	/// Attempts to apply string replacements to a text-based material file. Currently these are hardcoded, but i'd prefer if eventually they won't be.
-	pub fn try_apply_replacements(&self, load_context: &LoadContext, bytes: Vec<u8>) -> Vec<u8> {
-		let mut s = match String::from_utf8(bytes) {
+	/// Deserializes a material file, resolving its [definitions](MaterialDeserializer::resolve_defs) and running [`value_transforms`](Self::value_transforms) on it before structuring it.
+	fn parse(&self, input: &[u8]) -> Result<ParsedGenericMaterial<D::Value>, GenericMaterialLoadError> {
+		let mut value: D::Value = self
+			.deserializer
+			.deserialize(input)
+			.map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))?;
+
+		self.deserializer
+			.resolve_defs(&mut value)
+			.map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))?;
+
+		self.parse_value(value)
+	}
+
+	/// Runs [`value_transforms`](Self::value_transforms) on the value of a single material, then structures it.
+	fn parse_value(&self, mut value: D::Value) -> Result<ParsedGenericMaterial<D::Value>, GenericMaterialLoadError> {
+		for transform in &self.value_transforms {
+			transform(&mut value);
+		}
+
+		ParsedGenericMaterial::deserialize(value).map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))
+	}
+
+	/// Applies inheritance and platform overrides to a parsed material, then builds it and adds its variants as labeled sub-assets.
+	///
+	/// Labels of the sub-assets added start with `label_prefix`. Times and sub-asset counts are added to `metrics`.
+	#[allow(clippy::too_many_arguments)]
+	async fn load_parsed(
+		&self,
+		load_context: &mut LoadContext<'_>,
+		settings: &GenericMaterialLoaderSettings,
+		source_settings: &SourceLoaderSettings,
+		mut parsed: ParsedGenericMaterial<D::Value>,
+		local_materials: LocalMaterials<D::Value>,
+		label_prefix: &str,
+		metrics: &mut MaterialLoadMetrics,
+	) -> Result<GenericMaterial, GenericMaterialLoadError> {
+		if !source_settings.allow_inheritance && parsed.inherits.is_some() {
+			return Err(GenericMaterialLoadError::InheritanceNotAllowed(
+				load_context.path().source().clone_owned(),
+			));
+		}
+
+		let inheritance_start = Instant::now();
+
+		// Variants and ids are only read from the file being loaded, not the materials it inherits from.
+		let variants = parsed.variants.take().unwrap_or_default();
+		let id = parsed.id.take();
+
+		let parsed = apply_inheritance(self, load_context, settings, parsed, local_materials).await?;
+		let parsed = apply_default_inherits(self, load_context, settings, parsed).await?;
+
+		assert!(parsed.inherits.is_none());
+
+		// Applied after inheritance so overrides in inherited materials apply too.
+		#[cfg(feature = "bevy_pbr")]
+		let parsed = inheritance::apply_platform_overrides(self, parsed);
+
+		metrics.inheritance_time += inheritance_start.elapsed();
+		let reflect_start = Instant::now();
+		let mut channel_swizzles = Vec::new();
+
+		// Each variant is the final material with the variant's fields merged on top.
+		for (name, mut variant) in variants {
+			let variant_id = variant.id.take();
+			let mut variant_material = parsed.clone();
+			inheritance::merge(self, &mut variant_material, variant);
+
+			let mut generic_material = self.build_generic_material(
+				load_context,
+				settings,
+				source_settings,
+				variant_material,
+				format!("{label_prefix}{name}/Material"),
+				&mut metrics.sub_assets_queued,
+				&mut channel_swizzles,
+			)?;
+			generic_material.id = variant_id;
+			#[cfg(feature = "animation")]
+			check_next_material(load_context, &generic_material).await?;
+			load_context.add_labeled_asset(format!("{label_prefix}{name}"), generic_material);
+		}
+
+		let mut generic_material = self.build_generic_material(
+			load_context,
+			settings,
+			source_settings,
+			parsed,
+			format!("{label_prefix}Material"),
+			&mut metrics.sub_assets_queued,
+			&mut channel_swizzles,
+		)?;
+		generic_material.id = id;
+
+		#[cfg(feature = "animation")]
+		check_next_material(load_context, &generic_material).await?;
+		#[cfg(feature = "bevy_pbr")]
+		add_channel_swizzles(load_context, channel_swizzles).await?;
+
+		metrics.reflect_time += reflect_start.elapsed();
+
+		Ok(generic_material)
+	}
+
+	/// Returns the settings for the asset source the material is being loaded from, falling back to this loader's own settings.
+	pub fn settings_for(&self, load_context: &LoadContext) -> SourceLoaderSettings {
+		self.source_settings
+			.get(load_context.path().source())
+			.cloned()
+			.unwrap_or(SourceLoaderSettings {
+				do_text_replacements: self.do_text_replacements,
+				..default()
+			})
+	}
+
+	/// Deserializes a parsed material with inheritance already applied into a [`GenericMaterial`], adding its inner material as a sub-asset labeled `label`.
+	#[allow(clippy::too_many_arguments)]
+	fn build_generic_material(
+		&self,
+		load_context: &mut LoadContext,
+		#[allow(unused)] settings: &GenericMaterialLoaderSettings,
+		source_settings: &SourceLoaderSettings,
+		parsed: ParsedGenericMaterial<D::Value>,
+		#[allow(unused)] label: String,
+		sub_assets_queued: &mut usize,
+		channel_swizzles: &mut Vec<PendingChannelSwizzle>,
+	) -> Result<GenericMaterial, GenericMaterialLoadError> {
+		// MATERIAL
+
+		#[cfg(feature = "bevy_pbr")]
+		let (mat, reflect_generic_material) = {
+			let type_name = settings
+				.force_type
+				.as_deref()
+				.or(parsed.ty.as_deref())
+				.unwrap_or(StandardMaterial::type_path());
+
+			let type_registry = self.type_registry.read();
+
+			// Find the type we want to make. Explicit shorthands take priority, then full type paths, then short type paths, which may be ambiguous.
+			let shorthands = self.shorthands.values.read().unwrap();
+			let registration = match shorthands.get(type_name).or_else(|| type_registry.get_with_type_path(type_name)) {
+				Some(registration) => registration,
+				None => {
+					let registration_candidates: Vec<_> = type_registry
+						.iter()
+						.filter(|reg| reg.type_info().type_path_table().short_path() == type_name)
+						.collect();
+
+					// Only pass if there's exactly one.
+					match registration_candidates[..] {
+						[] => return Err(GenericMaterialLoadError::MaterialTypeNotFound(type_name.to_string())),
+						[registration] => registration,
+						_ => {
+							return Err(GenericMaterialLoadError::TooManyTypeCandidates(
+								type_name.to_string(),
+								registration_candidates
+									.into_iter()
+									.map(|reg| reg.type_info().type_path().to_string())
+									.collect(),
+							));
+						}
+					}
+				}
+			};
+
+			// Create the material's default value.
+			let Some(reflect_generic_material) = type_registry.get_type_data::<ReflectGenericMaterial>(registration.type_id()).cloned() else {
+				return Err(GenericMaterialLoadError::NotRegisteredGenericMaterial(
+					registration.type_info().type_path(),
+				));
+			};
+			let mut mat = reflect_generic_material.default();
+
+			// Deserialize and process the parsed values into the struct.
+			if let Some(mut material) = parsed.material {
+				let mut processor = MaterialDeserializerProcessor {
+					ctx: MaterialProcessorContext {
+						load_context,
+						color_space: parsed.color_space,
+						sub_assets_queued: 0,
+						allow_external_paths: source_settings.allow_external_paths,
+						unknown_fields: source_settings.unknown_fields,
+						property: None,
+						property_keys: Vec::new(),
+						channel_swizzles: Vec::new(),
+					},
+					material_processor: &self.processor,
+				};
+
+				self.field_hooks
+					.apply(&*self.deserializer, registration.type_id(), &mut material, &mut processor.ctx);
+				self.foreign_fields.apply(
+					&*self.deserializer,
+					&mut material,
+					mat.as_reflect_mut(),
+					self.processor.flattens_extended_materials(),
+				)?;
+
+				let data = TypedReflectDeserializer::with_processor(registration, &type_registry, &mut processor)
+					.deserialize(material)
+					.map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))?;
+
+				apply_material(mat.as_partial_reflect_mut(), data.as_ref())?;
+				*sub_assets_queued += processor.ctx.sub_assets_queued;
+				channel_swizzles.append(&mut processor.ctx.channel_swizzles);
+			}
+
+			(mat, reflect_generic_material)
+		};
+
+		// PROPERTIES
+
+		let mut properties: HashMap<String, Box<dyn Reflect>> = default();
+
+		if let Some(parsed_properties) = parsed.properties {
+			let type_registry = self.type_registry.read();
+			let property_registry = self.property_registry.inner.read().unwrap();
+
+			let mut processor = MaterialDeserializerProcessor {
+				ctx: MaterialProcessorContext {
+					load_context,
+					color_space: parsed.color_space,
+					sub_assets_queued: 0,
+					allow_external_paths: source_settings.allow_external_paths,
+					unknown_fields: source_settings.unknown_fields,
+					property: None,
+					property_keys: Vec::new(),
+					channel_swizzles: Vec::new(),
+				},
+				material_processor: &self.processor,
+			};
+
+			for (key, value) in parsed_properties {
+				let Some(type_id) = property_registry.get(&key).copied() else {
+					return Err(GenericMaterialLoadError::PropertyNotRegistered(key));
+				};
+				let Some(registration) = type_registry.get(type_id) else {
+					return Err(GenericMaterialLoadError::PropertyTypeNotRegistered(key));
+				};
+				let Some(from_reflect) = registration.data::<ReflectFromReflect>() else {
+					return Err(GenericMaterialLoadError::NoFromReflect(registration.type_info().type_path()));
+				};
+
+				processor.ctx.property = Some(key.clone());
+				let partial_data = TypedReflectDeserializer::with_processor(registration, &type_registry, &mut processor)
+					.deserialize(value)
+					.map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))?;
+
+				let Some(mut data) = from_reflect.from_reflect(&*partial_data) else {
+					return Err(GenericMaterialLoadError::FullReflect {
+						ty: partial_data.get_represented_type_info(),
+					});
+				};
+
+				if let Some(property) = registration
+					.data::<ReflectPropertySubAssets>()
+					.and_then(|sub_assets| sub_assets.get_mut(data.as_mut()))
+				{
+					property.add_sub_assets(&key, processor.ctx.load_context);
+				}
+
+				properties.insert(key, data);
+			}
+
+			*sub_assets_queued += processor.ctx.sub_assets_queued;
+			channel_swizzles.append(&mut processor.ctx.channel_swizzles);
+		}
+
+		for (key, default) in self.property_registry.defaults.read().unwrap().iter() {
+			if properties.contains_key(key) {
+				continue;
+			}
+			let Ok(value) = default.reflect_clone() else {
+				return Err(GenericMaterialLoadError::FullReflect {
+					ty: default.get_represented_type_info(),
+				});
+			};
+
+			properties.insert(key.clone(), value);
+		}
+
+		Ok(GenericMaterial {
+			#[cfg(feature = "bevy_pbr")]
+			handle: reflect_generic_material.add_labeled_asset(mat, load_context, label),
+			properties,
+			id: None,
+			computed: default(),
+		})
+	}
+
+	/// Attempts to apply string replacements to a text-based material file, replacing every `${key}` pattern
+	/// registered in [`text_replacement_processor`](Self::text_replacement_processor) or in the [`replacements`](GenericMaterialLoaderSettings::replacements) of `settings`,
+	/// each only if enabled in [`text_replacements`](Self::text_replacements).
+	///
+	/// `name_path` is the path of the material the replacements refer to, see [`TextReplacementContext::path`].
+	///
+	/// Files without any replacements are returned as they are, without being copied.
+	pub fn try_apply_replacements(
+		&self,
+		load_context: &LoadContext,
+		name_path: &AssetPath,
+		settings: &GenericMaterialLoaderSettings,
+		bytes: Vec<u8>,
+	) -> Vec<u8> {
+		// Most files don't use replacements, so we can skip validating and searching them as strings.
+		if !bytes.windows(2).any(|window| window == b"${") {
+			return bytes;
+		}
+
+		let s = match String::from_utf8(bytes) {
 			Ok(x) => x,
 			Err(err) => return err.into_bytes(),
 		};
 
-		if let Some(file_name) = load_context.path().path().with_extension("").file_name().and_then(OsStr::to_str) {
-			s = s.replace("${name}", file_name);
+		let ctx = TextReplacementContext {
+			load_context,
+			path: name_path,
+			settings,
+		};
+		match self.text_replacement_processor.replace_in(&s, &ctx, self.text_replacements) {
+			Some(replaced) => replaced.into_bytes(),
+			None => s.into_bytes(),
 		}
-
-		s.into_bytes()
 	}
 }
 impl<D: MaterialDeserializer, P: MaterialProcessor> AssetLoader for GenericMaterialLoader<D, P> {
 	type Asset = GenericMaterial;
-	type Settings = ();
+	type Settings = GenericMaterialLoaderSettings;
 	type Error = GenericMaterialLoadError;
 
 	fn load(
 		&self,
 		reader: &mut dyn bevy::asset::io::Reader,
-		#[allow(unused)] settings: &Self::Settings,
+		settings: &Self::Settings,
 		#[allow(unused)] load_context: &mut LoadContext,
 	) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
 		Box::pin(async {
 			let mut input = Vec::new();
 			reader.read_to_end(&mut input).await?;
 
-			if self.do_text_replacements {
-				input = self.try_apply_replacements(load_context, input);
-			}
+			let parse_start = Instant::now();
 
-			let parsed: ParsedGenericMaterial<D::Value> = self
-				.deserializer
-				.deserialize(&input)
-				.map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))?;
+			let source_settings = self.settings_for(load_context);
 
-			let parsed = apply_inheritance(self, load_context, parsed).await?;
+			if source_settings.do_text_replacements {
+				input = self.try_apply_replacements(load_context, load_context.path(), settings, input);
+			}
 
-			assert!(parsed.inherits.is_none());
+			let parsed = self.parse(&input)?;
 
-			// MATERIAL
+			let mut metrics = MaterialLoadMetrics::new(load_context.path().clone_owned());
+			metrics.parse_time = parse_start.elapsed();
 
-			#[cfg(feature = "bevy_pbr")]
-			let mat = {
-				let type_name = parsed.ty.as_deref().unwrap_or(StandardMaterial::type_path());
+			let generic_material = self
+				.load_parsed(load_context, settings, &source_settings, parsed, default(), "", &mut metrics)
+				.await?;
 
-				let type_registry = self.type_registry.read();
+			self.metrics.record(metrics);
 
-				// Find candidates for the type we want to make.
-				let mut registration_candidates = Vec::new();
-
-				let shorthands = self.shorthands.values.read().unwrap();
-				for (shorthand, reg) in shorthands.iter() {
-					if type_name == shorthand {
-						registration_candidates.push(reg);
-					}
-				}
+			Ok(generic_material)
+		})
+	}
 
-				for reg in type_registry.iter() {
-					if reg.type_info().type_path() == type_name || reg.type_info().type_path_table().short_path() == type_name {
-						registration_candidates.push(reg);
-					}
-				}
+	fn extensions(&self) -> &[&str] {
+		D::EXTENSIONS
+	}
+}
 
-				// Only pass if there's exactly one.
-				if registration_candidates.is_empty() {
-					return Err(GenericMaterialLoadError::MaterialTypeNotFound(type_name.to_string()));
-				} else if registration_candidates.len() > 1 {
-					return Err(GenericMaterialLoadError::TooManyTypeCandidates(
-						type_name.to_string(),
-						registration_candidates
-							.into_iter()
-							.map(|reg| reg.type_info().type_path().to_string())
-							.collect(),
-					));
-				}
-				let registration = registration_candidates[0];
-
-				// Create the material's default value.
-				let Some(mut mat) = type_registry
-					.get_type_data::<ReflectGenericMaterial>(registration.type_id())
-					.map(ReflectGenericMaterial::default)
-				else {
-					panic!("{} isn't a registered generic material", registration.type_info().type_path());
-				};
+/// Reports the next material of `generic_material`'s animation as a load error if it doesn't exist,
+/// as it's otherwise only checked once this material has loaded. Reading it also reloads this material if it's created later.
+#[cfg(feature = "animation")]
+async fn check_next_material(load_context: &mut LoadContext<'_>, generic_material: &GenericMaterial) -> Result<(), GenericMaterialLoadError> {
+	let Some(path) = generic_material
+		.properties
+		.get(GenericMaterial::ANIMATION.key)
+		.and_then(|animations| animations.downcast_ref::<crate::animation::MaterialAnimations>())
+		.and_then(|animations| animations.next.as_ref())
+		.and_then(|next| next.material.path())
+	else {
+		return Ok(());
+	};
+
+	let path = path.without_label().clone_owned();
+	match load_context.read_asset_bytes(path.clone()).await {
+		Ok(_) => Ok(()),
+		Err(ReadAssetBytesError::AssetReaderError(AssetReaderError::NotFound(_))) => Err(GenericMaterialLoadError::NextMaterialNotFound(path)),
+		Err(err) => Err(std::io::Error::other(err).into()),
+	}
+}
 
-				// Deserialize and process the parsed values into the struct.
-				if let Some(material) = parsed.material {
-					let mut processor = MaterialDeserializerProcessor {
-						ctx: MaterialProcessorContext { load_context },
-						material_processor: &self.processor,
-					};
+/// Reads the images queued to have their channels rearranged, and adds the rearranged images as sub-assets.
+#[cfg(feature = "bevy_pbr")]
+async fn add_channel_swizzles(
+	load_context: &mut LoadContext<'_>,
+	channel_swizzles: Vec<PendingChannelSwizzle>,
+) -> Result<(), GenericMaterialLoadError> {
+	for pending in channel_swizzles {
+		// The same image may be rearranged the same way by several fields or variants.
+		if load_context.get_labeled(&pending.label).is_some() {
+			continue;
+		}
 
-					let data = TypedReflectDeserializer::with_processor(registration, &type_registry, &mut processor)
-						.deserialize(material)
-						.map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))?;
+		let mut image = load_context
+			.load_builder()
+			.load_value::<Image>(pending.path.clone())
+			.await
+			.map_err(|err| GenericMaterialLoadError::ReadChannelSwizzleImage(pending.path.clone(), Box::new(err)))?
+			.take();
 
-					mat.try_apply(data.as_ref())?;
-				}
+		pending.swizzle.apply(&mut image)?;
+		if let Some(sampler) = pending.sampler {
+			image.sampler = bevy::image::ImageSampler::Descriptor(sampler);
+		}
 
-				mat
-			};
+		load_context.add_labeled_asset(pending.label, image);
+	}
 
-			// PROPERTIES
+	Ok(())
+}
 
-			let mut properties: HashMap<String, Box<dyn Reflect>> = default();
+/// Applies deserialized material data to `material`. For struct materials, this is done field by field so errors can name the field that failed.
+#[cfg(feature = "bevy_pbr")]
+fn apply_material(material: &mut dyn PartialReflect, data: &dyn PartialReflect) -> Result<(), GenericMaterialLoadError> {
+	if let ReflectRef::Struct(data) = data.reflect_ref()
+		&& let ReflectMut::Struct(material) = material.reflect_mut()
+	{
+		for (i, value) in data.iter_fields().enumerate() {
+			let Some(name) = data.name_at(i) else { continue };
+			let Some(field) = material.field_mut(name) else { continue };
+
+			if let Err(err) = field.try_apply(value) {
+				return Err(match err {
+					ApplyError::MismatchedTypes { .. } | ApplyError::MismatchedKinds { .. } => GenericMaterialLoadError::WrongType {
+						expected: field.reflect_type_path().to_string(),
+						found: value
+							.get_represented_type_info()
+							.map_or(value.reflect_type_path(), TypeInfo::type_path)
+							.to_string(),
+						field: name.to_string(),
+					},
+					err => GenericMaterialLoadError::InField(name.to_string(), Box::new(err.into())),
+				});
+			}
+		}
 
-			if let Some(parsed_properties) = parsed.properties {
-				let type_registry = self.type_registry.read();
-				let property_registry = self.property_registry.inner.read().unwrap();
+		return Ok(());
+	}
 
-				let mut processor = MaterialDeserializerProcessor {
-					ctx: MaterialProcessorContext { load_context },
-					material_processor: &self.processor,
-				};
+	Ok(material.try_apply(data)?)
+}
 
-				for (key, value) in parsed_properties {
-					let Some(type_id) = property_registry.get(&key).copied() else {
-						return Err(GenericMaterialLoadError::PropertyNotRegistered(key));
-					};
-					let Some(registration) = type_registry.get(type_id) else {
-						return Err(GenericMaterialLoadError::PropertyTypeNotRegistered(key));
-					};
-					let Some(from_reflect) = registration.data::<ReflectFromReflect>() else {
-						return Err(GenericMaterialLoadError::NoFromReflect(registration.type_info().type_path()));
-					};
-
-					let partial_data = TypedReflectDeserializer::with_processor(registration, &type_registry, &mut processor)
-						.deserialize(value)
-						.map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))?;
-
-					let Some(data) = from_reflect.from_reflect(&*partial_data) else {
-						return Err(GenericMaterialLoadError::FullReflect {
-							ty: partial_data.get_represented_type_info(),
-						});
-					};
-
-					properties.insert(key, data);
-				}
-			}
+/// Settings for [`GenericMaterialLoader`], set through `.meta` files or [`AssetServer::load_with_settings`].
+///
+/// # Examples
+/// ```ignore
+/// asset_server.load_with_settings("materials/player.toml", |settings: &mut GenericMaterialLoaderSettings| {
+///     settings.replacements.insert("skin".to_string(), "red".to_string());
+/// });
+/// ```
+///
+/// NOTE: Bevy identifies assets by path alone, so settings (including [`replacements`](Self::replacements)) aren't part of an asset's identity,
+/// and loading a path that's already loaded returns the existing asset regardless of settings.
+/// To load several parameterizations of one file at once, give each its own file that inherits from it, and load those with different settings.
+#[derive(serde::Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GenericMaterialLoaderSettings {
+	/// Replaces `${key}` with `value` in the material file and the materials it inherits from, if text replacements are enabled.
+	pub replacements: BTreeMap<String, String>,
+	/// Loads the material as this type (a shorthand or type path) regardless of the `type` in the file, for reusing generic texture packs with custom materials.
+	#[cfg(feature = "bevy_pbr")]
+	pub force_type: Option<String>,
+}
 
-			Ok(GenericMaterial {
-				#[cfg(feature = "bevy_pbr")]
-				handle: mat.add_labeled_asset(load_context, "Material".to_string()),
-				properties,
-			})
-		})
+/// Toggles for each kind of text replacement [`GenericMaterialLoader`] does, set with [`MaterializePlugin::with_name_replacement`],
+/// [`MaterializePlugin::with_settings_replacements`], and [`MaterializePlugin::with_provider_replacements`].
+///
+/// These only matter if text replacements are enabled at all, see [`MaterializePlugin::with_text_replacements`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextReplacements {
+	/// Whether `${name}` is replaced with the name of the material's file. (Default: `true`)
+	pub name: bool,
+	/// Whether `${key}` is replaced with the [`replacements`](GenericMaterialLoaderSettings::replacements) in the loader settings. (Default: `true`)
+	pub settings: bool,
+	/// Whether `${key}` is replaced with the value of the provider for `key` in the [`TextReplacementProcessor`], other than `${name}`. (Default: `true`)
+	pub providers: bool,
+}
+impl Default for TextReplacements {
+	fn default() -> Self {
+		Self {
+			name: true,
+			settings: true,
+			providers: true,
+		}
 	}
+}
 
-	fn extensions(&self) -> &[&str] {
-		D::EXTENSIONS
+/// Loader settings that can be set per asset source with [`MaterializePlugin::with_source_settings`].
+#[derive(Debug, Clone)]
+pub struct SourceLoaderSettings {
+	/// Whether to replace special patterns in text, such as replacing `${name}` with the name of the material loading.
+	pub do_text_replacements: bool,
+	/// Whether materials from this source may use `inherits`. If `false`, materials that do will fail to load.
+	pub allow_inheritance: bool,
+	/// If `true`, `${name}` in inherited materials is replaced with the name of that inherited material's own file.
+	/// By default it's the name of the material being loaded, so template materials can reference textures like `${name}.png`.
+	pub inherited_name_from_parent: bool,
+	/// Whether paths in `inherits` and asset fields may start with `/` or name an asset source (e.g. `other://texture.png`).
+	/// If `false`, such paths fail to load, keeping materials from untrusted sources like mods from referencing arbitrary assets.
+	pub allow_external_paths: bool,
+	/// What to do when a material inherits from a file that doesn't exist. (Default: [`MissingParent::Error`])
+	pub missing_parent: MissingParent,
+	/// What to do with fields in material files that don't exist on the type they're deserialized into. (Default: [`UnknownFieldPolicy::Error`])
+	pub unknown_fields: UnknownFieldPolicy,
+}
+impl Default for SourceLoaderSettings {
+	fn default() -> Self {
+		Self {
+			do_text_replacements: true,
+			allow_inheritance: true,
+			inherited_name_from_parent: false,
+			allow_external_paths: true,
+			missing_parent: MissingParent::Error,
+			unknown_fields: UnknownFieldPolicy::Error,
+		}
 	}
 }
 
+/// What to do when a material inherits from a file that doesn't exist, see [`SourceLoaderSettings::missing_parent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingParent {
+	/// The material fails to load.
+	#[default]
+	Error,
+	/// The missing file is skipped with a warning, and the material loads with what's left, keeping iteration going while files are being moved or renamed.
+	///
+	/// Since the missing file was never read, the material isn't hot-reloaded when it comes back, so it has to be reloaded some other way.
+	WarnAndSkip,
+}
+
+/// What to do with fields that don't exist on the struct they're deserialized into, see [`SourceLoaderSettings::unknown_fields`].
+///
+/// This applies to every struct in a material file, including nested ones and the fields of properties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownFieldPolicy {
+	/// The material fails to load.
+	#[default]
+	Error,
+	/// Unknown fields are skipped with a warning, keeping materials loading across Bevy versions that rename or remove fields.
+	WarnAndSkip,
+	/// Unknown fields are skipped without a warning.
+	Silent,
+}
+
 /// An in-between step in deserialization.
 /// Stores a structured version of the data actually in the material file itself to be fully deserialized into Rust data.
-#[derive(Deserialize)]
+///
+/// Maps are ordered so that merging, sub-asset labels, and anything else depending on iteration order are the same between runs.
+#[derive(Deserialize, Clone)]
 struct ParsedGenericMaterial<Value: GenericValue> {
-	inherits: Option<String>,
+	/// Stable identifier of the material, see [`GenericMaterial::id`]. Not inherited.
+	id: Option<String>,
+	inherits: Option<Inherits>,
+	/// Local materials that can be inherited from within the same file with `inherits = "#name"`.
+	materials: Option<BTreeMap<String, ParsedGenericMaterial<Value>>>,
+	/// Named variants of the material, each loaded as a labeled sub-asset with its fields merged on top of the material.
+	#[serde(rename = "variant")]
+	variants: Option<BTreeMap<String, ParsedGenericMaterial<Value>>>,
+	/// How color components without an explicit color space are interpreted by `ColorProcessor`.
+	color_space: Option<ColorSpace>,
 	#[cfg(feature = "bevy_pbr")]
 	#[serde(rename = "type")]
 	ty: Option<String>,
 	#[cfg(feature = "bevy_pbr")]
 	material: Option<Value>,
-	properties: Option<HashMap<String, Value>>,
+	properties: Option<BTreeMap<String, Value>>,
 }
 
-/// For unit tests.
-#[doc(hidden)]
+/// The `inherits` field of a material file, either a single material or a list of them.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum Inherits {
+	One(String),
+	Many(Vec<String>),
+}
+impl Inherits {
+	fn into_vec(self) -> Vec<String> {
+		match self {
+			Self::One(path) => vec![path],
+			Self::Many(paths) => paths,
+		}
+	}
+}
+
+/// The key of the table in a file's `material` holding fields merged on top of it when loading on specific platforms, keyed by the names from [`current_platforms`].
+///
+/// Overrides can only be used in formats implementing [`MaterialDeserializer::remove_field`].
+pub const OVERRIDES_KEY: &str = "overrides";
+
+/// Returns the names of the platforms the app is running on, for [`overrides`](OVERRIDES_KEY) in material files, from most to least general.
+///
+/// This includes `web` when running in a browser, `mobile` or `desktop`, and the operating system (e.g. `android`, `ios`, `windows`, `linux`, `macos`).
+/// Overrides for later platforms are merged on top of earlier ones, so `android` takes precedence over `mobile`.
+///
+/// # Examples
+/// ```toml
+/// [material]
+/// base_color_texture = "bricks.png"
+/// parallax_depth_scale = 0.1
+///
+/// [material.overrides.mobile]
+/// parallax_depth_scale = 0.0
+///
+/// [material.overrides.android]
+/// base_color_texture = "bricks.ktx2"
+/// ```
+pub fn current_platforms() -> Vec<&'static str> {
+	let mut platforms = Vec::new();
+
+	if cfg!(target_family = "wasm") {
+		platforms.push("web");
+	} else if cfg!(any(target_os = "android", target_os = "ios")) {
+		platforms.push("mobile");
+	} else {
+		platforms.push("desktop");
+	}
+
+	if !std::env::consts::OS.is_empty() {
+		platforms.push(std::env::consts::OS);
+	}
+
+	platforms
+}
+
+/// Creates a minimal headless [`App`] that can load generic materials, for unit testing material files and custom processors.
+///
+/// Register your properties and materials on it, then load with [`load_generic_material_blocking`].
 #[cfg(feature = "bevy_pbr")]
-pub fn create_loading_test_app(deserializer: impl MaterialDeserializer) -> App {
+pub fn create_material_test_app(deserializer: impl MaterialDeserializer) -> App {
 	let mut app = App::new();
 
 	app.add_plugins((
@@ -220,45 +754,47 @@ pub fn create_loading_test_app(deserializer: impl MaterialDeserializer) -> App {
 		ImagePlugin::default(),
 		MaterializePlugin::new(deserializer),
 	))
-	.register_material_property_manual::<bool>("collision")
-	.register_material_property_manual::<String>("sounds")
 	.init_asset::<StandardMaterial>();
 
 	app
 }
 
-#[test]
-fn load_toml() {
-	let app = create_loading_test_app(TomlMaterialDeserializer);
-	let asset_server = app.world().resource::<AssetServer>();
+/// For unit tests.
+#[doc(hidden)]
+#[cfg(feature = "bevy_pbr")]
+pub fn create_loading_test_app(deserializer: impl MaterialDeserializer) -> App {
+	let mut app = create_material_test_app(deserializer);
 
-	smol::block_on(async {
-		asset_server.load_builder().load_untyped_async("materials/animated.toml").await.unwrap();
-		// Custom materials require special scaffolding in the associated example, and so the test is there.
-		asset_server
-			.load_builder()
-			.load_untyped_async("materials/example.material.toml")
-			.await
-			.unwrap();
-		asset_server
-			.load_builder()
-			.load_untyped_async("materials/sub-material.toml")
-			.await
-			.unwrap();
-	});
+	app.register_material_property_manual::<bool>("collision")
+		.register_material_property_manual::<String>("sounds");
+
+	app
 }
 
-#[cfg(feature = "json")]
-#[test]
-fn load_json() {
-	let app = create_loading_test_app(JsonMaterialDeserializer);
-	let asset_server = app.world().resource::<AssetServer>();
+/// Loads a [`GenericMaterial`] and everything it depends on, blocking until it's done, then updates `app` once so the material is in its [`Assets`].
+///
+/// Meant for unit tests, usually with an app from [`create_material_test_app`].
+///
+/// # Examples
+/// ```ignore
+/// let mut app = create_material_test_app(TomlMaterialDeserializer);
+/// let handle = load_generic_material_blocking(&mut app, "materials/example.toml").unwrap();
+/// let material = app.world().resource::<Assets<GenericMaterial>>().get(&handle).unwrap();
+/// ```
+#[cfg(feature = "bevy_pbr")]
+pub fn load_generic_material_blocking<'a>(
+	app: &mut App,
+	path: impl Into<AssetPath<'a>>,
+) -> Result<Handle<GenericMaterial>, LoadGenericMaterialBlockingError> {
+	let path = path.into().into_owned();
+	let asset_server = app.world().resource::<AssetServer>().clone();
 
-	smol::block_on(async {
-		asset_server
-			.load_builder()
-			.load_untyped_async("materials/example.material.json")
-			.await
-			.unwrap();
-	});
+	let handle = bevy::tasks::block_on(asset_server.load_builder().load_untyped_async(path.clone()))?;
+
+	// Loaded assets are added to their `Assets` when asset events are handled.
+	app.update();
+
+	handle
+		.try_typed()
+		.map_err(|_| LoadGenericMaterialBlockingError::NotAGenericMaterial(path))
 }
@@ -0,0 +1,235 @@
+use std::path::PathBuf;
+
+use bevy::asset::{AssetLoader, AssetPath, LoadContext};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::tasks::ConditionalSendFuture;
+
+use super::MtlLoadError;
+use super::library::GenericMaterialLibrary;
+use crate::erased_material::ErasedMaterial;
+use crate::generic_material::GenericMaterial;
+
+/// Loads Wavefront `.mtl` files as a [`GenericMaterialLibrary`], with a [`GenericMaterial`] labeled with the name of each material.
+///
+/// This lets `GenericMaterial3d(asset_server.load("props.mtl#crate"))` just work with asset packs that only ship MTL files.
+///
+/// Texture paths are relative to the `.mtl` file, and may use `\` as a separator. Texture options (like `-bm 0.5`) are skipped,
+/// using the last word of the statement as the path, so paths containing spaces aren't supported.
+///
+/// Comments start with a word beginning with `#`, so `#` can still be used inside texture paths.
+#[derive(TypePath, Debug, Clone)]
+pub struct MtlLoader {
+	/// A function that provides the underlying material from the statements of an MTL material. Default is [`MtlMaterial::to_standard_material`].
+	pub material: fn(&MtlMaterial) -> Box<dyn ErasedMaterial>,
+	pub properties: fn() -> HashMap<String, Box<dyn Reflect>>,
+}
+impl Default for MtlLoader {
+	fn default() -> Self {
+		Self {
+			material: |mtl| mtl.to_standard_material().into(),
+			properties: HashMap::default,
+		}
+	}
+}
+impl AssetLoader for MtlLoader {
+	type Asset = GenericMaterialLibrary;
+	type Settings = ();
+	type Error = MtlLoadError;
+
+	fn load(
+		&self,
+		reader: &mut dyn bevy::asset::io::Reader,
+		_settings: &Self::Settings,
+		load_context: &mut LoadContext,
+	) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+		Box::pin(async {
+			let mut input = String::new();
+			reader.read_to_string(&mut input).await?;
+
+			let mut materials = HashMap::default();
+			for mtl in read_mtl(&input, load_context)? {
+				let material = (self.material)(&mtl);
+
+				let generic_material = GenericMaterial {
+					handle: material.add_labeled_asset(load_context, format!("{}/Material", mtl.name)),
+					properties: (self.properties)(),
+					id: None,
+					computed: default(),
+				};
+
+				let handle = load_context.add_labeled_asset(mtl.name.clone(), generic_material);
+				materials.insert(mtl.name, handle);
+			}
+
+			Ok(GenericMaterialLibrary { materials })
+		})
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["mtl"]
+	}
+}
+
+/// The statements of a material in an `.mtl` file that have a counterpart in [`StandardMaterial`]. Anything else, such as `illum` or ambient colors, is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct MtlMaterial {
+	/// The name given by `newmtl`.
+	pub name: String,
+	/// `Kd`
+	pub diffuse: Option<Vec3>,
+	/// `map_Kd`
+	pub diffuse_texture: Option<Handle<Image>>,
+	/// `Ns`, the Blinn-Phong specular exponent, usually between 0 and 1000.
+	pub specular_exponent: Option<f32>,
+	/// `d`, or 1 minus `Tr`.
+	pub dissolve: Option<f32>,
+	/// `Ke`
+	pub emissive: Option<Vec3>,
+	/// `map_Ke`
+	pub emissive_texture: Option<Handle<Image>>,
+	/// `norm`, `map_Bump`, or `bump`. These are assumed to be normal maps, since height maps aren't supported by [`StandardMaterial`].
+	pub normal_texture: Option<Handle<Image>>,
+	/// `Pr`, from the PBR extension.
+	pub roughness: Option<f32>,
+	/// `Pm`, from the PBR extension.
+	pub metallic: Option<f32>,
+}
+impl MtlMaterial {
+	/// Converts to a [`StandardMaterial`], approximating roughness from the specular exponent if `Pr` isn't specified.
+	/// Materials with a dissolve under 1 are alpha blended.
+	pub fn to_standard_material(&self) -> StandardMaterial {
+		let alpha = self.dissolve.unwrap_or(1.);
+		let diffuse = self.diffuse.unwrap_or(Vec3::ONE);
+
+		StandardMaterial {
+			base_color: Color::srgba(diffuse.x, diffuse.y, diffuse.z, alpha),
+			base_color_texture: self.diffuse_texture.clone(),
+			perceptual_roughness: self
+				.roughness
+				.or(self.specular_exponent.map(specular_exponent_to_roughness))
+				.unwrap_or(1.),
+			metallic: self.metallic.unwrap_or(0.),
+			emissive: self
+				.emissive
+				.map(|emissive| LinearRgba::rgb(emissive.x, emissive.y, emissive.z))
+				.unwrap_or(LinearRgba::BLACK),
+			emissive_texture: self.emissive_texture.clone(),
+			normal_map_texture: self.normal_texture.clone(),
+			alpha_mode: if alpha < 1. { AlphaMode::Blend } else { AlphaMode::Opaque },
+			..default()
+		}
+	}
+}
+
+/// Approximates perceptual roughness from a Blinn-Phong specular exponent.
+///
+/// The common `sqrt(2 / (Ns + 2))` mapping gives the Beckmann alpha, and perceptual roughness is the square root of that.
+pub fn specular_exponent_to_roughness(specular_exponent: f32) -> f32 {
+	(2. / (specular_exponent.max(0.) + 2.)).powf(0.25).clamp(0.089, 1.)
+}
+
+/// Reads every material of an `.mtl` file, loading the textures it refers to.
+fn read_mtl(input: &str, load_context: &mut LoadContext) -> Result<Vec<MtlMaterial>, MtlLoadError> {
+	let mut materials: Vec<MtlMaterial> = Vec::new();
+
+	for (i, line) in input.lines().enumerate() {
+		let line_number = i + 1;
+		let mut words = line.split_whitespace().take_while(|word| !word.starts_with('#'));
+		let Some(statement) = words.next() else { continue };
+
+		if statement == "newmtl" {
+			let name = words.collect::<Vec<_>>().join(" ");
+			if name.is_empty() {
+				return Err(MtlLoadError::MissingName(line_number));
+			}
+			materials.push(MtlMaterial { name, ..default() });
+			continue;
+		}
+
+		let number = |word: Option<&str>| -> Result<f32, MtlLoadError> {
+			let word = word.ok_or(MtlLoadError::MissingValue(line_number))?;
+			word.parse().map_err(|_| MtlLoadError::InvalidNumber(line_number, word.to_string()))
+		};
+		let mut texture = |words: &mut dyn Iterator<Item = &str>| -> Result<Handle<Image>, MtlLoadError> {
+			let path = words.last().ok_or(MtlLoadError::MissingValue(line_number))?;
+			// Parsed as a file path rather than an asset path, which would treat `#` as the start of a label.
+			let path = AssetPath::from_path_buf(PathBuf::from(path.replace('\\', "/")));
+			let path = load_context.path().parent().unwrap_or_default().resolve(&path);
+			Ok(load_context.load(path))
+		};
+
+		let Some(material) = materials.last_mut() else { return Err(MtlLoadError::OutsideMaterial(line_number)) };
+
+		match statement {
+			"Kd" => material.diffuse = Some(Vec3::new(number(words.next())?, number(words.next())?, number(words.next())?)),
+			"Ns" => material.specular_exponent = Some(number(words.next())?),
+			"d" => material.dissolve = Some(number(words.next())?),
+			"Tr" => material.dissolve = Some(1. - number(words.next())?),
+			"Ke" => material.emissive = Some(Vec3::new(number(words.next())?, number(words.next())?, number(words.next())?)),
+			"map_Kd" => material.diffuse_texture = Some(texture(&mut words)?),
+			"map_Ke" => material.emissive_texture = Some(texture(&mut words)?),
+			"norm" | "map_Bump" | "map_bump" | "bump" => material.normal_texture = Some(texture(&mut words)?),
+			"Pr" => material.roughness = Some(number(words.next())?),
+			"Pm" => material.metallic = Some(number(words.next())?),
+			_ => {}
+		}
+	}
+
+	Ok(materials)
+}
+
+#[test]
+fn specular_exponent_roughness() {
+	for (specular_exponent, roughness) in [(0., 1.), (2., 0.840_896), (14., 0.594_604), (1000., 0.211_474)] {
+		let approximated = specular_exponent_to_roughness(specular_exponent);
+		assert!(
+			(approximated - roughness).abs() < 1e-5,
+			"Ns {specular_exponent}: {approximated} != {roughness}"
+		);
+	}
+	// Extremely glossy materials are clamped to the lowest roughness Bevy handles well.
+	assert_eq!(specular_exponent_to_roughness(1e9), 0.089);
+}
+
+#[test]
+fn load_mtl() {
+	let mut app = super::create_loading_test_app(super::TomlMaterialDeserializer);
+	app.register_asset_loader(MtlLoader::default());
+
+	let handle = bevy::tasks::block_on(
+		app.world()
+			.resource::<AssetServer>()
+			.load_builder()
+			.load_untyped_async("materials/props.mtl"),
+	)
+	.unwrap();
+	app.update();
+
+	let world = app.world();
+	let library = world
+		.resource::<Assets<GenericMaterialLibrary>>()
+		.get(&handle.try_typed::<GenericMaterialLibrary>().unwrap())
+		.unwrap();
+	let generic_material = world
+		.resource::<Assets<GenericMaterial>>()
+		.get(&library.materials["wooden crate"])
+		.unwrap();
+	let material = world
+		.resource::<Assets<StandardMaterial>>()
+		.get(&generic_material.handle.typed::<StandardMaterial>().unwrap())
+		.unwrap();
+
+	assert_eq!(material.base_color, Color::srgba(0.8, 0.6, 0.4, 0.5));
+	assert_eq!(material.alpha_mode, AlphaMode::Blend);
+	assert!((material.perceptual_roughness - 0.594_604).abs() < 1e-5);
+	assert_eq!(
+		material
+			.base_color_texture
+			.as_ref()
+			.and_then(Handle::path)
+			.map(ToString::to_string)
+			.as_deref(),
+		Some("materials/textures/crate#1.png")
+	);
+}
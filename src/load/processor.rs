@@ -1,6 +1,23 @@
+use std::fmt;
+
 use ::serde;
-use bevy::reflect::{serde::*, *};
-use bevy::{asset::LoadContext, prelude::*};
+use bevy::reflect::{
+	map::{DynamicMap, Map, MapInfo},
+	serde::*,
+	structs::{DynamicStruct, StructInfo},
+	*,
+};
+use bevy::{
+	asset::{AssetPath, LoadContext},
+	prelude::*,
+};
+use serde::{
+	Deserialize,
+	de::{self, MapAccess, Visitor},
+};
+
+use super::UnknownFieldPolicy;
+use super::channels::PendingChannelSwizzle;
 
 /// API wrapping Bevy's [`ReflectDeserializerProcessor`](https://docs.rs/bevy/latest/bevy/reflect/serde/trait.ReflectDeserializerProcessor.html).
 /// This allows you to modify data as it's being deserialized. For example, this system is used for loading assets, treating strings as paths.
@@ -59,6 +76,14 @@ pub trait MaterialProcessor: TypePath + Clone + Send + Sync + 'static {
 			Ok(Err(deserializer))
 		}
 	}
+
+	/// Whether this processor or one of its children lets the base material's fields of [`ExtendedMaterial`](bevy::pbr::ExtendedMaterial)s be written at the top level,
+	/// like [`FlattenExtendedMaterialProcessor`](super::flatten::FlattenExtendedMaterialProcessor) does.
+	///
+	/// Used to route [foreign fields](crate::MaterializeAppExt::register_generic_material_foreign_field) of the base material, which don't go through processors.
+	fn flattens_extended_materials(&self) -> bool {
+		self.child().is_some_and(|child| child.flattens_extended_materials())
+	}
 }
 
 /// The root processor. Has no child, and immediately gives back its deserializer.
@@ -80,8 +105,30 @@ impl MaterialProcessor for () {
 }
 
 /// Data used for [`MaterialProcessor`]
+///
+/// Processors only ever run inside the [`GenericMaterial`](crate::GenericMaterial) loader, so there is a single deserialization path:
+/// sub-assets are always loaded through [`load_context`](Self::load_context), with paths relative to the material file.
 pub struct MaterialProcessorContext<'w, 'l> {
 	pub load_context: &'l mut LoadContext<'w>,
+	/// The color space of the material file, used by `ColorProcessor`.
+	pub color_space: Option<ColorSpace>,
+	/// How many sub-assets have been queued for loading, used for [`MaterializeLoadMetrics`](super::metrics::MaterializeLoadMetrics).
+	pub sub_assets_queued: usize,
+	/// Whether asset paths may be absolute or name an asset source, see [`SourceLoaderSettings::allow_external_paths`](super::SourceLoaderSettings::allow_external_paths).
+	pub allow_external_paths: bool,
+	/// What to do with fields that don't exist on the struct they're deserialized into, see [`SourceLoaderSettings::unknown_fields`](super::SourceLoaderSettings::unknown_fields).
+	pub unknown_fields: UnknownFieldPolicy,
+	/// The key of the property currently being deserialized, or [`None`] while deserializing the material itself.
+	///
+	/// Lets processors treat values differently per property, such as loading the assets of one property with different settings.
+	pub property: Option<String>,
+	/// The keys of the maps within [`property`](Self::property) leading to the value currently being deserialized,
+	/// e.g. `["footstep"]` for `"step.ogg"` in `sounds = { footstep = "step.ogg" }`, so processors can also treat values differently per key.
+	///
+	/// Only maps with [`String`] keys are tracked.
+	pub property_keys: Vec<String>,
+	/// Images that have to be read and have their channels rearranged before the material finishes loading, see [`PendingChannelSwizzle`].
+	pub channel_swizzles: Vec<PendingChannelSwizzle>,
 }
 
 /// Contains a [`MaterialProcessor`] and context, and kicks off the processing.
@@ -97,7 +144,170 @@ impl<P: MaterialProcessor> ReflectDeserializerProcessor for MaterialDeserializer
 		registry: &TypeRegistry,
 		deserializer: D,
 	) -> Result<Result<Box<dyn PartialReflect>, D>, D::Error> {
-		self.material_processor
-			.try_deserialize_recursive(&mut self.ctx, registration, registry, deserializer)
+		let deserializer = match self
+			.material_processor
+			.try_deserialize_recursive(&mut self.ctx, registration, registry, deserializer)?
+		{
+			Err(deserializer) => deserializer,
+			out => return Ok(out),
+		};
+
+		if let Some(info) = string_keyed_map_info(registration).filter(|_| self.ctx.property.is_some()) {
+			return deserializer
+				.deserialize_map(PropertyMapVisitor {
+					type_info: registration.type_info(),
+					info,
+					registry,
+					processor: self,
+				})
+				.map(Ok);
+		}
+
+		if self.ctx.unknown_fields != UnknownFieldPolicy::Error {
+			let (policy, path) = (self.ctx.unknown_fields, self.ctx.load_context.path().clone_owned());
+			return try_deserialize_skipping_unknown_fields(policy, path, registration, registry, deserializer, self);
+		}
+
+		Ok(Err(deserializer))
+	}
+}
+
+/// Returns the [`MapInfo`] of `registration` if it's a map with [`String`] keys,
+/// which properties deserialize entry by entry to track [`MaterialProcessorContext::property_keys`].
+fn string_keyed_map_info(registration: &TypeRegistration) -> Option<&'static MapInfo> {
+	match registration.type_info() {
+		TypeInfo::Map(info) if info.key_ty().is::<String>() => Some(info),
+		_ => None,
+	}
+}
+
+/// Deserializes a map within a property entry by entry, pushing each key to [`MaterialProcessorContext::property_keys`] while deserializing its value.
+struct PropertyMapVisitor<'a, 'w, 'l, P: MaterialProcessor> {
+	type_info: &'static TypeInfo,
+	info: &'static MapInfo,
+	registry: &'a TypeRegistry,
+	processor: &'a mut MaterialDeserializerProcessor<'w, 'l, P>,
+}
+impl<'de, P: MaterialProcessor> Visitor<'de> for PropertyMapVisitor<'_, '_, '_, P> {
+	type Value = Box<dyn PartialReflect>;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "a map of {}", self.info.type_path())
 	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+		let value_ty = self.info.value_ty();
+		let Some(registration) = self.registry.get(value_ty.id()) else {
+			return Err(de::Error::custom(format!("Type {} isn't registered", value_ty.path())));
+		};
+
+		let mut output = DynamicMap::default();
+		output.set_represented_type(Some(self.type_info));
+
+		while let Some(key) = map.next_key::<String>()? {
+			self.processor.ctx.property_keys.push(key.clone());
+			let value = map.next_value_seed(TypedReflectDeserializer::with_processor(
+				registration,
+				self.registry,
+				&mut *self.processor,
+			));
+			self.processor.ctx.property_keys.pop();
+
+			output.insert_boxed(Box::new(key), value?);
+		}
+
+		Ok(Box::new(output))
+	}
+}
+
+/// Deserializes a struct field by field, handling fields that don't exist on it according to `policy` rather than always failing.
+/// Gives the deserializer back for anything other than structs, and for structs that deserialize with serde.
+pub fn try_deserialize_skipping_unknown_fields<'de, D: serde::Deserializer<'de>, R: ReflectDeserializerProcessor>(
+	policy: UnknownFieldPolicy,
+	path: AssetPath<'static>,
+	registration: &TypeRegistration,
+	registry: &TypeRegistry,
+	deserializer: D,
+	processor: &mut R,
+) -> Result<Result<Box<dyn PartialReflect>, D>, D::Error> {
+	let TypeInfo::Struct(info) = registration.type_info() else { return Ok(Err(deserializer)) };
+	if registration.contains::<ReflectDeserialize>() {
+		return Ok(Err(deserializer));
+	}
+
+	deserializer
+		.deserialize_map(SkipUnknownFieldsVisitor {
+			type_info: registration.type_info(),
+			info,
+			registry,
+			processor,
+			policy,
+			path,
+		})
+		.map(Ok)
+}
+
+struct SkipUnknownFieldsVisitor<'a, R: ReflectDeserializerProcessor> {
+	type_info: &'static TypeInfo,
+	info: &'static StructInfo,
+	registry: &'a TypeRegistry,
+	processor: &'a mut R,
+	policy: UnknownFieldPolicy,
+	path: AssetPath<'static>,
+}
+impl<'de, R: ReflectDeserializerProcessor> Visitor<'de> for SkipUnknownFieldsVisitor<'_, R> {
+	type Value = Box<dyn PartialReflect>;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "a map of {} fields", self.info.type_path())
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+		let mut output = DynamicStruct::default();
+		output.set_represented_type(Some(self.type_info));
+
+		while let Some(key) = map.next_key::<String>()? {
+			let Some(field) = self.info.field(&key) else {
+				skip_unknown_field(&mut map, self.policy, &self.path, &key, self.info.type_path())?;
+				continue;
+			};
+			let Some(registration) = self.registry.get(field.type_id()) else {
+				return Err(de::Error::custom(format!("Type {} of field `{key}` isn't registered", field.type_path())));
+			};
+
+			let value = map.next_value_seed(TypedReflectDeserializer::with_processor(
+				registration,
+				self.registry,
+				&mut *self.processor,
+			))?;
+			output.insert_boxed(key, value);
+		}
+
+		Ok(Box::new(output))
+	}
+}
+
+/// Skips the value of field `key`, which doesn't exist on `type_path`, if `policy` allows it.
+pub(crate) fn skip_unknown_field<'de, A: MapAccess<'de>>(
+	map: &mut A,
+	policy: UnknownFieldPolicy,
+	path: &AssetPath,
+	key: &str,
+	type_path: &str,
+) -> Result<(), A::Error> {
+	match policy {
+		UnknownFieldPolicy::Error => return Err(de::Error::custom(format!("Field `{key}` doesn't exist on {type_path}"))),
+		UnknownFieldPolicy::WarnAndSkip => warn!("{path}: Skipping field `{key}`, as it doesn't exist on {type_path}"),
+		UnknownFieldPolicy::Silent => {}
+	}
+
+	map.next_value::<de::IgnoredAny>().map(|_| ())
+}
+
+/// The color space color components in a material file are authored in. Set for a whole file with the top-level `color_space` key.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorSpace {
+	Srgb,
+	Linear,
 }
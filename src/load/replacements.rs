@@ -0,0 +1,178 @@
+use std::ffi::OsStr;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use bevy::asset::{AssetPath, LoadContext};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use super::{GenericMaterialLoaderSettings, TextReplacements, current_platforms};
+
+/// Computes the value of a `${key}` pattern, or returns [`None`] to leave it as it is. See [`TextReplacementProcessor`].
+pub type TextReplacementProvider = Arc<dyn Fn(&TextReplacementContext) -> Option<String> + Send + Sync>;
+
+/// What a [`TextReplacementProvider`] can compute its value from.
+pub struct TextReplacementContext<'a, 'w> {
+	/// The context of the material being loaded.
+	pub load_context: &'a LoadContext<'w>,
+	/// The path of the material `${name}` refers to. This is the material being loaded, even inside the materials it inherits from,
+	/// unless [`SourceLoaderSettings::inherited_name_from_parent`](super::SourceLoaderSettings::inherited_name_from_parent) is set.
+	pub path: &'a AssetPath<'a>,
+	pub settings: &'a GenericMaterialLoaderSettings,
+}
+
+/// The registry of `${key}` patterns replaced in material files before they're parsed.
+///
+/// These are provided by default:
+/// - `${name}`: The file name of the material without its last extension, such as `bricks` for `bricks.toml`, or `bricks.material` for `bricks.material.toml`.
+/// - `${stem}`: The file name of the material without any extensions, such as `bricks` for `bricks.material.toml`.
+/// - `${parent}`: The name of the folder the material is in.
+/// - `${platform}`: `web`, `mobile` or `desktop`, the first of the [`current_platforms`].
+///
+/// [`replacements`](GenericMaterialLoaderSettings::replacements) in the loader settings take precedence over everything registered here.
+/// Patterns are replaced when a material is loaded, so materials that are already loaded aren't affected by changes until they're reloaded.
+///
+/// # Examples
+/// ```ignore
+/// let replacements = app.world().resource::<TextReplacementProcessor>();
+///
+/// // A fixed value, such as one picked by graphics quality settings.
+/// replacements.set_variable("quality.metallic", "0.5");
+///
+/// // A value computed for each material.
+/// replacements.register("extension", |ctx| ctx.path.get_full_extension());
+///
+/// // A value read from shared state, so it can change without registering the provider again.
+/// let skin = Arc::new(RwLock::new(String::from("red")));
+/// replacements.register("skin", move |_| Some(skin.read().unwrap().clone()));
+/// ```
+#[derive(Resource, Clone)]
+pub struct TextReplacementProcessor {
+	pub providers: Arc<RwLock<HashMap<String, TextReplacementProvider>>>,
+}
+impl Default for TextReplacementProcessor {
+	fn default() -> Self {
+		let replacements = Self { providers: default() };
+
+		replacements.register(Self::NAME, |ctx| {
+			ctx.path.path().with_extension("").file_name().and_then(OsStr::to_str).map(str::to_string)
+		});
+		replacements.register("stem", |ctx| {
+			let file_name = ctx.path.path().file_name().and_then(OsStr::to_str)?;
+			file_name.split('.').next().map(str::to_string)
+		});
+		replacements.register("parent", |ctx| {
+			ctx.path
+				.path()
+				.parent()
+				.and_then(Path::file_name)
+				.and_then(OsStr::to_str)
+				.map(str::to_string)
+		});
+		replacements.register("platform", |_| current_platforms().first().map(|platform| platform.to_string()));
+
+		replacements
+	}
+}
+impl TextReplacementProcessor {
+	/// The key of the `${name}` replacement, which can be toggled separately from other providers with [`TextReplacements::name`].
+	pub const NAME: &str = "name";
+
+	/// Registers `provider` to replace `${key}`, returning the previous provider for `key`, if any.
+	pub fn register(
+		&self,
+		key: impl Into<String>,
+		provider: impl Fn(&TextReplacementContext) -> Option<String> + Send + Sync + 'static,
+	) -> Option<TextReplacementProvider> {
+		self.providers.write().unwrap().insert(key.into(), Arc::new(provider))
+	}
+
+	/// Makes `${key}` be replaced with `value`, returning the previous provider for `key`, if any.
+	pub fn set_variable(&self, key: impl Into<String>, value: impl Into<String>) -> Option<TextReplacementProvider> {
+		let value = value.into();
+		self.register(key, move |_| Some(value.clone()))
+	}
+
+	/// Removes the provider for `${key}`, returning it, if any.
+	pub fn remove(&self, key: &str) -> Option<TextReplacementProvider> {
+		self.providers.write().unwrap().remove(key)
+	}
+
+	/// Replaces every `${key}` in `s` that's enabled in `enabled` and has a value, returning [`None`] if there weren't any.
+	pub fn replace_in(&self, s: &str, ctx: &TextReplacementContext, enabled: TextReplacements) -> Option<String> {
+		let providers = self.providers.read().unwrap();
+
+		replace_patterns(s, |key| {
+			if let Some(value) = ctx.settings.replacements.get(key).filter(|_| enabled.settings) {
+				return Some(value.clone());
+			}
+
+			let provider_enabled = match key {
+				Self::NAME => enabled.name,
+				_ => enabled.providers,
+			};
+			if !provider_enabled {
+				return None;
+			}
+
+			providers.get(key)?(ctx)
+		})
+	}
+}
+
+/// Replaces every `${key}` in `s` that `replacement` returns a value for, returning [`None`] if there weren't any.
+///
+/// Replaced values aren't searched for patterns themselves, and a `${` without a closing `}` is left as it is.
+pub(crate) fn replace_patterns(s: &str, mut replacement: impl FnMut(&str) -> Option<String>) -> Option<String> {
+	let mut replaced = String::new();
+	let mut rest = s;
+	let mut any = false;
+
+	while let Some(start) = rest.find("${") {
+		let Some(len) = rest[start + 2..].find('}') else { break };
+		let key = &rest[start + 2..start + 2 + len];
+		let end = start + 2 + len + 1;
+
+		match replacement(key) {
+			Some(value) => {
+				replaced.push_str(&rest[..start]);
+				replaced.push_str(&value);
+				any = true;
+			}
+			None => replaced.push_str(&rest[..end]),
+		}
+		rest = &rest[end..];
+	}
+
+	any.then(|| replaced + rest)
+}
+
+#[test]
+fn replace_patterns_in_text() {
+	let replacement = |key: &str| match key {
+		"name" => Some("bricks".to_string()),
+		"empty" => Some(String::new()),
+		_ => None,
+	};
+
+	assert_eq!(replace_patterns("${name}.png", replacement).as_deref(), Some("bricks.png"));
+	assert_eq!(
+		replace_patterns("${name}/${name}_normal.png", replacement).as_deref(),
+		Some("bricks/bricks_normal.png")
+	);
+	assert_eq!(replace_patterns("a${empty}b", replacement).as_deref(), Some("ab"));
+	// Unknown keys are left in place, but others around them are still replaced.
+	assert_eq!(replace_patterns("${unknown}-${name}", replacement).as_deref(), Some("${unknown}-bricks"));
+	assert_eq!(replace_patterns("${unknown}", replacement), None);
+	assert_eq!(replace_patterns("no patterns", replacement), None);
+	// Unclosed patterns are left as they are.
+	assert_eq!(replace_patterns("${name}${name", replacement).as_deref(), Some("bricks${name"));
+	assert_eq!(replace_patterns("$name {name}", replacement), None);
+}
+
+#[test]
+fn replaced_values_are_not_replaced_again() {
+	let replacement = |key: &str| (key == "a").then(|| "${a}".to_string());
+
+	assert_eq!(replace_patterns("${a}", replacement).as_deref(), Some("${a}"));
+}
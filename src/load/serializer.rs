@@ -0,0 +1,255 @@
+#[cfg(feature = "bevy_pbr")]
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use bevy::asset::{ReflectHandle, io::AssetSourceId};
+#[cfg(feature = "bevy_pbr")]
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+#[cfg(feature = "bevy_pbr")]
+use bevy::reflect::ReflectRef;
+use bevy::reflect::{
+	TypeRegistry,
+	serde::{ReflectSerializerProcessor, TypedReflectSerializer},
+};
+#[cfg(feature = "bevy_pbr")]
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+use crate::generic_material::GenericMaterial;
+#[cfg(feature = "bevy_pbr")]
+use crate::generic_material::ReflectGenericMaterial;
+
+use super::SerializeMaterialError;
+#[cfg(feature = "bevy_pbr")]
+use super::hooks::{ForeignField, GenericMaterialForeignFields};
+
+/// Counterpart to [`MaterialDeserializer`](super::deserializer::MaterialDeserializer), writing material files.
+/// Implemented by [`TomlMaterialDeserializer`](super::deserializer::TomlMaterialDeserializer) and [`JsonMaterialDeserializer`](super::deserializer::JsonMaterialDeserializer).
+pub trait MaterialSerializer: Send + Sync + 'static {
+	type Error: Error + Send + Sync + 'static;
+
+	/// Serializes a value into the raw bytes of a file.
+	fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, Self::Error>;
+}
+
+#[cfg(feature = "toml")]
+impl MaterialSerializer for super::deserializer::TomlMaterialDeserializer {
+	type Error = toml::ser::Error;
+
+	fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+		toml::to_string_pretty(value).map(String::into_bytes)
+	}
+}
+
+#[cfg(feature = "json")]
+impl MaterialSerializer for super::deserializer::JsonMaterialDeserializer {
+	type Error = serde_json::Error;
+
+	fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+		serde_json::to_vec_pretty(value)
+	}
+}
+
+impl GenericMaterial {
+	/// Writes this material out as a material file, for things like in-game material editors.
+	///
+	/// Only fields of the material that differ from its registered default are written, and handles are written as paths from the root of their asset source,
+	/// such as `/textures/bricks.png`, or `source://textures/bricks.png` for assets from named sources.
+	/// Handles without a path, such as ones to images created at runtime, can't be written and produce an error.
+	///
+	/// # Examples
+	/// ```ignore
+	/// let bytes = generic_material.serialize_with(&TomlMaterialDeserializer, world)?;
+	/// std::fs::write("assets/materials/edited.toml", bytes)?;
+	/// ```
+	pub fn serialize_with<S: MaterialSerializer>(&self, serializer: &S, world: &World) -> Result<Vec<u8>, SerializeMaterialError> {
+		let registry = world.resource::<AppTypeRegistry>().read();
+
+		#[cfg(feature = "bevy_pbr")]
+		let material = self.handle.get_from_world(world).ok_or(SerializeMaterialError::NotLoaded)?;
+		#[cfg(feature = "bevy_pbr")]
+		let foreign_fields = world.resource::<GenericMaterialForeignFields>().inner.read().unwrap();
+		#[cfg(feature = "bevy_pbr")]
+		let default = registry
+			.get_type_data::<ReflectGenericMaterial>(self.handle.inner().type_id())
+			.map(ReflectGenericMaterial::default);
+
+		let file = SerializedGenericMaterial {
+			#[cfg(feature = "bevy_pbr")]
+			ty: material.reflect_type_path(),
+			#[cfg(feature = "bevy_pbr")]
+			material: SerializedMaterialFields {
+				material: material.as_partial_reflect(),
+				default: default.as_deref().map(|default| default.as_partial_reflect()),
+				registry: &registry,
+				foreign_fields: &foreign_fields,
+			},
+			properties: self
+				.properties
+				.iter()
+				.map(|(key, value)| {
+					(
+						key.as_str(),
+						TypedReflectSerializer::with_processor(value.as_partial_reflect(), &registry, &HandlePathProcessor),
+					)
+				})
+				.collect(),
+		};
+
+		serializer
+			.serialize(&file)
+			.map_err(|err| SerializeMaterialError::Serialize(Box::new(err)))
+	}
+}
+
+#[derive(Serialize)]
+struct SerializedGenericMaterial<'a> {
+	#[cfg(feature = "bevy_pbr")]
+	#[serde(rename = "type")]
+	ty: &'a str,
+	#[cfg(feature = "bevy_pbr")]
+	material: SerializedMaterialFields<'a>,
+	#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+	properties: BTreeMap<&'a str, TypedReflectSerializer<'a, HandlePathProcessor>>,
+}
+
+/// Serializes the fields of a struct material that differ from its default, or the whole material if it isn't a struct.
+///
+/// Structs with [foreign fields](crate::MaterializeAppExt::register_generic_material_foreign_field) nested in the material, such as the base material of an
+/// [`ExtendedMaterial`](bevy::pbr::ExtendedMaterial), are diffed the same way, as reflection can't compare their foreign fields.
+#[cfg(feature = "bevy_pbr")]
+#[derive(Clone, Copy)]
+struct SerializedMaterialFields<'a> {
+	material: &'a dyn PartialReflect,
+	default: Option<&'a dyn PartialReflect>,
+	registry: &'a TypeRegistry,
+	foreign_fields: &'a HashMap<(TypeId, String), ForeignField>,
+}
+#[cfg(feature = "bevy_pbr")]
+impl<'a> SerializedMaterialFields<'a> {
+	/// Returns the fields to write, or [`None`] if the material isn't a struct.
+	fn changed_fields(&self) -> Option<Vec<(&'a str, Box<dyn erased_serde::Serialize + 'a>)>> {
+		let ReflectRef::Struct(material) = self.material.reflect_ref() else { return None };
+		let default = self.default.and_then(|default| match default.reflect_ref() {
+			ReflectRef::Struct(default) => Some(default),
+			_ => None,
+		});
+
+		let mut fields: Vec<(&'a str, Box<dyn erased_serde::Serialize + 'a>)> = Vec::new();
+
+		// Foreign fields come first, as they're plain values, which some formats require before tables.
+		if let Some(material) = self.material.try_as_reflect() {
+			let material_type = Reflect::as_any(material).type_id();
+			let default = self.default.and_then(PartialReflect::try_as_reflect);
+
+			for ((type_id, field), foreign_field) in self.foreign_fields {
+				if *type_id != material_type {
+					continue;
+				}
+				if let Some(value) = (foreign_field.get)(material, default) {
+					fields.push((field, value));
+				}
+			}
+		}
+
+		for (name, value) in material.iter_fields() {
+			let default = default.and_then(|default| default.field(name));
+
+			let nested = Self {
+				material: value,
+				default,
+				..*self
+			};
+			if let Some(nested_fields) = nested.has_foreign_fields().then(|| nested.changed_fields()).flatten() {
+				if !nested_fields.is_empty() {
+					fields.push((name, Box::new(SerializedFields(nested_fields))));
+				}
+				continue;
+			}
+
+			if default.is_some_and(|default| default.reflect_partial_eq(value) == Some(true)) {
+				continue;
+			}
+
+			fields.push((
+				name,
+				Box::new(TypedReflectSerializer::with_processor(value, self.registry, &HandlePathProcessor)),
+			));
+		}
+
+		Some(fields)
+	}
+
+	/// Returns `true` if the material or a struct nested in it has foreign fields.
+	fn has_foreign_fields(&self) -> bool {
+		let Some(material) = self.material.try_as_reflect() else { return false };
+		let material_type = Reflect::as_any(material).type_id();
+		if self.foreign_fields.keys().any(|(type_id, _)| *type_id == material_type) {
+			return true;
+		}
+
+		match self.material.reflect_ref() {
+			ReflectRef::Struct(material) => material.iter_fields().any(|(_, value)| {
+				Self {
+					material: value,
+					default: None,
+					..*self
+				}
+				.has_foreign_fields()
+			}),
+			_ => false,
+		}
+	}
+}
+#[cfg(feature = "bevy_pbr")]
+impl Serialize for SerializedMaterialFields<'_> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match self.changed_fields() {
+			Some(fields) => SerializedFields(fields).serialize(serializer),
+			None => TypedReflectSerializer::with_processor(self.material, self.registry, &HandlePathProcessor).serialize(serializer),
+		}
+	}
+}
+
+/// Serializes a list of fields as a map.
+#[cfg(feature = "bevy_pbr")]
+struct SerializedFields<'a>(Vec<(&'a str, Box<dyn erased_serde::Serialize + 'a>)>);
+#[cfg(feature = "bevy_pbr")]
+impl Serialize for SerializedFields<'_> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut map = serializer.serialize_map(Some(self.0.len()))?;
+		for (name, value) in &self.0 {
+			map.serialize_entry(name, value.as_ref())?;
+		}
+		map.end()
+	}
+}
+
+/// Serializes handles as the paths the loader reads them from.
+struct HandlePathProcessor;
+impl ReflectSerializerProcessor for HandlePathProcessor {
+	fn try_serialize<S: Serializer>(&self, value: &dyn PartialReflect, registry: &TypeRegistry, serializer: S) -> Result<Result<S::Ok, S>, S::Error> {
+		let Some(value) = value.try_as_reflect() else { return Ok(Err(serializer)) };
+		let Some(reflect_handle) = registry.get_type_data::<ReflectHandle>(value.reflect_type_info().type_id()) else {
+			return Ok(Err(serializer));
+		};
+		let Some(handle) = reflect_handle.downcast_handle_untyped(value.as_any()) else { return Ok(Err(serializer)) };
+
+		let Some(path) = handle.path() else {
+			return Err(serde::ser::Error::custom(format!(
+				"{handle:?} has no path, so it can't be written to a material file"
+			)));
+		};
+
+		// A leading `/` makes the loader read the path from the root of the material's asset source,
+		// so handles from named sources keep their `source://` prefix to be read from the same source again.
+		let path = match path.source() {
+			AssetSourceId::Default => format!("/{path}"),
+			AssetSourceId::Name(source) => format!("{source}://{}", path.clone().with_source(AssetSourceId::Default)),
+		};
+
+		Ok(Ok(serializer.serialize_str(&path)?))
+	}
+}
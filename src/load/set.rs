@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::tasks::ConditionalSendFuture;
+use serde::Deserialize;
+
+use super::GenericMaterialLoadError;
+use super::asset::relative_asset_path;
+use super::deserializer::MaterialDeserializer;
+use crate::generic_material::GenericMaterial;
+
+/// A named group of [`GenericMaterial`]s loaded from a single file, such as a themed set of tiles.
+///
+/// # Examples
+/// ```toml
+/// # tiles.materialset.toml
+/// [materials]
+/// grass = "grass.toml"
+/// stone = "stone/stone.toml"
+/// ```
+#[derive(Asset, TypePath, Debug, Clone, Default)]
+pub struct GenericMaterialSet {
+	pub materials: HashMap<String, Handle<GenericMaterial>>,
+}
+impl GenericMaterialSet {
+	/// Returns the handle of the member material called `name`.
+	pub fn get(&self, name: &str) -> Option<&Handle<GenericMaterial>> {
+		self.materials.get(name)
+	}
+}
+
+/// Loads a [`GenericMaterialSet`], loading each member material with paths relative to the set's file.
+///
+/// Uses the [`SET_EXTENSIONS`](MaterialDeserializer::SET_EXTENSIONS) of `D`.
+#[derive(TypePath)]
+pub struct GenericMaterialSetLoader<D: MaterialDeserializer> {
+	pub deserializer: Arc<D>,
+}
+impl<D: MaterialDeserializer> AssetLoader for GenericMaterialSetLoader<D> {
+	type Asset = GenericMaterialSet;
+	type Settings = ();
+	type Error = GenericMaterialLoadError;
+
+	fn load(
+		&self,
+		reader: &mut dyn bevy::asset::io::Reader,
+		_settings: &Self::Settings,
+		load_context: &mut LoadContext,
+	) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+		Box::pin(async {
+			let mut input = Vec::new();
+			reader.read_to_end(&mut input).await?;
+
+			let parsed: ParsedGenericMaterialSet = self
+				.deserializer
+				.deserialize(&input)
+				.map_err(|err| GenericMaterialLoadError::Deserialize(Box::new(err)))?;
+
+			let mut materials = HashMap::with_capacity(parsed.materials.len());
+			for (name, path) in parsed.materials {
+				let path = relative_asset_path(load_context.path(), &path).map_err(io::Error::other)?;
+				materials.insert(name, load_context.load(path));
+			}
+
+			Ok(GenericMaterialSet { materials })
+		})
+	}
+
+	fn extensions(&self) -> &[&str] {
+		D::SET_EXTENSIONS
+	}
+}
+
+#[derive(Deserialize)]
+struct ParsedGenericMaterialSet {
+	#[serde(default)]
+	materials: BTreeMap<String, String>,
+}
@@ -40,6 +40,8 @@ impl AssetLoader for SimpleGenericMaterialLoader {
 				#[cfg(feature = "bevy_pbr")]
 				handle: material.add_labeled_asset(load_context, "Material".to_string()),
 				properties: (self.properties)(),
+				id: None,
+				computed: default(),
 			})
 		})
 	}
@@ -0,0 +1,41 @@
+use bevy::{
+	asset::io::AssetSourceId,
+	prelude::*,
+	reflect::{TypeRegistration, TypeRegistry},
+};
+
+use super::processor::{MaterialProcessor, MaterialProcessorContext};
+
+/// Material processor that only runs `processor` for materials loaded from the asset source `source`,
+/// such as for processing generated materials differently than hand-authored ones.
+///
+/// `processor` is a processor stack of its own, usually ending in `()`, which gets the deserializer after `child` gives it up.
+/// Added with [`MaterializePlugin::with_source_processor`](crate::MaterializePlugin::with_source_processor).
+///
+/// NOTE: The source is the one of the material being loaded, so `processor` also runs on everything that material inherits from in other sources.
+#[derive(TypePath, Clone)]
+pub struct SourceProcessor<P: MaterialProcessor, S: MaterialProcessor> {
+	pub child: P,
+	pub source: AssetSourceId<'static>,
+	pub processor: S,
+}
+impl<P: MaterialProcessor, S: MaterialProcessor> MaterialProcessor for SourceProcessor<P, S> {
+	type Child = P;
+	fn child(&self) -> Option<&Self::Child> {
+		Some(&self.child)
+	}
+
+	fn try_deserialize<'de, D: serde::Deserializer<'de>>(
+		&self,
+		ctx: &mut MaterialProcessorContext,
+		registration: &TypeRegistration,
+		registry: &TypeRegistry,
+		deserializer: D,
+	) -> Result<Result<Box<dyn PartialReflect>, D>, D::Error> {
+		if *ctx.load_context.path().source() != self.source {
+			return Ok(Err(deserializer));
+		}
+
+		self.processor.try_deserialize_recursive(ctx, registration, registry, deserializer)
+	}
+}
@@ -0,0 +1,839 @@
+//! Tests of loading, reloading, and serializing material files, all set up through [`Fixture`].
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bevy::asset::io::{
+	AssetSourceBuilder,
+	memory::{Dir, MemoryAssetReader},
+};
+
+use super::*;
+
+/// A headless app loading materials from `assets/`, and from the in-memory `memory://` asset source for files written by tests.
+///
+/// The `collision: bool` and `sounds: String` properties used by the fixtures in `assets/materials` are registered.
+struct Fixture {
+	app: App,
+	memory: Dir,
+}
+impl Fixture {
+	fn new<D: MaterialDeserializer, P: MaterialProcessor>(plugin: MaterializePlugin<D, P>) -> Self {
+		let memory = Dir::default();
+		let reader_memory = memory.clone();
+
+		let mut app = App::new();
+		app.register_asset_source(
+			"memory",
+			AssetSourceBuilder::new(move || Box::new(MemoryAssetReader { root: reader_memory.clone() })),
+		);
+		app.add_plugins((MinimalPlugins, AssetPlugin::default(), ImagePlugin::default(), plugin))
+			.init_asset::<StandardMaterial>()
+			.register_material_property_manual::<bool>("collision")
+			.register_material_property_manual::<String>("sounds");
+
+		Self { app, memory }
+	}
+
+	fn toml() -> Self {
+		Self::new(MaterializePlugin::new(TomlMaterialDeserializer))
+	}
+
+	/// Also registers [`TestExtendedMaterial`].
+	fn with_extended_material(mut self) -> Self {
+		self.app
+			.init_asset::<TestExtendedMaterial>()
+			.register_generic_material::<TestExtendedMaterial>()
+			.register_generic_material_shorthand::<TestExtendedMaterial>("TestExtendedMaterial");
+		self
+	}
+
+	/// Writes a file to `memory://path`.
+	fn write(&self, path: &str, contents: impl Into<Vec<u8>>) {
+		self.memory.insert_asset(Path::new(path), contents.into());
+	}
+
+	/// Copies a file from `assets/` to the same path in `memory://`.
+	fn copy_asset(&self, path: &str) {
+		self.write(path, std::fs::read(Path::new("assets").join(path)).unwrap());
+	}
+
+	fn load(&mut self, path: &str) -> Result<Handle<GenericMaterial>, LoadGenericMaterialBlockingError> {
+		load_generic_material_blocking(&mut self.app, path)
+	}
+
+	/// Updates the app until `condition` is met, for waiting on work done in the background such as reloads.
+	fn update_until(&mut self, mut condition: impl FnMut(&World) -> bool) {
+		for _ in 0..1000 {
+			self.app.update();
+			if condition(self.app.world()) {
+				return;
+			}
+			std::thread::sleep(std::time::Duration::from_millis(1));
+		}
+		panic!("condition wasn't met in time");
+	}
+
+	/// Returns the handle of an entity's [`StandardMaterial`].
+	fn mesh_material(&self, entity: Entity) -> Handle<StandardMaterial> {
+		self.app.world().get::<MeshMaterial3d<StandardMaterial>>(entity).unwrap().0.clone()
+	}
+
+	fn generic_material(&self, handle: &Handle<GenericMaterial>) -> &GenericMaterial {
+		self.app.world().resource::<Assets<GenericMaterial>>().get(handle).unwrap()
+	}
+
+	/// Returns the material of the generic material loaded from `path`.
+	fn material<M: Material>(&mut self, path: &str) -> M {
+		let handle = self.load(path).unwrap();
+		let world = self.app.world();
+
+		world
+			.resource::<Assets<M>>()
+			.get(&self.generic_material(&handle).handle.typed::<M>().unwrap())
+			.unwrap()
+			.clone()
+	}
+
+	/// Loads the generic material at `path` and writes it out as TOML.
+	fn serialize(&mut self, path: &str) -> Vec<u8> {
+		let handle = self.load(path).unwrap();
+		let world = self.app.world();
+
+		self.generic_material(&handle).serialize_with(&TomlMaterialDeserializer, world).unwrap()
+	}
+}
+
+/// Extension for testing [`ExtendedMaterial`](bevy::pbr::ExtendedMaterial)s, registered as `TestExtendedMaterial` by [`Fixture::with_extended_material`].
+#[derive(Asset, bevy::render::render_resource::AsBindGroup, Reflect, Debug, Clone, Default)]
+struct TestExtension {
+	#[uniform(100)]
+	strength: f32,
+}
+impl bevy::pbr::MaterialExtension for TestExtension {}
+type TestExtendedMaterial = bevy::pbr::ExtendedMaterial<StandardMaterial, TestExtension>;
+
+#[test]
+fn load_toml() {
+	let fixture = Fixture::toml();
+	let asset_server = fixture.app.world().resource::<AssetServer>();
+
+	smol::block_on(async {
+		#[cfg(feature = "animation")]
+		asset_server.load_builder().load_untyped_async("materials/animated.toml").await.unwrap();
+		// Custom materials require special scaffolding in the associated example, and so the test is there.
+		for path in [
+			"materials/example.material.toml",
+			"materials/sub-material.toml",
+			"materials/local-inheritance.toml",
+			"materials/defs.toml",
+			"materials/tiles.materialset.toml",
+			"materials/tiles.materiallib.toml",
+			crate::embedded::EmbeddedMaterialsPlugin::MISSING,
+		] {
+			asset_server.load_builder().load_untyped_async(path).await.unwrap();
+		}
+	});
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn load_json() {
+	let mut fixture = Fixture::new(MaterializePlugin::new(JsonMaterialDeserializer));
+
+	let handle = fixture.load("materials/example.material.json").unwrap();
+	assert!(fixture.app.world().resource::<Assets<GenericMaterial>>().contains(&handle));
+}
+
+#[test]
+fn reproducible_serialization() {
+	// Materials are read from memory, so the serialized file can be loaded again from the same asset source.
+	let mut fixture = Fixture::toml();
+	fixture.copy_asset("materials/example.material.toml");
+	fixture.copy_asset("materials/example.png");
+
+	let serialized = fixture.serialize("memory://materials/example.material.toml");
+	fixture.write("materials/reserialized.toml", serialized.clone());
+
+	assert_eq!(
+		String::from_utf8(fixture.serialize("memory://materials/reserialized.toml")).unwrap(),
+		String::from_utf8(serialized).unwrap(),
+	);
+}
+
+#[test]
+fn serialize_named_source_handles() {
+	let mut fixture = Fixture::toml();
+	fixture.write("textures/example.png", std::fs::read("assets/materials/example.png").unwrap());
+
+	let texture = |fixture: &mut Fixture, path: &str| fixture.material::<StandardMaterial>(path).base_color_texture.unwrap().path().cloned();
+
+	let serialized = fixture.serialize("materials/named-source-texture.toml");
+	assert_eq!(
+		texture(&mut fixture, "materials/named-source-texture.toml"),
+		Some(AssetPath::from("memory://textures/example.png"))
+	);
+	assert!(str::from_utf8(&serialized).unwrap().contains("\"memory://textures/example.png\""));
+
+	// Loaded from the named source itself, the texture is still read from there.
+	fixture.write("materials/reserialized.toml", serialized.clone());
+	assert_eq!(fixture.serialize("memory://materials/reserialized.toml"), serialized);
+	assert_eq!(
+		texture(&mut fixture, "memory://materials/reserialized.toml"),
+		Some(AssetPath::from("memory://textures/example.png"))
+	);
+}
+
+#[test]
+fn unknown_field_policy() {
+	for policy in [UnknownFieldPolicy::Error, UnknownFieldPolicy::WarnAndSkip, UnknownFieldPolicy::Silent] {
+		let mut fixture = Fixture::new(MaterializePlugin::new(TomlMaterialDeserializer).with_source_settings(
+			AssetSourceId::Default,
+			SourceLoaderSettings {
+				unknown_fields: policy,
+				..default()
+			},
+		));
+
+		if policy == UnknownFieldPolicy::Error {
+			assert!(fixture.load("materials/unknown-field.toml").is_err());
+			continue;
+		}
+
+		let material = fixture.material::<StandardMaterial>("materials/unknown-field.toml");
+		assert_eq!(material.metallic, 0.5, "{policy:?}");
+	}
+}
+
+#[test]
+fn missing_parent_policy() {
+	for policy in [MissingParent::Error, MissingParent::WarnAndSkip] {
+		let mut fixture = Fixture::new(MaterializePlugin::new(TomlMaterialDeserializer).with_source_settings(
+			AssetSourceId::Default,
+			SourceLoaderSettings {
+				missing_parent: policy,
+				..default()
+			},
+		));
+
+		if policy == MissingParent::Error {
+			let err = fixture.load("materials/missing-parent.toml").unwrap_err();
+			assert!(err.to_string().contains("does-not-exist.toml"), "{err}");
+			continue;
+		}
+
+		// The missing file is skipped, and the material loads with its own fields and those of the parent that exists.
+		let material = fixture.material::<StandardMaterial>("materials/missing-parent.toml");
+		assert_eq!(material.metallic, 0.4);
+		assert_eq!(material.perceptual_roughness, 0.3);
+	}
+}
+
+#[test]
+fn zero_apply_budget() {
+	let mut fixture = Fixture::toml();
+	fixture.app.insert_resource(crate::GenericMaterialApplyBudget {
+		max_entities: None,
+		max_time: Some(std::time::Duration::ZERO),
+	});
+	let handle = fixture.load("materials/example.material.toml").unwrap();
+	let app = &mut fixture.app;
+	let entities = [(); 3].map(|_| app.world_mut().spawn(GenericMaterial3d(handle.clone())).id());
+
+	// Even with no time to spare, at least one entity is applied each frame.
+	for frame in 1..=entities.len() {
+		app.update();
+		let applied = entities
+			.iter()
+			.filter(|&&entity| app.world().entity(entity).contains::<crate::generic_material::GenericMaterialApplied>())
+			.count();
+		assert!(applied >= frame, "only {applied} entities applied after {frame} frames");
+	}
+}
+
+#[test]
+fn duplicate_material_ids() {
+	use crate::generic_material::GenericMaterialIds;
+
+	let mut fixture = Fixture::toml();
+	let a = fixture.load("materials/duplicate-id-a.toml").unwrap();
+	let b = fixture.load("materials/duplicate-id-b.toml").unwrap();
+	let app = &mut fixture.app;
+	app.update();
+
+	let indexed = app.world().resource::<GenericMaterialIds>().get("test.duplicate").unwrap();
+	assert!(indexed == a.id() || indexed == b.id());
+	let remaining = if indexed == a.id() { b.id() } else { a.id() };
+
+	// Removal events are sent at the end of the frame, and handled at the start of the next.
+	app.world_mut().resource_mut::<Assets<GenericMaterial>>().remove(indexed);
+	app.update();
+	app.update();
+
+	assert_eq!(app.world().resource::<GenericMaterialIds>().get("test.duplicate"), Some(remaining));
+}
+
+#[test]
+fn platform_overrides() {
+	// Written at runtime, since which platforms apply depends on where the tests run.
+	let [platform, os] = current_platforms()[..] else { panic!("expected a platform and an operating system") };
+	let mut fixture = Fixture::toml();
+	fixture.write(
+		"base.toml",
+		format!(
+			"type = \"StandardMaterial\"\n\
+			[material]\nmetallic = 0.1\nperceptual_roughness = 0.1\nreflectance = 0.1\n\
+			[material.overrides.{platform}]\nmetallic = 0.5\nperceptual_roughness = 0.5\n\
+			[material.overrides.{os}]\nperceptual_roughness = 0.7\n\
+			[material.overrides.not-a-platform]\nreflectance = 0.9\n"
+		),
+	);
+	fixture.write(
+		"child.toml",
+		format!("inherits = \"base.toml\"\n[material.overrides.{platform}]\nmetallic = 0.8\n"),
+	);
+
+	let material = fixture.material::<StandardMaterial>("memory://base.toml");
+	assert_eq!(material.metallic, 0.5);
+	// Operating system overrides take precedence over the more general platform.
+	assert_eq!(material.perceptual_roughness, 0.7);
+	assert_eq!(material.reflectance, 0.1);
+
+	// Overrides are merged through inheritance, like the rest of the material.
+	let material = fixture.material::<StandardMaterial>("memory://child.toml");
+	assert_eq!(material.metallic, 0.8);
+	assert_eq!(material.perceptual_roughness, 0.7);
+}
+
+#[test]
+fn foreign_fields() {
+	use bevy::render::render_resource::Face;
+
+	let mut fixture = Fixture::toml().with_extended_material();
+
+	let material = fixture.material::<StandardMaterial>("materials/cull-mode.toml");
+	assert_eq!(material.cull_mode, None);
+
+	let material = fixture.material::<TestExtendedMaterial>("materials/extended-cull-mode.toml");
+	assert_eq!(material.base.cull_mode, Some(Face::Front));
+	assert_eq!(material.extension.strength, 0.5);
+}
+
+#[test]
+fn serialize_foreign_fields() {
+	let mut fixture = Fixture::toml().with_extended_material();
+	let mut serialize = |path: &str| -> toml::Value { toml::from_str(str::from_utf8(&fixture.serialize(path)).unwrap()).unwrap() };
+
+	let file = serialize("materials/cull-mode.toml");
+	assert_eq!(file["material"]["cull_mode"].as_str(), Some("None"));
+
+	let file = serialize("materials/extended-cull-mode.toml");
+	assert_eq!(file["material"]["base"]["cull_mode"].as_str(), Some("Front"));
+	assert_eq!(file["material"]["extension"]["strength"].as_float(), Some(0.5));
+	// Unchanged fields of the base material aren't written.
+	assert!(file["material"]["base"].get("base_color").is_none());
+}
+
+#[test]
+fn flatten_extended_materials() {
+	use bevy::reflect::Typed;
+	use flatten::{FlattenExtendedMaterialProcessor, is_extended_material};
+
+	assert!(is_extended_material(TestExtendedMaterial::type_info().type_path_table()));
+	assert!(!is_extended_material(StandardMaterial::type_info().type_path_table()));
+
+	let mut fixture =
+		Fixture::new(MaterializePlugin::new(TomlMaterialDeserializer).with_processor(FlattenExtendedMaterialProcessor)).with_extended_material();
+
+	// Fields only the extension has go to the extension, everything else, including foreign fields, goes to the base material.
+	let material = fixture.material::<TestExtendedMaterial>("materials/flattened-extended.toml");
+	assert_eq!(material.base.metallic, 0.25);
+	assert_eq!(material.base.cull_mode, None);
+	assert_eq!(material.extension.strength, 0.5);
+
+	// Nested materials still work.
+	let material = fixture.material::<TestExtendedMaterial>("materials/extended-cull-mode.toml");
+	assert_eq!(material.base.cull_mode, Some(bevy::render::render_resource::Face::Front));
+	assert_eq!(material.extension.strength, 0.5);
+
+	assert!(fixture.load("materials/flattened-and-nested-base.toml").is_err());
+
+	// Without the processor, the base material's fields must be nested.
+	let mut fixture = Fixture::toml().with_extended_material();
+	assert!(fixture.load("materials/flattened-extended.toml").is_err());
+}
+
+#[test]
+fn text_replacements() {
+	let mut fixture = Fixture::toml();
+	let replacements = fixture.app.world().resource::<TextReplacementProcessor>();
+	replacements.set_variable("quality.metallic", "0.5");
+	replacements.register("loading", |ctx| Some(ctx.load_context.path().to_string()));
+
+	let handle = fixture.load("materials/replacements.toml").unwrap();
+	let platform = current_platforms()[0];
+	assert_eq!(
+		fixture.generic_material(&handle).get_property_manual::<String>("sounds").unwrap(),
+		&format!("replacements/materials/{platform}/materials/replacements.toml"),
+	);
+
+	assert_eq!(fixture.material::<StandardMaterial>("materials/replacements.toml").metallic, 0.5);
+}
+
+#[cfg(feature = "animation")]
+#[test]
+fn missing_next_material() {
+	let mut fixture = Fixture::toml();
+
+	let err = fixture.load("materials/missing-next.toml").unwrap_err();
+	assert!(err.to_string().contains("does-not-exist.toml"), "{err}");
+	// Variants are checked too.
+	let err = fixture.load("materials/missing-next-variant.toml").unwrap_err();
+	assert!(err.to_string().contains("does-not-exist.toml"), "{err}");
+
+	assert!(fixture.load("materials/animated.toml").is_ok());
+}
+
+#[test]
+fn computed_properties_are_cleared_on_edit() {
+	use bevy::ecs::system::SystemState;
+	use std::cell::Cell;
+
+	let mut fixture = Fixture::toml();
+	let handle = fixture.load("materials/example.material.toml").unwrap();
+	let app = &mut fixture.app;
+
+	let computations = Cell::new(0);
+	let loud_sounds = |generic_material: &GenericMaterial| {
+		generic_material
+			.get_or_compute_property_manual("loud_sounds", |material: &GenericMaterial| {
+				computations.set(computations.get() + 1);
+				material.get_property_manual::<String>("sounds").unwrap().to_uppercase()
+			})
+			.into_owned()
+	};
+
+	{
+		let mut generic_materials = app.world_mut().resource_mut::<Assets<GenericMaterial>>();
+		let mut generic_material = generic_materials.get_mut(&handle).unwrap();
+		assert_eq!(loud_sounds(&generic_material), "WOOD");
+		assert_eq!(loud_sounds(&generic_material), "WOOD");
+		assert_eq!(computations.get(), 1);
+
+		// Setting a property clears the values computed from it.
+		generic_material.set_property_manual("sounds", "stone".to_string());
+		assert_eq!(loud_sounds(&generic_material), "STONE");
+		assert_eq!(computations.get(), 2);
+
+		// Editing the map directly doesn't, until the cache is cleared explicitly.
+		*generic_material.properties.get_mut("sounds").unwrap() = Box::new("grass".to_string());
+		assert_eq!(loud_sounds(&generic_material), "STONE");
+		generic_material.clear_computed_properties();
+		assert_eq!(loud_sounds(&generic_material), "GRASS");
+		assert_eq!(computations.get(), 3);
+	}
+
+	// So does getting the material through `GenericMaterialsMut`, as computed values can be derived from more than properties.
+	let mut state = SystemState::<GenericMaterialsMut>::new(app.world_mut());
+	let mut generic_materials = state.get_mut(app.world_mut());
+	let generic_material = generic_materials.get_mut(&handle).unwrap();
+	assert_eq!(loud_sounds(&generic_material), "GRASS");
+	assert_eq!(computations.get(), 4);
+}
+
+#[test]
+fn load_fallback_keeps_handle() {
+	let mut fixture = Fixture::toml();
+	let app = &mut fixture.app;
+
+	let fallback_material = app
+		.world_mut()
+		.resource_mut::<Assets<StandardMaterial>>()
+		.add(StandardMaterial::default());
+	let fallback = app
+		.world_mut()
+		.resource_mut::<Assets<GenericMaterial>>()
+		.add(GenericMaterial::new(fallback_material.clone()));
+	app.insert_resource(crate::GenericMaterialLoadFallback(fallback));
+
+	let broken = app
+		.world()
+		.resource::<AssetServer>()
+		.load::<GenericMaterial>("materials/does-not-exist.toml");
+	let entity = app.world_mut().spawn(GenericMaterial3d(broken.clone())).id();
+	assert!(fixture.load("materials/does-not-exist.toml").is_err());
+	fixture.app.update();
+
+	let entity = fixture.app.world().entity(entity);
+	assert_eq!(entity.get::<GenericMaterial3d>().unwrap().0, broken);
+	assert_eq!(entity.get::<MeshMaterial3d<StandardMaterial>>().unwrap().0, fallback_material);
+}
+
+#[test]
+fn inheritance_in_library() {
+	let mut fixture = Fixture::toml();
+
+	let example = fixture.material::<StandardMaterial>("materials/tiles.materiallib.toml#example");
+	assert_eq!(example.perceptual_roughness, 0.9);
+	assert!(example.base_color_texture.is_some());
+
+	// Inherits from `example`, which itself inherits from `base`.
+	let glossy = fixture.material::<StandardMaterial>("materials/tiles.materiallib.toml#glossy");
+	assert_eq!(glossy.perceptual_roughness, 0.2);
+	assert!(glossy.base_color_texture.is_some());
+}
+
+#[test]
+fn default_inherits() {
+	static PARSED: AtomicUsize = AtomicUsize::new(0);
+
+	let mut fixture = Fixture::new(
+		MaterializePlugin::new(TomlMaterialDeserializer)
+			.with_default_inherits("materials/default-base.toml")
+			.with_value_transform(|_| {
+				PARSED.fetch_add(1, Ordering::Relaxed);
+			}),
+	);
+
+	let handle = fixture.load("materials/cull-mode.toml").unwrap();
+	assert_eq!(
+		fixture.generic_material(&handle).get_property_manual::<bool>("collision").ok(),
+		Some(&true)
+	);
+	assert_eq!(fixture.material::<StandardMaterial>("materials/cull-mode.toml").perceptual_roughness, 0.7);
+	assert_eq!(PARSED.load(Ordering::Relaxed), 2);
+
+	// The default material is only parsed once.
+	let material = fixture.material::<StandardMaterial>("materials/example.material.toml");
+	assert_eq!(material.perceptual_roughness, 0.7);
+	assert_eq!(PARSED.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn inherited_files_are_read_once() {
+	static PARSED: AtomicUsize = AtomicUsize::new(0);
+
+	let mut fixture = Fixture::new(MaterializePlugin::new(TomlMaterialDeserializer).with_value_transform(|_| {
+		PARSED.fetch_add(1, Ordering::Relaxed);
+	}));
+
+	let material = fixture.material::<StandardMaterial>("materials/diamond.toml");
+	assert_eq!(material.perceptual_roughness, 0.3);
+	assert_eq!(material.metallic, 0.5);
+	assert_eq!(material.reflectance, 0.2);
+	// `diamond-base.toml` is inherited from through both sides, but only parsed once.
+	assert_eq!(PARSED.load(Ordering::Relaxed), 4);
+}
+
+#[test]
+fn channel_swizzle_labels() {
+	let material = Fixture::toml().material::<StandardMaterial>("materials/channels.toml");
+
+	let metallic_roughness = material.metallic_roughness_texture.unwrap();
+	// Rearranged the same way with the same sampler, so it's only added once.
+	assert_eq!(material.emissive_texture.unwrap(), metallic_roughness);
+	// A different sampler needs its own copy.
+	assert_ne!(material.occlusion_texture.unwrap(), metallic_roughness);
+}
+
+#[test]
+fn settings_replacements() {
+	let mut fixture = Fixture::toml();
+	let asset_server = fixture.app.world().resource::<AssetServer>().clone();
+	let load_with_skin = |path: &'static str, skin: &'static str| {
+		asset_server
+			.load_builder()
+			.with_settings(move |settings: &mut GenericMaterialLoaderSettings| {
+				settings.replacements.insert("skin".to_string(), skin.to_string());
+			})
+			.load::<GenericMaterial>(path)
+	};
+
+	let red = load_with_skin("materials/skin.toml", "red");
+	// Settings aren't part of the asset's identity, so this is the same asset.
+	assert_eq!(load_with_skin("materials/skin.toml", "blue"), red);
+	// But a file inheriting from it is a different asset, with its own settings.
+	let blue = load_with_skin("materials/skin-variant.toml", "blue");
+
+	fixture.load("materials/skin.toml").unwrap();
+	fixture.load("materials/skin-variant.toml").unwrap();
+
+	let sounds = |handle: &Handle<GenericMaterial>| fixture.generic_material(handle).get_property_manual::<String>("sounds").unwrap().clone();
+	assert_eq!(sounds(&red), "red");
+	assert_eq!(sounds(&blue), "blue");
+}
+
+/// Doubles every `f32` it deserializes.
+#[derive(TypePath, Clone)]
+struct DoubleFloatProcessor<P: MaterialProcessor>(P);
+impl<P: MaterialProcessor> MaterialProcessor for DoubleFloatProcessor<P> {
+	type Child = P;
+	fn child(&self) -> Option<&Self::Child> {
+		Some(&self.0)
+	}
+
+	fn try_deserialize<'de, D: serde::Deserializer<'de>>(
+		&self,
+		_ctx: &mut MaterialProcessorContext,
+		registration: &TypeRegistration,
+		_registry: &TypeRegistry,
+		deserializer: D,
+	) -> Result<Result<Box<dyn PartialReflect>, D>, D::Error> {
+		if registration.type_id() != std::any::TypeId::of::<f32>() {
+			return Ok(Err(deserializer));
+		}
+
+		let value = f32::deserialize(deserializer)?;
+		Ok(Ok(Box::new(value * 2.)))
+	}
+}
+
+#[test]
+fn source_processors() {
+	let mut fixture = Fixture::new(MaterializePlugin::new(TomlMaterialDeserializer).with_source_processor("memory", DoubleFloatProcessor(())));
+	fixture.copy_asset("materials/diamond-base.toml");
+
+	assert_eq!(
+		fixture
+			.material::<StandardMaterial>("memory://materials/diamond-base.toml")
+			.perceptual_roughness,
+		0.6
+	);
+	assert_eq!(
+		fixture.material::<StandardMaterial>("materials/diamond-base.toml").perceptual_roughness,
+		0.3
+	);
+}
+
+/// Uppercases the strings of the `loud` key of the `sound_map` property.
+#[derive(TypePath, Clone)]
+struct LoudSoundProcessor<P: MaterialProcessor>(P);
+impl<P: MaterialProcessor> MaterialProcessor for LoudSoundProcessor<P> {
+	type Child = P;
+	fn child(&self) -> Option<&Self::Child> {
+		Some(&self.0)
+	}
+
+	fn try_deserialize<'de, D: serde::Deserializer<'de>>(
+		&self,
+		ctx: &mut MaterialProcessorContext,
+		registration: &TypeRegistration,
+		_registry: &TypeRegistry,
+		deserializer: D,
+	) -> Result<Result<Box<dyn PartialReflect>, D>, D::Error> {
+		if registration.type_id() != std::any::TypeId::of::<String>() || ctx.property.as_deref() != Some("sound_map") || ctx.property_keys != ["loud"]
+		{
+			return Ok(Err(deserializer));
+		}
+
+		let value = String::deserialize(deserializer)?;
+		Ok(Ok(Box::new(value.to_uppercase())))
+	}
+}
+
+#[test]
+fn property_keys() {
+	let mut fixture = Fixture::new(MaterializePlugin::new(TomlMaterialDeserializer).with_processor(LoudSoundProcessor));
+	fixture.app.register_material_property_manual::<HashMap<String, String>>("sound_map");
+
+	let handle = fixture.load("materials/property-keys.toml").unwrap();
+	let sound_map = fixture
+		.generic_material(&handle)
+		.get_property_manual::<HashMap<String, String>>("sound_map")
+		.unwrap();
+
+	assert_eq!(sound_map["loud"], "HIT");
+	assert_eq!(sound_map["quiet"], "step");
+}
+
+#[test]
+fn reload_keeps_handles() {
+	use bevy::ecs::message::Messages;
+
+	let mut fixture = Fixture::toml();
+	let material_file = |metallic: f32| format!("type = \"StandardMaterial\"\n[material]\nmetallic = {metallic:?}\n");
+	fixture.write("reloaded.toml", material_file(0.1));
+
+	let handle = fixture.load("memory://reloaded.toml").unwrap();
+	let entity = fixture.app.world_mut().spawn(GenericMaterial3d(handle.clone())).id();
+	fixture.app.update();
+	let material = fixture
+		.app
+		.world()
+		.entity(entity)
+		.get::<MeshMaterial3d<StandardMaterial>>()
+		.unwrap()
+		.0
+		.clone();
+
+	// Messages are only kept for two frames, so they're counted every frame.
+	let mut cursor = fixture
+		.app
+		.world()
+		.resource::<Messages<AssetEvent<GenericMaterial>>>()
+		.get_cursor_current();
+	let mut reloads = 0;
+	let mut count_reloads = |world: &World| {
+		reloads += cursor
+			.read(world.resource::<Messages<AssetEvent<GenericMaterial>>>())
+			.filter(|event| **event == AssetEvent::Modified { id: handle.id() })
+			.count();
+	};
+
+	fixture.write("reloaded.toml", material_file(0.9));
+	fixture.app.world().resource::<AssetServer>().reload("memory://reloaded.toml");
+	fixture.update_until(|world| {
+		count_reloads(world);
+		world.resource::<Assets<StandardMaterial>>().get(&material).unwrap().metallic == 0.9
+	});
+	// Give any extra events a chance to be sent.
+	for _ in 0..2 {
+		fixture.app.update();
+		count_reloads(fixture.app.world());
+	}
+	assert_eq!(reloads, 1);
+
+	let world = fixture.app.world();
+	assert_eq!(world.entity(entity).get::<GenericMaterial3d>().unwrap().0, handle);
+	assert_eq!(world.entity(entity).get::<MeshMaterial3d<StandardMaterial>>().unwrap().0, material);
+	assert_eq!(fixture.generic_material(&handle).handle.typed::<StandardMaterial>().unwrap(), material);
+}
+
+#[test]
+fn highlight_unique_material() {
+	use crate::{highlight::HighlightCommandsExt, material_overrides::UniqueGenericMaterial};
+
+	let mut fixture = Fixture::toml();
+	let handle = fixture.load("materials/example.material.toml").unwrap();
+	let entity = fixture
+		.app
+		.world_mut()
+		.spawn((GenericMaterial3d(handle.clone()), UniqueGenericMaterial))
+		.id();
+	fixture.app.update();
+	let unique = fixture.mesh_material(entity);
+	assert_ne!(Some(unique.clone()), fixture.generic_material(&handle).handle.typed());
+
+	fixture.app.world_mut().commands().highlight_generic_material(entity, Color::WHITE);
+	fixture.app.world_mut().flush();
+	fixture.app.update();
+	let highlighted = fixture.mesh_material(entity);
+	assert_ne!(highlighted, unique);
+	let materials = fixture.app.world().resource::<Assets<StandardMaterial>>();
+	assert_eq!(materials.get(&highlighted).unwrap().emissive, LinearRgba::WHITE);
+
+	// The entity gets its own material back, not the one shared by the generic material.
+	fixture.app.world_mut().commands().clear_generic_material_highlight(entity);
+	fixture.app.world_mut().flush();
+	fixture.app.update();
+	assert_eq!(fixture.mesh_material(entity), unique);
+}
+
+#[test]
+fn highlight_reload() {
+	use crate::highlight::{GenericMaterialHighlight, HighlightCommandsExt};
+
+	let mut fixture = Fixture::toml();
+	let material_file = |metallic: f32| format!("type = \"StandardMaterial\"\n[material]\nmetallic = {metallic:?}\n");
+	fixture.write("highlighted.toml", material_file(0.1));
+
+	let handle = fixture.load("memory://highlighted.toml").unwrap();
+	let entity = fixture.app.world_mut().spawn(GenericMaterial3d(handle.clone())).id();
+	fixture.app.update();
+	let original = fixture.mesh_material(entity);
+
+	fixture.app.world_mut().commands().highlight_generic_material(entity, Color::WHITE);
+	fixture.app.world_mut().flush();
+	fixture.app.update();
+
+	fixture.write("highlighted.toml", material_file(0.9));
+	fixture.app.world().resource::<AssetServer>().reload("memory://highlighted.toml");
+	fixture.update_until(|world| world.resource::<Assets<StandardMaterial>>().get(&original).unwrap().metallic == 0.9);
+	fixture.app.update();
+
+	// The reloaded material is highlighted in turn.
+	let highlighted = fixture.mesh_material(entity);
+	assert_ne!(highlighted, original);
+	let material = fixture.app.world().resource::<Assets<StandardMaterial>>().get(&highlighted).unwrap();
+	assert_eq!(material.metallic, 0.9);
+	assert_eq!(material.emissive, LinearRgba::WHITE);
+	let highlight = fixture.app.world().get::<GenericMaterialHighlight>(entity).unwrap();
+	assert_eq!(highlight.original.typed(), Some(original.clone()));
+
+	fixture.app.world_mut().commands().clear_generic_material_highlight(entity);
+	fixture.app.world_mut().flush();
+	fixture.app.update();
+	assert_eq!(fixture.mesh_material(entity), original);
+}
+
+#[cfg(feature = "animation")]
+#[test]
+fn animation_state_survives_late_next_material() {
+	use crate::animation::AnimatedGenericMaterials;
+
+	let mut fixture = Fixture::toml();
+	fixture.write("next.toml", "type = \"StandardMaterial\"\n");
+	fixture.write(
+		"animated.toml",
+		"type = \"StandardMaterial\"\n[properties.animation]\nnext.seconds = 100\nnext.material = \"next.toml\"\n",
+	);
+
+	let handle = fixture.load("memory://animated.toml").unwrap();
+	let next = fixture.load("memory://next.toml").unwrap();
+	let next_frame_time = |world: &World| {
+		let states = &world.resource::<AnimatedGenericMaterials>().states;
+		states
+			.get(&handle.id())
+			.map(|animations| animations.next.as_ref().unwrap().state.next_frame_time)
+	};
+	fixture.update_until(|world| next_frame_time(world).is_some());
+	let started = next_frame_time(fixture.app.world());
+
+	// The material is modified while its next material is missing, so it can't be set up again until that's back.
+	let mut generic_materials = fixture.app.world_mut().resource_mut::<Assets<GenericMaterial>>();
+	let next_material = generic_materials.remove(&next).unwrap();
+	generic_materials.get_mut(&handle).unwrap().set_property_manual("collision", true);
+	for _ in 0..2 {
+		std::thread::sleep(std::time::Duration::from_millis(10));
+		fixture.app.update();
+		assert_eq!(next_frame_time(fixture.app.world()), None);
+	}
+
+	fixture
+		.app
+		.world_mut()
+		.resource_mut::<Assets<GenericMaterial>>()
+		.insert(&next, next_material)
+		.unwrap();
+	fixture.app.update();
+
+	// The animation didn't change, so it keeps its timer rather than restarting.
+	assert_eq!(next_frame_time(fixture.app.world()), started);
+}
+
+#[cfg(feature = "animation")]
+#[test]
+fn transition_blend_for_new_entities() {
+	let mut fixture = Fixture::toml();
+	fixture.write("next.toml", "type = \"StandardMaterial\"\n[material]\nmetallic = 1.0\n");
+	fixture.write(
+		"fading.toml",
+		"type = \"StandardMaterial\"\n[material]\nmetallic = 0.0\n\
+		[properties.animation]\nnext.seconds = 0.01\nnext.material = \"next.toml\"\nnext.transition_seconds = 100\n",
+	);
+
+	let handle = fixture.load("memory://fading.toml").unwrap();
+	let material = fixture.generic_material(&handle).handle.typed::<StandardMaterial>().unwrap();
+	let first = fixture.app.world_mut().spawn(GenericMaterial3d(handle.clone())).id();
+	fixture.update_until(|world| {
+		world
+			.get::<MeshMaterial3d<StandardMaterial>>(first)
+			.is_some_and(|mesh_material| mesh_material.0 != material)
+	});
+	let blend = fixture.mesh_material(first);
+
+	// Given the material mid-transition, so it gets the blend the first entity already has.
+	let second = fixture.app.world_mut().spawn(GenericMaterial3d(handle.clone())).id();
+	fixture.app.update();
+	assert_eq!(fixture.mesh_material(second), blend);
+}
@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use bevy::asset::{AssetLoader, LoadContext, RenderAssetUsages};
+use bevy::platform::collections::HashMap;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::tasks::ConditionalSendFuture;
+use bevy::{image::ImageSampler, prelude::*};
+
+use super::WadLoadError;
+use super::library::GenericMaterialLibrary;
+use crate::erased_material::ErasedMaterial;
+use crate::generic_material::GenericMaterial;
+
+/// A 256 color palette, used for the textures of Quake's WAD2 archives, which don't contain their own palettes.
+pub type WadPalette = [[u8; 3]; 256];
+
+/// Loads Quake (WAD2) and Half-Life (WAD3) texture archives as a [`GenericMaterialLibrary`],
+/// with a [`GenericMaterial`] labeled with the name of each texture, like [`SimpleGenericMaterialLoader`](super::simple::SimpleGenericMaterialLoader) would for an image.
+///
+/// This lets `GenericMaterial3d(asset_server.load("textures.wad#CRATE01"))` just work.
+///
+/// Textures whose names start with `{` are treated as masked, with the last color of the palette being transparent.
+/// Only the full resolution of each texture is read, mipmaps are left to the renderer.
+#[derive(TypePath, Debug, Clone)]
+pub struct WadLoader {
+	/// A function that provides the underlying material given the loaded image, and whether the texture is masked.
+	/// Default is a [`StandardMaterial`] with `perceptual_roughness` set to 1, using [`AlphaMode::Mask`] for masked textures.
+	pub material: fn(Handle<Image>, bool) -> Box<dyn ErasedMaterial>,
+	pub properties: fn() -> HashMap<String, Box<dyn Reflect>>,
+	/// The palette used for WAD2 textures, usually read from Quake's `gfx/palette.lmp` with [`WadLoader::with_palette_lmp`].
+	/// WAD2 archives can't be loaded without one. (Default: [`None`])
+	pub palette: Option<Arc<WadPalette>>,
+}
+impl WadLoader {
+	/// Sets the palette used for WAD2 textures from the contents of a `palette.lmp` file, which is 256 RGB colors.
+	pub fn with_palette_lmp(self, lmp: &[u8]) -> Result<Self, WadLoadError> {
+		let Some(colors) = lmp.get(..256 * 3) else { return Err(WadLoadError::InvalidPalette) };
+
+		let mut palette = [[0; 3]; 256];
+		for (color, rgb) in palette.iter_mut().zip(colors.chunks_exact(3)) {
+			color.copy_from_slice(rgb);
+		}
+
+		Ok(Self {
+			palette: Some(Arc::new(palette)),
+			..self
+		})
+	}
+}
+impl Default for WadLoader {
+	fn default() -> Self {
+		Self {
+			material: |image, masked| {
+				StandardMaterial {
+					base_color_texture: Some(image),
+					perceptual_roughness: 1.,
+					alpha_mode: if masked { AlphaMode::Mask(0.5) } else { AlphaMode::Opaque },
+					..default()
+				}
+				.into()
+			},
+			properties: HashMap::default,
+			palette: None,
+		}
+	}
+}
+impl AssetLoader for WadLoader {
+	type Asset = GenericMaterialLibrary;
+	type Settings = ();
+	type Error = WadLoadError;
+
+	fn load(
+		&self,
+		reader: &mut dyn bevy::asset::io::Reader,
+		_settings: &Self::Settings,
+		load_context: &mut LoadContext,
+	) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+		Box::pin(async {
+			let mut bytes = Vec::new();
+			reader.read_to_end(&mut bytes).await?;
+
+			let mut materials = HashMap::default();
+			for texture in read_wad(&bytes, self.palette.as_deref())? {
+				let mut image = Image::new(
+					Extent3d {
+						width: texture.width,
+						height: texture.height,
+						depth_or_array_layers: 1,
+					},
+					TextureDimension::D2,
+					texture.data,
+					TextureFormat::Rgba8UnormSrgb,
+					RenderAssetUsages::default(),
+				);
+				// These textures are usually pixel art.
+				image.sampler = ImageSampler::nearest();
+
+				let image = load_context.add_labeled_asset(format!("{}/Image", texture.name), image);
+				let material = (self.material)(image, texture.masked);
+
+				let generic_material = GenericMaterial {
+					handle: material.add_labeled_asset(load_context, format!("{}/Material", texture.name)),
+					properties: (self.properties)(),
+					id: None,
+					computed: default(),
+				};
+
+				let handle = load_context.add_labeled_asset(texture.name.clone(), generic_material);
+				materials.insert(texture.name, handle);
+			}
+
+			Ok(GenericMaterialLibrary { materials })
+		})
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["wad"]
+	}
+}
+
+/// A texture read from a WAD archive, converted to RGBA.
+struct WadTexture {
+	name: String,
+	width: u32,
+	height: u32,
+	masked: bool,
+	data: Vec<u8>,
+}
+
+/// Reads every mip texture of a WAD2 or WAD3 archive. Other lumps, such as status bar pictures and fonts, are skipped.
+///
+/// Every offset and size comes from the file, so they're all checked, failing with [`WadLoadError::UnexpectedEnd`] rather than overflowing or over-allocating.
+fn read_wad(bytes: &[u8], palette: Option<&WadPalette>) -> Result<Vec<WadTexture>, WadLoadError> {
+	const WAD2_MIPTEX: u8 = 0x44;
+	const WAD3_MIPTEX: u8 = 0x43;
+
+	let (is_wad3, miptex_type) = match bytes.get(..4) {
+		Some(b"WAD2") => (false, WAD2_MIPTEX),
+		Some(b"WAD3") => (true, WAD3_MIPTEX),
+		_ => return Err(WadLoadError::NotAWad),
+	};
+	let entry_count = read_u32(bytes, 4)? as usize;
+	let directory = read_u32(bytes, 8)? as usize;
+
+	let mut textures = Vec::new();
+	for i in 0..entry_count {
+		let entry = i
+			.checked_mul(32)
+			.and_then(|entry| entry.checked_add(directory))
+			.ok_or(WadLoadError::UnexpectedEnd)?;
+		let offset = read_u32(bytes, entry)? as usize;
+		let ty = read_bytes(bytes, entry, 12, 1)?[0];
+		let compression = read_bytes(bytes, entry, 13, 1)?[0];
+		let name = read_name(bytes, entry, 16)?;
+
+		if ty != miptex_type {
+			continue;
+		}
+		if compression != 0 {
+			return Err(WadLoadError::Compressed(name));
+		}
+
+		let width = read_u32(bytes, offset.checked_add(16).ok_or(WadLoadError::UnexpectedEnd)?)?;
+		let height = read_u32(bytes, offset.checked_add(20).ok_or(WadLoadError::UnexpectedEnd)?)?;
+		let pixels = (width as usize).checked_mul(height as usize).ok_or(WadLoadError::UnexpectedEnd)?;
+		let pixels_offset = read_u32(bytes, offset.checked_add(24).ok_or(WadLoadError::UnexpectedEnd)?)? as usize;
+		let indices = read_bytes(bytes, offset, pixels_offset, pixels)?;
+
+		// WAD3 textures have their palette after their last mipmap, which is an eighth of the size of the texture on each axis.
+		let texture_palette: WadPalette;
+		let palette = if is_wad3 {
+			let last_mip_offset = read_u32(bytes, offset.checked_add(36).ok_or(WadLoadError::UnexpectedEnd)?)? as usize;
+			let colors = read_bytes(bytes, offset, last_mip_offset, pixels / 64 + 2 + 256 * 3)?;
+			let colors = &colors[pixels / 64 + 2..];
+
+			texture_palette = std::array::from_fn(|i| [colors[i * 3], colors[i * 3 + 1], colors[i * 3 + 2]]);
+			&texture_palette
+		} else {
+			palette.ok_or(WadLoadError::NoPalette)?
+		};
+
+		let masked = name.starts_with('{');
+		// `indices` was read from the file, so this can't be more than 4 times the file's size.
+		let mut data = Vec::with_capacity(pixels * 4);
+		for &index in indices {
+			let [r, g, b] = palette[index as usize];
+			let a = if masked && index == 255 { 0 } else { 255 };
+			data.extend_from_slice(&[r, g, b, a]);
+		}
+
+		textures.push(WadTexture {
+			name,
+			width,
+			height,
+			masked,
+			data,
+		});
+	}
+
+	Ok(textures)
+}
+
+/// Returns `len` bytes starting `offset` bytes after `base`.
+fn read_bytes(bytes: &[u8], base: usize, offset: usize, len: usize) -> Result<&[u8], WadLoadError> {
+	base.checked_add(offset)
+		.and_then(|start| Some(start..start.checked_add(len)?))
+		.and_then(|range| bytes.get(range))
+		.ok_or(WadLoadError::UnexpectedEnd)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, WadLoadError> {
+	let bytes = read_bytes(bytes, offset, 0, 4)?;
+	Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Reads a null-terminated name of up to 16 bytes.
+fn read_name(bytes: &[u8], base: usize, offset: usize) -> Result<String, WadLoadError> {
+	let bytes = read_bytes(bytes, base, offset, 16)?;
+	let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+	Ok(String::from_utf8_lossy(&bytes[..len]).into_owned())
+}
+
+#[test]
+fn read_wad3() {
+	let bytes = include_bytes!("../../assets/materials/test.wad");
+	let textures = read_wad(bytes, None).unwrap();
+
+	let [texture] = &textures[..] else { panic!("expected 1 texture, found {}", textures.len()) };
+	assert_eq!(texture.name, "{FENCE");
+	assert_eq!((texture.width, texture.height), (8, 8));
+	assert!(texture.masked);
+	// The first pixel uses color 1, and the last is masked out with color 255.
+	assert_eq!(texture.data[..4], [255, 0, 0, 255]);
+	assert_eq!(texture.data[texture.data.len() - 4..], [0, 0, 255, 0]);
+}
+
+#[test]
+fn read_truncated_wad() {
+	let bytes = include_bytes!("../../assets/materials/test.wad");
+
+	for len in 0..bytes.len() {
+		assert!(read_wad(&bytes[..len], None).is_err(), "truncated to {len} bytes");
+	}
+}
+
+#[test]
+fn read_hostile_wad() {
+	let mut bytes = include_bytes!("../../assets/materials/test.wad").to_vec();
+	// Claim billions of entries, and a texture size that overflows when added to its offset.
+	bytes[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+	let directory = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+	let offset = u32::from_le_bytes(bytes[directory..directory + 4].try_into().unwrap()) as usize;
+	bytes[offset + 16..offset + 28].copy_from_slice(&[0xFF; 12]);
+
+	assert!(read_wad(&bytes, None).is_err());
+}
@@ -0,0 +1,145 @@
+//! Serializable versions of `wgpu` types that materials use, but that can't be accessed through reflection.
+//!
+//! Use them with [`MaterializeAppExt::register_generic_material_foreign_field`](crate::MaterializeAppExt::register_generic_material_foreign_field).
+
+use bevy::render::render_resource::{CompareFunction, Face, FrontFace, PolygonMode};
+use serde::{Deserialize, Serialize};
+
+/// Stands for an [`Option<Face>`], as used by `cull_mode` fields. `"None"` disables culling.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CullModeDef {
+	None,
+	Front,
+	Back,
+}
+impl From<CullModeDef> for Option<Face> {
+	fn from(value: CullModeDef) -> Self {
+		match value {
+			CullModeDef::None => None,
+			CullModeDef::Front => Some(Face::Front),
+			CullModeDef::Back => Some(Face::Back),
+		}
+	}
+}
+impl From<Option<Face>> for CullModeDef {
+	fn from(value: Option<Face>) -> Self {
+		match value {
+			None => CullModeDef::None,
+			Some(Face::Front) => CullModeDef::Front,
+			Some(Face::Back) => CullModeDef::Back,
+		}
+	}
+}
+
+/// Stands for a [`Face`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaceDef {
+	Front,
+	Back,
+}
+impl From<FaceDef> for Face {
+	fn from(value: FaceDef) -> Self {
+		match value {
+			FaceDef::Front => Face::Front,
+			FaceDef::Back => Face::Back,
+		}
+	}
+}
+impl From<Face> for FaceDef {
+	fn from(value: Face) -> Self {
+		match value {
+			Face::Front => FaceDef::Front,
+			Face::Back => FaceDef::Back,
+		}
+	}
+}
+
+/// Stands for a [`CompareFunction`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareFunctionDef {
+	Never,
+	Less,
+	Equal,
+	LessEqual,
+	Greater,
+	NotEqual,
+	GreaterEqual,
+	Always,
+}
+impl From<CompareFunctionDef> for CompareFunction {
+	fn from(value: CompareFunctionDef) -> Self {
+		match value {
+			CompareFunctionDef::Never => CompareFunction::Never,
+			CompareFunctionDef::Less => CompareFunction::Less,
+			CompareFunctionDef::Equal => CompareFunction::Equal,
+			CompareFunctionDef::LessEqual => CompareFunction::LessEqual,
+			CompareFunctionDef::Greater => CompareFunction::Greater,
+			CompareFunctionDef::NotEqual => CompareFunction::NotEqual,
+			CompareFunctionDef::GreaterEqual => CompareFunction::GreaterEqual,
+			CompareFunctionDef::Always => CompareFunction::Always,
+		}
+	}
+}
+impl From<CompareFunction> for CompareFunctionDef {
+	fn from(value: CompareFunction) -> Self {
+		match value {
+			CompareFunction::Never => CompareFunctionDef::Never,
+			CompareFunction::Less => CompareFunctionDef::Less,
+			CompareFunction::Equal => CompareFunctionDef::Equal,
+			CompareFunction::LessEqual => CompareFunctionDef::LessEqual,
+			CompareFunction::Greater => CompareFunctionDef::Greater,
+			CompareFunction::NotEqual => CompareFunctionDef::NotEqual,
+			CompareFunction::GreaterEqual => CompareFunctionDef::GreaterEqual,
+			CompareFunction::Always => CompareFunctionDef::Always,
+		}
+	}
+}
+
+/// Stands for a [`FrontFace`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontFaceDef {
+	Ccw,
+	Cw,
+}
+impl From<FrontFaceDef> for FrontFace {
+	fn from(value: FrontFaceDef) -> Self {
+		match value {
+			FrontFaceDef::Ccw => FrontFace::Ccw,
+			FrontFaceDef::Cw => FrontFace::Cw,
+		}
+	}
+}
+impl From<FrontFace> for FrontFaceDef {
+	fn from(value: FrontFace) -> Self {
+		match value {
+			FrontFace::Ccw => FrontFaceDef::Ccw,
+			FrontFace::Cw => FrontFaceDef::Cw,
+		}
+	}
+}
+
+/// Stands for a [`PolygonMode`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolygonModeDef {
+	Fill,
+	Line,
+	Point,
+}
+impl From<PolygonModeDef> for PolygonMode {
+	fn from(value: PolygonModeDef) -> Self {
+		match value {
+			PolygonModeDef::Fill => PolygonMode::Fill,
+			PolygonModeDef::Line => PolygonMode::Line,
+			PolygonModeDef::Point => PolygonMode::Point,
+		}
+	}
+}
+impl From<PolygonMode> for PolygonModeDef {
+	fn from(value: PolygonMode) -> Self {
+		match value {
+			PolygonMode::Fill => PolygonModeDef::Fill,
+			PolygonMode::Line => PolygonModeDef::Line,
+			PolygonMode::Point => PolygonModeDef::Point,
+		}
+	}
+}
@@ -0,0 +1,78 @@
+use bevy::{prelude::*, reflect::GetPath};
+
+use crate::{erased_material::ErasedMaterialHandle, generic_material::GenericMaterialApplied};
+
+/// Overrides fields of the material applied by an entity's [`GenericMaterial3d`](crate::GenericMaterial3d), such as tinting a single entity.
+///
+/// The entity gets its own copy of the material with the overrides applied, so it no longer batches with other entities using the same material.
+/// Fields are reflect paths, so nested fields like `base.base_color` are supported. Values are applied as-is, so `Option` fields need their value wrapped in [`Some`].
+///
+/// # Examples
+/// ```ignore
+/// commands.spawn((
+///     Mesh3d(mesh),
+///     GenericMaterial3d(asset_server.load("materials/crate.toml")),
+///     GenericMaterialOverrides::default().with("base_color", Color::srgb(1., 0., 0.)),
+/// ));
+/// ```
+#[derive(Component, Debug, Default)]
+pub struct GenericMaterialOverrides {
+	pub fields: Vec<(String, Box<dyn PartialReflect>)>,
+}
+impl GenericMaterialOverrides {
+	/// Overrides `field` with `value`. Later overrides of the same field take precedence.
+	pub fn with(mut self, field: impl Into<String>, value: impl PartialReflect) -> Self {
+		self.fields.push((field.into(), Box::new(value)));
+		self
+	}
+
+	/// Returns a copy of `material` with these overrides applied, added as a new asset.
+	///
+	/// Overrides of fields that don't exist or don't match the type of their value are skipped with an error.
+	pub fn instantiate(&self, world: &World, material: &ErasedMaterialHandle) -> Option<ErasedMaterialHandle> {
+		let Some(mut copy) = material.clone_material(world) else {
+			error!("Failed to copy {material:?} for material overrides, is it a registered generic material?");
+			return None;
+		};
+
+		for (field, value) in &self.fields {
+			let result = match copy.as_reflect_mut().reflect_path_mut(field.as_str()) {
+				Ok(target) => target.try_apply(value.as_ref()).map_err(|err| err.to_string()),
+				Err(err) => Err(err.to_string()),
+			};
+
+			if let Err(err) = result {
+				error!("Failed to override field `{field}` of {}: {err}", copy.reflect_short_type_path());
+			}
+		}
+
+		Some(copy.add_asset(world.resource::<AssetServer>()))
+	}
+}
+
+/// Gives an entity its own copy of the material applied by its [`GenericMaterial3d`](crate::GenericMaterial3d),
+/// so it can be changed without affecting other entities using the same material, e.g. for damage flashes or highlight effects.
+///
+/// The copy can be accessed through the entity's [`MeshMaterial3d`] once the material is applied.
+/// Entities with [`GenericMaterialOverrides`] or the [`RANDOM_UV_OFFSET`](crate::GenericMaterial::RANDOM_UV_OFFSET) property already get their own copy.
+#[derive(Component, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Component, Default)]
+pub struct UniqueGenericMaterial;
+
+/// Re-applies materials of entities whose [`GenericMaterialOverrides`] or [`UniqueGenericMaterial`] were changed or removed.
+pub fn reapply_material_overrides(
+	mut commands: Commands,
+	changed_query: Query<
+		Entity,
+		(
+			Or<(Changed<GenericMaterialOverrides>, Added<UniqueGenericMaterial>)>,
+			With<GenericMaterialApplied>,
+		),
+	>,
+	mut removed: RemovedComponents<GenericMaterialOverrides>,
+	mut removed_unique: RemovedComponents<UniqueGenericMaterial>,
+) {
+	for entity in changed_query.iter().chain(removed.read()).chain(removed_unique.read()) {
+		commands.entity(entity).try_remove::<GenericMaterialApplied>();
+	}
+}
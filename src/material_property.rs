@@ -5,16 +5,49 @@ use std::{
 };
 
 use bevy::{
+	asset::LoadContext,
 	platform::collections::HashMap,
 	prelude::*,
-	reflect::{GetTypeRegistration, TypeInfo},
+	reflect::{GetTypeRegistration, TypeInfo, TypeRegistry, reflect_trait},
 };
 use thiserror::Error;
 
+use crate::{GenericMaterial, GenericMaterial3d};
+
 /// Maps property names to the types they represent.
 #[derive(Resource, Debug, Clone, Default)]
 pub struct MaterialPropertyRegistry {
 	pub inner: Arc<RwLock<HashMap<String, TypeId>>>,
+	/// Values given to materials that don't specify the property. Usually set by a [`PropertyManifest`](crate::property_manifest::PropertyManifest).
+	pub defaults: Arc<RwLock<HashMap<String, Box<dyn Reflect>>>>,
+}
+impl MaterialPropertyRegistry {
+	/// Registers `key` as a property of the type `type_id`.
+	///
+	/// If `key` is already registered with a different type, an error naming both types is logged, the existing registration is kept, and `false` is returned.
+	pub fn register(&self, key: String, type_id: TypeId, type_registry: &TypeRegistry) -> bool {
+		let mut property_map = self.inner.write().unwrap();
+		match property_map.get(&key) {
+			Some(&existing) if existing != type_id => {
+				let type_path = |type_id| {
+					type_registry
+						.get(type_id)
+						.map_or("<unregistered type>", |registration| registration.type_info().type_path())
+				};
+				error!(
+					"Material property `{key}` is already registered as {}, ignoring registration as {}. \
+					Consider registering it with `register_material_property_namespaced`",
+					type_path(existing),
+					type_path(type_id),
+				);
+				false
+			}
+			_ => {
+				property_map.insert(key, type_id);
+				true
+			}
+		}
+	}
 }
 
 /// Helper type containing both a type and key for material properties.
@@ -54,18 +87,103 @@ pub enum GetPropertyError {
 	NotFound,
 	#[error("Property found doesn't have the required type. Type found: {:?}", found.map(TypeInfo::type_path))]
 	WrongType { found: Option<&'static TypeInfo> },
+	#[error("Entity doesn't have a loaded generic material")]
+	NoMaterial,
+}
+
+/// Implemented by property types that produce extra assets while their material loads, such as a generated mesh or a baked lookup table.
+///
+/// Register it on the property's type with `#[reflect(PropertySubAssets)]` for the loader to pick it up.
+///
+/// # Examples
+/// ```ignore
+/// #[derive(Reflect, Default)]
+/// #[reflect(PropertySubAssets)]
+/// pub struct Gibs {
+///     pub size: f32,
+///     #[reflect(ignore)]
+///     pub mesh: Handle<Mesh>,
+/// }
+/// impl PropertySubAssets for Gibs {
+///     fn add_sub_assets(&mut self, key: &str, load_context: &mut LoadContext) {
+///         self.mesh = load_context.add_labeled_asset(format!("{key}_mesh"), Cuboid::from_length(self.size).into());
+///     }
+/// }
+/// ```
+#[reflect_trait]
+pub trait PropertySubAssets {
+	/// Called after the property with the key `key` is deserialized. Labeled assets added here become sub-assets of the [`GenericMaterial`].
+	fn add_sub_assets(&mut self, key: &str, load_context: &mut LoadContext);
+}
+
+/// A collection of material properties that can be registered all at once. Usually implemented via `#[derive(MaterialProperties)]` with the `derive` feature.
+pub trait MaterialProperties {
+	/// Registers every property in this collection.
+	fn register(app: &mut App);
+}
+
+/// Functions that apply components to an entity based on its [`GenericMaterial`]'s properties,
+/// run on demand by [`refresh_generic_material_properties`](MaterialPropertyCommandsExt::refresh_generic_material_properties).
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MaterialPropertyAppliers {
+	pub inner: Vec<fn(&GenericMaterial, &mut EntityWorldMut)>,
+}
+
+pub trait MaterialPropertyCommandsExt {
+	/// Re-runs every function in [`MaterialPropertyAppliers`] (such as the one for [`VISIBILITY`](GenericMaterial::VISIBILITY)) on an entity with a [`GenericMaterial3d`].
+	///
+	/// Properties are normally only applied when the material is first inserted, so use this after changing a [`GenericMaterial`]'s properties at runtime.
+	fn refresh_generic_material_properties(&mut self, entity: Entity);
+}
+impl MaterialPropertyCommandsExt for Commands<'_, '_> {
+	fn refresh_generic_material_properties(&mut self, entity: Entity) {
+		self.queue(move |world: &mut World| {
+			let Some(appliers) = world.get_resource::<MaterialPropertyAppliers>().cloned() else { return };
+			let Some(handle) = world.get::<GenericMaterial3d>(entity).map(|holder| holder.0.clone()) else { return };
+
+			world.resource_scope(|world, generic_materials: Mut<Assets<GenericMaterial>>| {
+				let Some(generic_material) = generic_materials.get(&handle) else { return };
+				let Ok(mut entity) = world.get_entity_mut(entity) else { return };
+
+				for applier in &appliers.inner {
+					applier(generic_material, &mut entity);
+				}
+			});
+		});
+	}
 }
 
 pub trait MaterialPropertyAppExt {
-	/// Registers material properties with the specified key to try to deserialize into `T`. Overwrites registration if one already exists for `key`.
+	/// Registers material properties with the specified key to try to deserialize into `T`.
+	///
+	/// If `key` is already registered with a different type, an error naming both types is logged and the existing registration is kept.
+	/// Plugins can avoid such conflicts with [`register_material_property_namespaced`](MaterialPropertyAppExt::register_material_property_namespaced).
 	///
 	/// Also registers the type if it hasn't been already.
 	fn register_material_property_manual<T: Reflect + GetTypeRegistration>(&mut self, key: impl Into<String>) -> &mut Self;
 
-	/// Uses the [`MaterialProperty`] helper type to register a material property. Overwrites registration if one already exists for `key`.
+	/// Uses the [`MaterialProperty`] helper type to register a material property. See [`register_material_property_manual`](MaterialPropertyAppExt::register_material_property_manual).
 	///
 	/// Also registers the type if it hasn't been already.
 	fn register_material_property<T: Reflect + GetTypeRegistration>(&mut self, property: MaterialProperty<T>) -> &mut Self;
+
+	/// Registers a material property under the key `namespace:key`, so properties of different plugins can't conflict.
+	///
+	/// # Examples
+	/// ```ignore
+	/// app.register_material_property_namespaced::<f32>("my_plugin", "wetness");
+	/// ```
+	/// ```toml
+	/// [properties]
+	/// "my_plugin:wetness" = 0.5
+	/// ```
+	fn register_material_property_namespaced<T: Reflect + GetTypeRegistration>(&mut self, namespace: &str, key: &str) -> &mut Self;
+
+	/// Registers every property in a [`MaterialProperties`] collection.
+	fn register_material_properties<T: MaterialProperties>(&mut self) -> &mut Self;
+
+	/// Adds a function to [`MaterialPropertyAppliers`], which applies components to entities based on their material's properties.
+	fn register_material_property_applier(&mut self, applier: fn(&GenericMaterial, &mut EntityWorldMut)) -> &mut Self;
 }
 impl MaterialPropertyAppExt for App {
 	fn register_material_property_manual<T: Reflect + GetTypeRegistration>(&mut self, key: impl Into<String>) -> &mut Self {
@@ -73,11 +191,11 @@ impl MaterialPropertyAppExt for App {
 		if type_registry.get(TypeId::of::<T>()).is_none() {
 			type_registry.register::<T>();
 		}
-		drop(type_registry);
 
-		let mut property_map = self.world().resource::<MaterialPropertyRegistry>().inner.write().unwrap();
-		property_map.insert(key.into(), TypeId::of::<T>());
-		drop(property_map);
+		self.world()
+			.resource::<MaterialPropertyRegistry>()
+			.register(key.into(), TypeId::of::<T>(), &type_registry);
+		drop(type_registry);
 
 		self
 	}
@@ -85,4 +203,18 @@ impl MaterialPropertyAppExt for App {
 	fn register_material_property<T: Reflect + GetTypeRegistration>(&mut self, property: MaterialProperty<T>) -> &mut Self {
 		self.register_material_property_manual::<T>(property.key)
 	}
+
+	fn register_material_property_namespaced<T: Reflect + GetTypeRegistration>(&mut self, namespace: &str, key: &str) -> &mut Self {
+		self.register_material_property_manual::<T>(format!("{namespace}:{key}"))
+	}
+
+	fn register_material_properties<T: MaterialProperties>(&mut self) -> &mut Self {
+		T::register(self);
+		self
+	}
+
+	fn register_material_property_applier(&mut self, applier: fn(&GenericMaterial, &mut EntityWorldMut)) -> &mut Self {
+		self.world_mut().get_resource_or_init::<MaterialPropertyAppliers>().inner.push(applier);
+		self
+	}
 }
@@ -0,0 +1,69 @@
+use bevy::{
+	ecs::{lifecycle::HookContext, world::DeferredWorld},
+	platform::collections::HashMap,
+	prelude::*,
+};
+
+#[cfg(feature = "bevy_sprite_render")]
+use crate::generic_material::GenericMaterial2d;
+use crate::generic_material::{GenericMaterial, GenericMaterial3d};
+
+/// Priority of the [`GenericMaterial3d`] (or `GenericMaterial2d`) inserted alongside it, for when several systems set an entity's material in the same frame,
+/// such as prefabs, scene spawning, and gameplay.
+///
+/// Within a frame, inserting a material with a lower priority than the one already inserted reverts back to the higher priority material,
+/// rather than the last write winning. Only inserts that include this component are arbitrated.
+///
+/// # Examples
+/// ```ignore
+/// // Even if a prefab inserts its own material later this frame, the frozen material stays.
+/// commands.entity(entity).insert((GenericMaterial3d(frozen), GenericMaterialSource(10)));
+/// ```
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[component(on_insert = Self::on_insert)]
+#[reflect(Component, Default)]
+pub struct GenericMaterialSource(pub i32);
+impl GenericMaterialSource {
+	fn on_insert(mut world: DeferredWorld, ctx: HookContext) {
+		let entity = ctx.entity;
+		let priority = world.entity(entity).get::<Self>().unwrap().0;
+		let Some(handle) = material_handle(&world, entity) else { return };
+		let Some(claims) = world.get_resource::<GenericMaterialClaims>() else { return };
+
+		match claims.claims.get(&entity).cloned() {
+			Some((claimed_priority, claimed_handle)) if claimed_priority > priority => {
+				if let Some(mut holder) = world.get_mut::<GenericMaterial3d>(entity) {
+					holder.0 = claimed_handle.clone();
+				}
+				#[cfg(feature = "bevy_sprite_render")]
+				if let Some(mut holder) = world.get_mut::<GenericMaterial2d>(entity) {
+					holder.0 = claimed_handle;
+				}
+				world.get_mut::<Self>(entity).unwrap().0 = claimed_priority;
+			}
+			_ => {
+				world.resource_mut::<GenericMaterialClaims>().claims.insert(entity, (priority, handle));
+			}
+		}
+	}
+}
+
+/// The highest priority material inserted with a [`GenericMaterialSource`] on each entity this frame, cleared by [`clear_generic_material_claims`].
+#[derive(Resource, Debug, Clone, Default)]
+pub struct GenericMaterialClaims {
+	pub claims: HashMap<Entity, (i32, Handle<GenericMaterial>)>,
+}
+
+/// Clears [`GenericMaterialClaims`] at the end of each frame, so materials can be changed to lower priority ones in later frames.
+pub fn clear_generic_material_claims(mut claims: ResMut<GenericMaterialClaims>) {
+	claims.claims.clear();
+}
+
+fn material_handle(world: &DeferredWorld, entity: Entity) -> Option<Handle<GenericMaterial>> {
+	let entity = world.entity(entity);
+	let handle = entity.get::<GenericMaterial3d>().map(|holder| &holder.0);
+	#[cfg(feature = "bevy_sprite_render")]
+	let handle = handle.or_else(|| entity.get::<GenericMaterial2d>().map(|holder| &holder.0));
+
+	handle.cloned()
+}
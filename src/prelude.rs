@@ -1,12 +1,26 @@
+#[cfg(feature = "bevy_sprite_render")]
+pub use crate::generic_material::GenericMaterial2d;
 #[cfg(feature = "json")]
 pub use crate::load::deserializer::JsonMaterialDeserializer;
+#[cfg(feature = "kdl")]
+pub use crate::load::deserializer::KdlMaterialDeserializer;
 #[cfg(feature = "toml")]
 pub use crate::load::deserializer::TomlMaterialDeserializer;
 #[cfg(feature = "bevy_pbr")]
-pub use crate::{MaterializeAppExt, generic_material::ReflectGenericMaterial};
+pub use crate::{
+	GenericMaterialCommandsExt, GenericMaterialPlugin, MaterializeAppExt,
+	generic_material::{GenericMaterialBuilder, ReflectGenericMaterial},
+	highlight::HighlightCommandsExt,
+	material_overrides::{GenericMaterialOverrides, UniqueGenericMaterial},
+	texture_override::GenericMaterialTextureOverride,
+};
 pub use crate::{
 	MaterializePlugin,
-	generic_material::{GenericMaterial, GenericMaterial3d},
-	load::{asset::GenericMaterialSubAssetAppExt, deserializer::MaterialDeserializer},
-	material_property::{MaterialProperty, MaterialPropertyAppExt},
+	collider::ColliderAppExt,
+	generic_material::{GenericMaterial, GenericMaterial3d, GenericMaterialQuery, GenericMaterials, GenericMaterialsMut},
+	load::{asset::GenericMaterialSubAssetAppExt, deserializer::MaterialDeserializer, library::GenericMaterialLibrary, set::GenericMaterialSet},
+	material_property::{MaterialProperties, MaterialProperty, MaterialPropertyAppExt, MaterialPropertyCommandsExt},
+	material_source::GenericMaterialSource,
 };
+#[cfg(feature = "derive")]
+pub use bevy_materialize_derive::MaterialProperties;
@@ -0,0 +1,182 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use bevy::{
+	prelude::*,
+	reflect::{ReflectFromReflect, serde::TypedReflectDeserializer},
+};
+use serde::{Deserialize, de::DeserializeSeed};
+use thiserror::Error;
+
+use crate::material_property::MaterialPropertyRegistry;
+
+/// Static declaration of every property materials are allowed to have, usually read from a `materialize.properties.toml` file.
+///
+/// Adding this to [`MaterializePlugin`](crate::MaterializePlugin) registers every declared property, so you don't have to call
+/// [`register_material_property`](crate::material_property::MaterialPropertyAppExt::register_material_property) for each one.
+/// Since loading already fails on unregistered properties, this also acts as a schema for your material files.
+///
+/// If no manifest is given to the plugin, the one at [`DEFAULT_PATH`](Self::DEFAULT_PATH) is used if it exists.
+///
+/// # Examples
+/// ```toml
+/// [properties.collision]
+/// type = "bool"
+/// default = true
+///
+/// [properties.sounds]
+/// type = "alloc::string::String"
+/// ```
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PropertyManifest {
+	#[serde(default)]
+	pub properties: BTreeMap<String, PropertyDeclaration>,
+}
+impl PropertyManifest {
+	/// Where the manifest is conventionally stored, relative to the working directory.
+	pub const DEFAULT_PATH: &str = "assets/materialize.properties.toml";
+
+	/// Parses a manifest from a TOML string.
+	pub fn from_toml(s: &str) -> Result<Self, PropertyManifestError> {
+		Ok(toml::from_str(s)?)
+	}
+
+	/// Reads and parses a manifest file from disk.
+	pub fn read(path: impl AsRef<Path>) -> Result<Self, PropertyManifestError> {
+		Self::from_toml(&fs::read_to_string(path)?)
+	}
+
+	/// Like [`read`](Self::read), but returns [`None`] if the file doesn't exist.
+	pub fn read_if_exists(path: impl AsRef<Path>) -> Result<Option<Self>, PropertyManifestError> {
+		match fs::read_to_string(path) {
+			Ok(s) => Self::from_toml(&s).map(Some),
+			Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+			Err(err) => Err(err.into()),
+		}
+	}
+
+	/// Registers every declared property and its default. Types are looked up in the app's type registry by their full or short type path.
+	///
+	/// Properties already registered with a different type are skipped with an error logged,
+	/// like with [`register_material_property`](crate::material_property::MaterialPropertyAppExt::register_material_property).
+	pub fn register(&self, app: &mut App) -> Result<(), PropertyManifestError> {
+		let type_registry = app.world().resource::<AppTypeRegistry>().read();
+		let property_registry = app.world().resource::<MaterialPropertyRegistry>();
+
+		for (key, declaration) in &self.properties {
+			let Some(registration) = type_registry
+				.get_with_type_path(&declaration.ty)
+				.or_else(|| type_registry.get_with_short_type_path(&declaration.ty))
+			else {
+				return Err(PropertyManifestError::TypeNotFound {
+					key: key.clone(),
+					ty: declaration.ty.clone(),
+				});
+			};
+
+			if !property_registry.register(key.clone(), registration.type_id(), &type_registry) {
+				continue;
+			}
+
+			let Some(default) = declaration.default.clone() else { continue };
+
+			let Some(from_reflect) = registration.data::<ReflectFromReflect>() else {
+				return Err(PropertyManifestError::NoFromReflect(registration.type_info().type_path()));
+			};
+			let partial_data = TypedReflectDeserializer::new(registration, &type_registry)
+				.deserialize(default)
+				.map_err(|err| PropertyManifestError::Default { key: key.clone(), err })?;
+			let Some(data) = from_reflect.from_reflect(&*partial_data) else {
+				return Err(PropertyManifestError::NoFromReflect(registration.type_info().type_path()));
+			};
+
+			property_registry.defaults.write().unwrap().insert(key.clone(), data);
+		}
+
+		Ok(())
+	}
+}
+
+/// A single property in a [`PropertyManifest`].
+#[derive(Deserialize, Debug, Clone)]
+pub struct PropertyDeclaration {
+	/// The full or short type path of the property's type. The type must be registered in the type registry.
+	#[serde(rename = "type")]
+	pub ty: String,
+	/// Value used for materials that don't specify this property.
+	pub default: Option<toml::Value>,
+}
+
+/// Errors that may occur when reading or registering a [`PropertyManifest`].
+#[derive(Error, Debug)]
+pub enum PropertyManifestError {
+	#[error("{0}")]
+	Io(#[from] io::Error),
+	#[error("Deserialize error: {0}")]
+	Deserialize(#[from] toml::de::Error),
+	#[error("Property {key} is declared with type {ty}, which isn't registered in the type registry")]
+	TypeNotFound { key: String, ty: String },
+	#[error("Could not get `ReflectFromReflect` for type {0}")]
+	NoFromReflect(&'static str),
+	#[error("Invalid default for property {key}: {err}")]
+	Default { key: String, err: toml::de::Error },
+}
+
+#[test]
+fn parse_error() {
+	// Missing the required `type`.
+	let err = PropertyManifest::from_toml("[properties.collision]\ndefault = true\n").unwrap_err();
+	assert!(matches!(err, PropertyManifestError::Deserialize(_)), "{err}");
+}
+
+#[test]
+fn missing_file() {
+	let path = "assets/does-not-exist.properties.toml";
+	assert!(PropertyManifest::read_if_exists(path).unwrap().is_none());
+	assert!(matches!(PropertyManifest::read(path), Err(PropertyManifestError::Io(_))));
+}
+
+#[test]
+fn conflicting_key() {
+	use std::any::TypeId;
+
+	use crate::material_property::MaterialPropertyAppExt;
+
+	let mut app = App::new();
+	app.init_resource::<MaterialPropertyRegistry>()
+		.register_type::<String>()
+		.register_material_property_manual::<bool>("collision");
+
+	let manifest = PropertyManifest::from_toml(
+		"[properties.collision]\ntype = \"String\"\ndefault = \"yes\"\n\
+		[properties.sounds]\ntype = \"String\"\ndefault = \"wood\"\n",
+	)
+	.unwrap();
+	manifest.register(&mut app).unwrap();
+
+	let property_registry = app.world().resource::<MaterialPropertyRegistry>();
+	let properties = property_registry.inner.read().unwrap();
+	let defaults = property_registry.defaults.read().unwrap();
+	// The existing registration is kept, and the conflicting default isn't used.
+	assert_eq!(properties["collision"], TypeId::of::<bool>());
+	assert!(!defaults.contains_key("collision"));
+	// Other properties are still registered.
+	assert_eq!(properties["sounds"], TypeId::of::<String>());
+	assert_eq!(defaults["sounds"].downcast_ref::<String>().unwrap(), "wood");
+}
+
+#[test]
+fn plugin_logs_manifest_errors() {
+	use crate::{MaterializePlugin, load::deserializer::TomlMaterialDeserializer};
+
+	let manifest = PropertyManifest::from_toml("[properties.collision]\ntype = \"NotAType\"\n").unwrap();
+	let mut app = App::new();
+	app.add_plugins((
+		MinimalPlugins,
+		AssetPlugin::default(),
+		MaterializePlugin::new(TomlMaterialDeserializer).with_property_manifest(manifest),
+	));
+	app.finish();
+
+	let property_registry = app.world().resource::<MaterialPropertyRegistry>();
+	assert!(!property_registry.inner.read().unwrap().contains_key("collision"));
+}
@@ -0,0 +1,188 @@
+use std::marker::PhantomData;
+
+use bevy::{
+	color::ColorToComponents,
+	pbr::{ExtendedMaterial, MaterialExtension},
+	prelude::*,
+	reflect::{GetTypeRegistration, Typed},
+	render::render_resource::AsBindGroup,
+	shader::ShaderRef,
+};
+
+use crate::{MaterializeAppExt, prelude::*};
+
+/// How many properties a [`PropertyUniformsExtension`] can expose.
+pub const MAX_PROPERTY_UNIFORMS: usize = 16;
+
+/// Describes which material properties a [`PropertyUniformsExtension`] exposes, and the shaders reading them.
+///
+/// # Examples
+/// ```ignore
+/// #[derive(TypePath, Clone)]
+/// struct Wetness;
+/// impl PropertyUniforms for Wetness {
+///     const PROPERTIES: &[&str] = &["wetness", "puddle_color"];
+///
+///     fn fragment_shader() -> ShaderRef {
+///         "shaders/wetness.wgsl".into()
+///     }
+/// }
+/// ```
+pub trait PropertyUniforms: TypePath + Clone + Send + Sync + 'static {
+	/// Keys of the properties written to the uniform, in order, each taking up one `vec4<f32>`. Only the first [`MAX_PROPERTY_UNIFORMS`] are used.
+	const PROPERTIES: &[&str];
+
+	fn vertex_shader() -> ShaderRef {
+		ShaderRef::Default
+	}
+
+	fn fragment_shader() -> ShaderRef {
+		ShaderRef::Default
+	}
+}
+
+/// Material extension exposing the properties listed by `U` to shaders, so effect shaders can read data-driven properties without a bespoke extension material.
+///
+/// The properties are written from the [`GenericMaterial`] using this material by [`PropertyUniformsPlugin`] whenever it's loaded or modified.
+/// Numbers and booleans are written to the `x` component, vectors to as many components as they have, and colors as linear RGBA.
+/// Properties that are missing or of other types are left as zero.
+///
+/// In shaders, the uniform is an array at binding 100 of the material bind group.
+/// ```wgsl
+/// @group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> properties: array<vec4<f32>, 16>;
+/// ```
+#[derive(Asset, AsBindGroup, Reflect, Clone)]
+#[reflect(Default)]
+pub struct PropertyUniformsExtension<U: PropertyUniforms> {
+	#[uniform(100)]
+	pub values: [Vec4; MAX_PROPERTY_UNIFORMS],
+	#[reflect(ignore)]
+	pub marker: PhantomData<U>,
+}
+impl<U: PropertyUniforms> Default for PropertyUniformsExtension<U> {
+	fn default() -> Self {
+		Self {
+			values: default(),
+			marker: PhantomData,
+		}
+	}
+}
+impl<U: PropertyUniforms> MaterialExtension for PropertyUniformsExtension<U> {
+	fn vertex_shader() -> ShaderRef {
+		U::vertex_shader()
+	}
+
+	fn fragment_shader() -> ShaderRef {
+		U::fragment_shader()
+	}
+}
+impl<U: PropertyUniforms> PropertyUniformsExtension<U> {
+	/// Returns the uniform values for the properties of `generic_material`.
+	pub fn values_of(generic_material: &GenericMaterial) -> [Vec4; MAX_PROPERTY_UNIFORMS] {
+		let mut values = [Vec4::ZERO; MAX_PROPERTY_UNIFORMS];
+
+		for (value, key) in values.iter_mut().zip(U::PROPERTIES) {
+			let Some(property) = generic_material.properties.get(*key) else { continue };
+			*value = property_to_vec4(property.as_ref()).unwrap_or_default();
+		}
+
+		values
+	}
+}
+
+/// Converts a property value to a `vec4<f32>` for [`PropertyUniformsExtension`], returning [`None`] if it's of an unsupported type.
+pub fn property_to_vec4(property: &dyn Reflect) -> Option<Vec4> {
+	let any = property.as_any();
+
+	if let Some(&value) = any.downcast_ref::<f32>() {
+		Some(Vec4::new(value, 0., 0., 0.))
+	} else if let Some(&value) = any.downcast_ref::<f64>() {
+		Some(Vec4::new(value as f32, 0., 0., 0.))
+	} else if let Some(&value) = any.downcast_ref::<i32>() {
+		Some(Vec4::new(value as f32, 0., 0., 0.))
+	} else if let Some(&value) = any.downcast_ref::<u32>() {
+		Some(Vec4::new(value as f32, 0., 0., 0.))
+	} else if let Some(&value) = any.downcast_ref::<bool>() {
+		Some(Vec4::new(if value { 1. } else { 0. }, 0., 0., 0.))
+	} else if let Some(&value) = any.downcast_ref::<Vec2>() {
+		Some(value.extend(0.).extend(0.))
+	} else if let Some(&value) = any.downcast_ref::<Vec3>() {
+		Some(value.extend(0.))
+	} else if let Some(&value) = any.downcast_ref::<Vec4>() {
+		Some(value)
+	} else {
+		any.downcast_ref::<Color>().map(|color| color.to_linear().to_vec4())
+	}
+}
+
+/// Writes the properties of generic materials using an `ExtendedMaterial<B, PropertyUniformsExtension<U>>` to the extension when they're loaded or modified.
+pub fn write_property_uniforms<B: Material, U: PropertyUniforms>(
+	mut asset_events: MessageReader<AssetEvent<GenericMaterial>>,
+	generic_materials: Res<Assets<GenericMaterial>>,
+	mut materials: ResMut<Assets<ExtendedMaterial<B, PropertyUniformsExtension<U>>>>,
+) {
+	for event in asset_events.read() {
+		let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = event else { continue };
+		let Some(generic_material) = generic_materials.get(*id) else { continue };
+		let Ok(handle) = generic_material
+			.handle
+			.inner()
+			.clone()
+			.try_typed::<ExtendedMaterial<B, PropertyUniformsExtension<U>>>()
+		else {
+			continue;
+		};
+
+		let values = PropertyUniformsExtension::<U>::values_of(generic_material);
+		// Only touching the material when something changed avoids re-uploading it every time the generic material is modified.
+		if materials.get(&handle).is_none_or(|material| material.extension.values == values) {
+			continue;
+		}
+		if let Some(mut material) = materials.get_mut(&handle) {
+			material.extension.values = values;
+		}
+	}
+}
+
+/// Adds `ExtendedMaterial<B, PropertyUniformsExtension<U>>` as a generic material with the given shorthand, and writes the properties listed by `U` to it.
+///
+/// # Examples
+/// ```ignore
+/// app.add_plugins(PropertyUniformsPlugin::<StandardMaterial, Wetness>::new("WetMaterial"));
+/// ```
+/// ```toml
+/// type = "WetMaterial"
+///
+/// [material.base]
+/// base_color_texture = "cobblestone.png"
+///
+/// [properties]
+/// wetness = 0.6
+/// ```
+pub struct PropertyUniformsPlugin<B, U> {
+	pub shorthand: String,
+	pub marker: PhantomData<fn() -> (B, U)>,
+}
+impl<B, U> PropertyUniformsPlugin<B, U> {
+	pub fn new(shorthand: impl Into<String>) -> Self {
+		Self {
+			shorthand: shorthand.into(),
+			marker: PhantomData,
+		}
+	}
+}
+impl<B, U> Plugin for PropertyUniformsPlugin<B, U>
+where
+	B: Material + FromReflect + Typed + FromWorld + GetTypeRegistration,
+	U: PropertyUniforms,
+	ExtendedMaterial<B, PropertyUniformsExtension<U>>: Material,
+{
+	fn build(&self, app: &mut App) {
+		#[rustfmt::skip]
+		app
+			.add_plugins(MaterialPlugin::<ExtendedMaterial<B, PropertyUniformsExtension<U>>>::default())
+			.register_extended_generic_material::<B, PropertyUniformsExtension<U>>(self.shorthand.clone())
+			.add_systems(PreUpdate, write_property_uniforms::<B, U>)
+		;
+	}
+}
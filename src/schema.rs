@@ -0,0 +1,178 @@
+//! Machine-readable description of the materials, shorthands, and properties registered in an app, for external tools like web-based material editors.
+
+use std::any::TypeId;
+use std::collections::{BTreeMap, VecDeque};
+
+use bevy::{
+	prelude::*,
+	reflect::{TypeInfo, TypeRegistry, enums::VariantInfo},
+};
+use serde::Serialize;
+
+#[cfg(feature = "bevy_pbr")]
+use crate::generic_material::{GenericMaterialShorthands, ReflectGenericMaterial};
+use crate::material_property::MaterialPropertyRegistry;
+
+/// Everything registered in an app that material files can refer to, created with [`MaterializeSchema::from_world`].
+///
+/// Every type reachable from a material or property is described once in [`types`](Self::types), and referred to everywhere else by its type path.
+/// Serialize it with any serde serializer, e.g. `serde_json::to_string_pretty(&schema)`.
+///
+/// Fields set with [`register_generic_material_foreign_field`](crate::MaterializeAppExt::register_generic_material_foreign_field) aren't included,
+/// since their types aren't known to reflection.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct MaterializeSchema {
+	/// Type paths of every registered generic material.
+	#[cfg(feature = "bevy_pbr")]
+	pub materials: Vec<String>,
+	/// Material type shorthands, and the type paths they refer to.
+	#[cfg(feature = "bevy_pbr")]
+	pub shorthands: BTreeMap<String, String>,
+	/// Registered property keys, and the type paths of their values.
+	pub properties: BTreeMap<String, String>,
+	/// Descriptions of every type referred to, keyed by type path.
+	pub types: BTreeMap<String, TypeSchema>,
+}
+impl MaterializeSchema {
+	/// Describes what's registered in `world`, which must have had [`MaterializePlugin`](crate::MaterializePlugin) added to it.
+	pub fn from_world(world: &World) -> Self {
+		let registry = world.resource::<AppTypeRegistry>().read();
+		let mut schema = Self::default();
+		let mut queue = VecDeque::new();
+
+		#[cfg(feature = "bevy_pbr")]
+		{
+			for (registration, _) in registry.iter_with_data::<ReflectGenericMaterial>() {
+				schema.materials.push(registration.type_info().type_path().to_string());
+				queue.push_back(registration.type_id());
+			}
+			schema.materials.sort();
+
+			let shorthands = world.resource::<GenericMaterialShorthands>().values.read().unwrap();
+			for (shorthand, registration) in shorthands.iter() {
+				schema
+					.shorthands
+					.insert(shorthand.clone(), registration.type_info().type_path().to_string());
+			}
+		}
+
+		let properties = world.resource::<MaterialPropertyRegistry>().inner.read().unwrap();
+		for (key, &type_id) in properties.iter() {
+			let Some(registration) = registry.get(type_id) else { continue };
+			schema.properties.insert(key.clone(), registration.type_info().type_path().to_string());
+			queue.push_back(type_id);
+		}
+
+		while let Some(type_id) = queue.pop_front() {
+			schema.add_type(&registry, type_id, &mut queue);
+		}
+
+		schema
+	}
+
+	/// Describes the type with id `type_id` if it isn't already, queueing the types it refers to.
+	fn add_type(&mut self, registry: &TypeRegistry, type_id: TypeId, queue: &mut VecDeque<TypeId>) {
+		let Some(registration) = registry.get(type_id) else { return };
+		let type_info = registration.type_info();
+		if self.types.contains_key(type_info.type_path()) {
+			return;
+		}
+
+		let mut reference = |type_id: TypeId, type_path: &str| {
+			queue.push_back(type_id);
+			type_path.to_string()
+		};
+
+		let schema = match type_info {
+			TypeInfo::Struct(info) => TypeSchema::Struct {
+				fields: info
+					.iter()
+					.map(|field| (field.name().to_string(), reference(field.type_id(), field.type_path())))
+					.collect(),
+			},
+			TypeInfo::TupleStruct(info) => TypeSchema::Tuple {
+				fields: info.iter().map(|field| reference(field.type_id(), field.type_path())).collect(),
+			},
+			TypeInfo::Tuple(info) => TypeSchema::Tuple {
+				fields: info.iter().map(|field| reference(field.type_id(), field.type_path())).collect(),
+			},
+			TypeInfo::List(info) => TypeSchema::List {
+				item: reference(info.item_ty().id(), info.item_ty().path()),
+				len: None,
+			},
+			TypeInfo::Array(info) => TypeSchema::List {
+				item: reference(info.item_ty().id(), info.item_ty().path()),
+				len: Some(info.capacity()),
+			},
+			TypeInfo::Set(info) => TypeSchema::List {
+				item: reference(info.value_ty().id(), info.value_ty().path()),
+				len: None,
+			},
+			TypeInfo::Map(info) => TypeSchema::Map {
+				key: reference(info.key_ty().id(), info.key_ty().path()),
+				value: reference(info.value_ty().id(), info.value_ty().path()),
+			},
+			TypeInfo::Enum(info) => TypeSchema::Enum {
+				variants: info
+					.iter()
+					.map(|variant| {
+						let schema = match variant {
+							VariantInfo::Unit(_) => VariantSchema::Unit,
+							VariantInfo::Tuple(variant) => VariantSchema::Tuple {
+								fields: variant.iter().map(|field| reference(field.type_id(), field.type_path())).collect(),
+							},
+							VariantInfo::Struct(variant) => VariantSchema::Struct {
+								fields: variant
+									.iter()
+									.map(|field| (field.name().to_string(), reference(field.type_id(), field.type_path())))
+									.collect(),
+							},
+						};
+						(variant.name().to_string(), schema)
+					})
+					.collect(),
+			},
+			TypeInfo::Opaque(_) => TypeSchema::Opaque,
+		};
+
+		self.types.insert(type_info.type_path().to_string(), schema);
+	}
+}
+
+/// Description of a type in a [`MaterializeSchema`]. Types of fields and items are referred to by type path.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TypeSchema {
+	/// Written as a table, with fields in declared order.
+	Struct {
+		fields: Vec<(String, String)>,
+	},
+	/// Tuples and tuple structs, written as arrays.
+	Tuple {
+		fields: Vec<String>,
+	},
+	/// Lists, sets, and arrays, which have a fixed `len`.
+	List {
+		item: String,
+		len: Option<usize>,
+	},
+	Map {
+		key: String,
+		value: String,
+	},
+	/// Variants in declared order.
+	Enum {
+		variants: Vec<(String, VariantSchema)>,
+	},
+	/// Types without reflected structure, such as numbers, strings, and asset handles.
+	Opaque,
+}
+
+/// Description of an enum variant in a [`TypeSchema::Enum`].
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VariantSchema {
+	Unit,
+	Tuple { fields: Vec<String> },
+	Struct { fields: Vec<(String, String)> },
+}
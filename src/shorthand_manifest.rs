@@ -0,0 +1,92 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::generic_material::GenericMaterialShorthands;
+
+/// Material type shorthands declared in data, usually read from a `materialize.shorthands.toml` file, so they can be changed without recompiling.
+///
+/// Adding this to [`MaterializePlugin`](crate::MaterializePlugin) registers every declared shorthand once all plugins are built,
+/// so the materials they refer to can be registered after the plugin is added.
+///
+/// # Examples
+/// ```toml
+/// [shorthands]
+/// Water = "my_game::materials::WaterMaterial"
+/// Foliage = "FoliageMaterial"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ShorthandManifest {
+	/// Shorthands, and the full or short type paths they refer to.
+	#[serde(default)]
+	pub shorthands: BTreeMap<String, String>,
+}
+impl ShorthandManifest {
+	/// Where the manifest is conventionally stored, relative to the working directory.
+	pub const DEFAULT_PATH: &str = "assets/materialize.shorthands.toml";
+
+	/// Parses a manifest from a TOML string.
+	pub fn from_toml(s: &str) -> Result<Self, ShorthandManifestError> {
+		Ok(toml::from_str(s)?)
+	}
+
+	/// Reads and parses a manifest file from disk.
+	pub fn read(path: impl AsRef<Path>) -> Result<Self, ShorthandManifestError> {
+		Self::from_toml(&fs::read_to_string(path)?)
+	}
+
+	/// Creates a manifest of every shorthand currently registered, such as for writing out the shorthands registered in code.
+	pub fn from_shorthands(shorthands: &GenericMaterialShorthands) -> Self {
+		Self {
+			shorthands: shorthands
+				.values
+				.read()
+				.unwrap()
+				.iter()
+				.map(|(shorthand, registration)| (shorthand.clone(), registration.type_info().type_path().to_string()))
+				.collect(),
+		}
+	}
+
+	/// Serializes this manifest into a TOML string.
+	pub fn to_toml(&self) -> Result<String, ShorthandManifestError> {
+		Ok(toml::to_string_pretty(self)?)
+	}
+
+	/// Registers every declared shorthand. Types are looked up in the app's type registry by their full or short type path.
+	pub fn register(&self, app: &mut App) -> Result<(), ShorthandManifestError> {
+		let type_registry = app.world().resource::<AppTypeRegistry>().read();
+		let shorthands = app.world().resource::<GenericMaterialShorthands>();
+
+		for (shorthand, ty) in &self.shorthands {
+			let Some(registration) = type_registry
+				.get_with_type_path(ty)
+				.or_else(|| type_registry.get_with_short_type_path(ty))
+			else {
+				return Err(ShorthandManifestError::TypeNotFound {
+					shorthand: shorthand.clone(),
+					ty: ty.clone(),
+				});
+			};
+
+			shorthands.insert(shorthand.clone(), registration.clone());
+		}
+
+		Ok(())
+	}
+}
+
+/// Errors that may occur when reading or registering a [`ShorthandManifest`].
+#[derive(Error, Debug)]
+pub enum ShorthandManifestError {
+	#[error("{0}")]
+	Io(#[from] io::Error),
+	#[error("Deserialize error: {0}")]
+	Deserialize(#[from] toml::de::Error),
+	#[error("Serialize error: {0}")]
+	Serialize(#[from] toml::ser::Error),
+	#[error("Shorthand {shorthand} refers to type {ty}, which isn't registered in the type registry")]
+	TypeNotFound { shorthand: String, ty: String },
+}
@@ -0,0 +1,142 @@
+use std::any::TypeId;
+
+use bevy::{
+	light::{EnvironmentMapLight, Skybox},
+	platform::collections::HashSet,
+	prelude::*,
+};
+
+use crate::{erased_material::ErasedMaterialHandle, generic_material::ReflectGenericMaterial, prelude::*};
+
+/// Generic material that drives a camera's [`Skybox`] and [`EnvironmentMapLight`], so environment setups can live in material files.
+///
+/// When applied to an entity with [`GenericMaterial3d`], this inserts [`SkyboxMaterial3d`], which keeps the entity's skybox and environment map light in sync with the material.
+/// Images should be cubemaps, such as `.ktx2` files.
+///
+/// Registered with the shorthand `Skybox` by [`SkyboxMaterialPlugin`].
+///
+/// # Examples
+/// ```toml
+/// type = "Skybox"
+///
+/// [material]
+/// image = "sky_cubemap.ktx2"
+/// brightness = 1000.0
+/// diffuse_map = "sky_diffuse.ktx2"
+/// specular_map = "sky_specular.ktx2"
+/// intensity = 900.0
+/// ```
+#[derive(Asset, Reflect, Debug, Clone)]
+#[reflect(Default, Debug, Clone)]
+pub struct SkyboxMaterial {
+	/// The cubemap shown as the skybox. If [`None`], no [`Skybox`] is inserted.
+	pub image: Option<Handle<Image>>,
+	/// Scale factor applied to the skybox image, see [`Skybox::brightness`]. (Default: `1000`)
+	pub brightness: f32,
+	/// The diffuse cubemap of the [`EnvironmentMapLight`]. The environment map light is only inserted if both this and [`specular_map`](Self::specular_map) are set.
+	pub diffuse_map: Option<Handle<Image>>,
+	/// The specular cubemap of the [`EnvironmentMapLight`].
+	pub specular_map: Option<Handle<Image>>,
+	/// Scale factor applied to the environment map light, see [`EnvironmentMapLight::intensity`]. (Default: `1000`)
+	pub intensity: f32,
+	/// Rotation applied to both the skybox and the environment map light.
+	pub rotation: Quat,
+}
+impl Default for SkyboxMaterial {
+	fn default() -> Self {
+		Self {
+			image: None,
+			brightness: 1000.,
+			diffuse_map: None,
+			specular_map: None,
+			intensity: 1000.,
+			rotation: Quat::IDENTITY,
+		}
+	}
+}
+impl SkyboxMaterial {
+	/// Returns the [`Skybox`] this material describes, if it has an image.
+	pub fn skybox(&self) -> Option<Skybox> {
+		Some(Skybox {
+			image: Some(self.image.clone()?),
+			brightness: self.brightness,
+			rotation: self.rotation,
+		})
+	}
+
+	/// Returns the [`EnvironmentMapLight`] this material describes, if it has both of its maps.
+	pub fn environment_map_light(&self) -> Option<EnvironmentMapLight> {
+		Some(EnvironmentMapLight {
+			diffuse_map: self.diffuse_map.clone()?,
+			specular_map: self.specular_map.clone()?,
+			intensity: self.intensity,
+			rotation: self.rotation,
+			..default()
+		})
+	}
+}
+
+/// Inserted by [`SkyboxMaterial`]s in place of a mesh material component. Stores a handle to the applied material.
+///
+/// NOTE: Removing the material removes the entity's [`Skybox`] and [`EnvironmentMapLight`], even if they were inserted some other way.
+#[derive(Component, Reflect, Debug, Clone, PartialEq, Eq, Default, Deref, DerefMut)]
+#[reflect(Component, Default)]
+pub struct SkyboxMaterial3d(pub Handle<SkyboxMaterial>);
+
+/// Inserts the [`Skybox`] and [`EnvironmentMapLight`] of entities with a [`SkyboxMaterial3d`] when it's added or its material is loaded or modified.
+pub fn sync_skybox_materials(
+	mut commands: Commands,
+	query: Query<(Entity, Ref<SkyboxMaterial3d>)>,
+	mut asset_events: MessageReader<AssetEvent<SkyboxMaterial>>,
+	skybox_materials: Res<Assets<SkyboxMaterial>>,
+) {
+	let modified: HashSet<AssetId<SkyboxMaterial>> = asset_events
+		.read()
+		.filter_map(|event| match event {
+			AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+			_ => None,
+		})
+		.collect();
+
+	for (entity, holder) in &query {
+		if !holder.is_changed() && !modified.contains(&holder.id()) {
+			continue;
+		}
+		let Some(skybox_material) = skybox_materials.get(&holder.0) else { continue };
+
+		let mut entity = commands.entity(entity);
+		match skybox_material.skybox() {
+			Some(skybox) => entity.insert(skybox),
+			None => entity.remove::<Skybox>(),
+		};
+		match skybox_material.environment_map_light() {
+			Some(environment_map_light) => entity.insert(environment_map_light),
+			None => entity.remove::<EnvironmentMapLight>(),
+		};
+	}
+}
+
+/// Adds [`SkyboxMaterial`], registers it as a generic material with the shorthand `Skybox`, and keeps the skyboxes of entities using it in sync.
+pub struct SkyboxMaterialPlugin;
+impl Plugin for SkyboxMaterialPlugin {
+	fn build(&self, app: &mut App) {
+		#[rustfmt::skip]
+		app
+			.init_asset::<SkyboxMaterial>()
+			.register_type::<SkyboxMaterial3d>()
+			.register_generic_material_shorthand::<SkyboxMaterial>("Skybox")
+			.add_systems(PreUpdate, sync_skybox_materials.after(crate::insert_generic_materials))
+		;
+
+		// `SkyboxMaterial` isn't a mesh material, so it can't go through `register_generic_material`.
+		let mut type_registry = app.world().resource::<AppTypeRegistry>().write();
+		type_registry.register::<SkyboxMaterial>();
+		type_registry
+			.get_mut(TypeId::of::<SkyboxMaterial>())
+			.unwrap()
+			.insert(ReflectGenericMaterial {
+				default_value: Box::new(SkyboxMaterial::default()),
+				new_handle: |handle| ErasedMaterialHandle::new_skybox(handle.typed_debug_checked()),
+			});
+	}
+}
@@ -0,0 +1,194 @@
+use std::any::TypeId;
+
+use bevy::{
+	platform::collections::HashMap,
+	prelude::*,
+	reflect::{ReflectMut, ReflectRef},
+};
+
+use crate::{lerp::lerp_reflect, prelude::*};
+
+/// The material fields and properties captured by [`MaterialSnapshot`], set with [`MaterialSnapshotAppExt`].
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MaterialSnapshotFields {
+	/// Fields captured for each material type, keyed by the material's [`TypeId`].
+	pub fields: HashMap<TypeId, Vec<String>>,
+	/// Properties captured for every material.
+	pub properties: Vec<String>,
+}
+
+pub trait MaterialSnapshotAppExt {
+	/// Adds `field` of materials of type `M` to the fields captured by [`MaterialSnapshot::capture`].
+	fn register_material_snapshot_field<M: Material>(&mut self, field: impl Into<String>) -> &mut Self;
+
+	/// Adds the property `key` to the properties captured by [`MaterialSnapshot::capture`].
+	fn register_material_snapshot_property(&mut self, key: impl Into<String>) -> &mut Self;
+}
+impl MaterialSnapshotAppExt for App {
+	fn register_material_snapshot_field<M: Material>(&mut self, field: impl Into<String>) -> &mut Self {
+		self.world_mut()
+			.get_resource_or_init::<MaterialSnapshotFields>()
+			.fields
+			.entry(TypeId::of::<M>())
+			.or_default()
+			.push(field.into());
+		self
+	}
+
+	fn register_material_snapshot_property(&mut self, key: impl Into<String>) -> &mut Self {
+		self.world_mut()
+			.get_resource_or_init::<MaterialSnapshotFields>()
+			.properties
+			.push(key.into());
+		self
+	}
+}
+
+/// A captured field or property, along with its name.
+pub type SnapshotEntry = (String, Box<dyn PartialReflect>);
+
+/// Compact copy of the dynamic parts of a [`GenericMaterial`], for syncing material state (such as a damage glow) in networked or rollback games.
+///
+/// Only the fields and properties registered with [`MaterialSnapshotAppExt`] are captured.
+/// Values can be sent over the network with Bevy's reflection serializers, and [`diff`](Self::diff) makes it easy to only send what changed.
+///
+/// # Examples
+/// ```ignore
+/// app.register_material_snapshot_field::<StandardMaterial>("emissive");
+///
+/// // Later, on the client, between two received snapshots.
+/// apply_snapshot(world, &generic_material, &previous, &latest, 0.5);
+/// ```
+#[derive(Debug, Default)]
+pub struct MaterialSnapshot {
+	pub fields: Vec<SnapshotEntry>,
+	pub properties: Vec<SnapshotEntry>,
+}
+impl Clone for MaterialSnapshot {
+	fn clone(&self) -> Self {
+		Self {
+			fields: clone_entries(&self.fields),
+			properties: clone_entries(&self.properties),
+		}
+	}
+}
+impl MaterialSnapshot {
+	/// Captures the registered fields and properties of a loaded [`GenericMaterial`].
+	///
+	/// Returns [`None`] if the generic material or its inner material isn't loaded.
+	pub fn capture(world: &World, generic_material: &Handle<GenericMaterial>) -> Option<Self> {
+		let registered = world.get_resource::<MaterialSnapshotFields>()?;
+		let generic_material = world.resource::<Assets<GenericMaterial>>().get(generic_material)?;
+		let material = generic_material.handle.get_from_world(world)?;
+
+		let mut snapshot = Self::default();
+
+		let fields = registered.fields.get(&generic_material.handle.inner().type_id());
+		if let (ReflectRef::Struct(s), Some(fields)) = (material.reflect_ref(), fields) {
+			for name in fields {
+				let Some(value) = s.field(name) else { continue };
+				snapshot.fields.push((name.clone(), clone_value(value)));
+			}
+		}
+
+		for key in &registered.properties {
+			let Some(value) = generic_material.properties.get(key) else { continue };
+			snapshot.properties.push((key.clone(), clone_value(value.as_partial_reflect())));
+		}
+
+		Some(snapshot)
+	}
+
+	/// Returns a snapshot of only the values that differ from `previous`.
+	///
+	/// Applying the result on top of `previous` produces this snapshot.
+	pub fn diff(&self, previous: &Self) -> Self {
+		fn changed(entries: &[SnapshotEntry], previous: &[SnapshotEntry]) -> Vec<SnapshotEntry> {
+			entries
+				.iter()
+				.filter(|(name, value)| {
+					!previous
+						.iter()
+						.any(|(previous_name, previous_value)| previous_name == name && previous_value.reflect_partial_eq(&**value) == Some(true))
+				})
+				.map(|(name, value)| (name.clone(), clone_value(&**value)))
+				.collect()
+		}
+
+		Self {
+			fields: changed(&self.fields, &previous.fields),
+			properties: changed(&self.properties, &previous.properties),
+		}
+	}
+
+	/// Interpolates from this snapshot towards `other` by `t` with [`lerp_reflect`]. Values only in this snapshot are kept as-is.
+	pub fn interpolate(&self, other: &Self, t: f32) -> Self {
+		fn interpolate(entries: &[SnapshotEntry], other: &[SnapshotEntry], t: f32) -> Vec<SnapshotEntry> {
+			entries
+				.iter()
+				.map(|(name, value)| {
+					let mut value = clone_value(&**value);
+					if let Some((_, other_value)) = other.iter().find(|(other_name, _)| other_name == name) {
+						lerp_reflect(&mut *value, &**other_value, t);
+					}
+					(name.clone(), value)
+				})
+				.collect()
+		}
+
+		Self {
+			fields: interpolate(&self.fields, &other.fields, t),
+			properties: interpolate(&self.properties, &other.properties, t),
+		}
+	}
+
+	/// Writes the values of this snapshot into a [`GenericMaterial`] and its inner material.
+	pub fn apply(self, world: &mut World, generic_material: &Handle<GenericMaterial>) {
+		let handle = {
+			let mut generic_materials = world.resource_mut::<Assets<GenericMaterial>>();
+			let Some(mut generic_material) = generic_materials.get_mut(generic_material) else { return };
+
+			for (key, value) in &self.properties {
+				let Some(property) = generic_material.properties.get_mut(key) else { continue };
+				if let Err(err) = property.try_apply(&**value) {
+					error!("Failed to apply snapshot of property {key}: {err}");
+				}
+			}
+			generic_material.clear_computed_properties();
+
+			generic_material.handle.clone()
+		};
+
+		if self.fields.is_empty() {
+			return;
+		}
+
+		handle.asset_scope_mut(
+			world,
+			Box::new(move |_, material| {
+				let Some(material) = material else { return };
+				let ReflectMut::Struct(s) = material.reflect_mut() else { return };
+
+				for (name, value) in &self.fields {
+					let Some(field) = s.field_mut(name) else { continue };
+					if let Err(err) = field.try_apply(&**value) {
+						error!("Failed to apply snapshot of field {name}: {err}");
+					}
+				}
+			}),
+		);
+	}
+}
+
+/// Interpolates between two [`MaterialSnapshot`]s by `t` and applies the result to a [`GenericMaterial`].
+pub fn apply_snapshot(world: &mut World, generic_material: &Handle<GenericMaterial>, from: &MaterialSnapshot, to: &MaterialSnapshot, t: f32) {
+	from.interpolate(to, t).apply(world, generic_material);
+}
+
+fn clone_value(value: &dyn PartialReflect) -> Box<dyn PartialReflect> {
+	value.reflect_clone().map_or_else(|_| value.to_dynamic(), Reflect::into_partial_reflect)
+}
+
+fn clone_entries(entries: &[SnapshotEntry]) -> Vec<SnapshotEntry> {
+	entries.iter().map(|(name, value)| (name.clone(), clone_value(&**value))).collect()
+}
@@ -0,0 +1,89 @@
+use bevy::{
+	asset::embedded_asset,
+	pbr::{ExtendedMaterial, MaterialExtension},
+	prelude::*,
+	render::render_resource::AsBindGroup,
+	shader::ShaderRef,
+};
+
+use crate::MaterializeAppExt;
+
+/// Terrain material that blends 4 layer textures by the channels of a splat map, on top of a [`StandardMaterial`].
+///
+/// Registered with the shorthand `SplatMaterial` by [`SplatMaterialPlugin`].
+///
+/// # Examples
+/// ```toml
+/// type = "SplatMaterial"
+///
+/// [material.base]
+/// perceptual_roughness = 1
+///
+/// [material.extension]
+/// splat_map = "terrain_splat.png"
+/// layer_0 = "grass.png"
+/// layer_1 = "dirt.png"
+/// layer_2 = "rock.png"
+/// layer_3 = "sand.png"
+/// tiling = [16, 16, 8, 16]
+/// ```
+pub type SplatMaterial = ExtendedMaterial<StandardMaterial, SplatExtension>;
+
+/// The extension part of [`SplatMaterial`].
+///
+/// Layer textures should use a repeating sampler for tiling to work.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct SplatExtension {
+	/// How many times each layer repeats across the mesh's UVs, one component per layer. (Default: `[1, 1, 1, 1]`)
+	#[uniform(100)]
+	pub tiling: Vec4,
+
+	/// How much of each layer is shown, in the red, green, blue, and alpha channels respectively for layers 0 to 3.
+	#[texture(101)]
+	#[sampler(102)]
+	pub splat_map: Option<Handle<Image>>,
+
+	#[texture(103)]
+	#[sampler(104)]
+	pub layer_0: Option<Handle<Image>>,
+	#[texture(105)]
+	#[sampler(106)]
+	pub layer_1: Option<Handle<Image>>,
+	#[texture(107)]
+	#[sampler(108)]
+	pub layer_2: Option<Handle<Image>>,
+	#[texture(109)]
+	#[sampler(110)]
+	pub layer_3: Option<Handle<Image>>,
+}
+impl Default for SplatExtension {
+	fn default() -> Self {
+		Self {
+			tiling: Vec4::ONE,
+			splat_map: None,
+			layer_0: None,
+			layer_1: None,
+			layer_2: None,
+			layer_3: None,
+		}
+	}
+}
+impl MaterialExtension for SplatExtension {
+	fn fragment_shader() -> ShaderRef {
+		"embedded://bevy_materialize/splat/splat.wgsl".into()
+	}
+}
+
+/// Adds [`SplatMaterial`] and registers it as a generic material with the shorthand `SplatMaterial`.
+pub struct SplatMaterialPlugin;
+impl Plugin for SplatMaterialPlugin {
+	fn build(&self, app: &mut App) {
+		embedded_asset!(app, "splat.wgsl");
+
+		#[rustfmt::skip]
+		app
+			.add_plugins(MaterialPlugin::<SplatMaterial>::default())
+			.register_extended_generic_material::<StandardMaterial, SplatExtension>("SplatMaterial")
+		;
+	}
+}
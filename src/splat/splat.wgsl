@@ -0,0 +1,63 @@
+#import bevy_pbr::{
+	pbr_fragment::pbr_input_from_standard_material,
+	pbr_functions::alpha_discard,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+	prepass_io::{VertexOutput, FragmentOutput},
+	pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+	forward_io::{VertexOutput, FragmentOutput},
+	pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+
+struct SplatMaterial {
+	tiling: vec4f,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> material: SplatMaterial;
+@group(#{MATERIAL_BIND_GROUP}) @binding(101) var splat_map: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(102) var splat_map_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(103) var layer_0: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(104) var layer_0_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(105) var layer_1: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(106) var layer_1_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(107) var layer_2: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(108) var layer_2_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(109) var layer_3: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(110) var layer_3_sampler: sampler;
+
+@fragment
+fn fragment(
+	in: VertexOutput,
+	@builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+	var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+	// Normalize so the layers always add up to full coverage.
+	var weights = textureSample(splat_map, splat_map_sampler, in.uv);
+	weights /= max(weights.r + weights.g + weights.b + weights.a, 0.0001);
+
+	let color =
+		textureSample(layer_0, layer_0_sampler, in.uv * material.tiling.x) * weights.r +
+		textureSample(layer_1, layer_1_sampler, in.uv * material.tiling.y) * weights.g +
+		textureSample(layer_2, layer_2_sampler, in.uv * material.tiling.z) * weights.b +
+		textureSample(layer_3, layer_3_sampler, in.uv * material.tiling.w) * weights.a;
+
+	pbr_input.material.base_color *= color;
+	pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+	let out = deferred_output(in, pbr_input);
+#else
+	var out: FragmentOutput;
+	out.color = apply_pbr_lighting(pbr_input);
+	out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+	return out;
+}
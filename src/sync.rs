@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Copies the [`GenericMaterial3d`] of each source entity in `source` onto its counterpart in `target`.
+/// Counterparts of source entities without one have theirs removed.
+///
+/// `entities` is a list of `(source entity, target entity)` pairs. Components are only written if they differ, so this is cheap to run every frame.
+///
+/// Both worlds should share the same [`AssetServer`] so that handles stay valid. If `target` has its own [`MaterializePlugin`] systems running,
+/// they'll take care of inserting the actual materials, otherwise [`GenericMaterial3d`] is just carried over as data.
+pub fn sync_generic_materials(source: &World, target: &mut World, entities: impl IntoIterator<Item = (Entity, Entity)>) {
+	for (source_entity, target_entity) in entities {
+		let Ok(mut target_entity) = target.get_entity_mut(target_entity) else { continue };
+
+		match source.get::<GenericMaterial3d>(source_entity) {
+			Some(generic_material_3d) => {
+				if target_entity.get::<GenericMaterial3d>() != Some(generic_material_3d) {
+					target_entity.insert(generic_material_3d.clone());
+				}
+			}
+			None => {
+				target_entity.remove::<GenericMaterial3d>();
+			}
+		}
+	}
+}
@@ -0,0 +1,113 @@
+use bevy::{
+	asset::{AssetPath, UntypedAssetId},
+	platform::collections::HashMap,
+	prelude::*,
+	reflect::GetPath,
+};
+
+use crate::{erased_material::ErasedMaterialHandle, generic_material::GenericMaterialApplied};
+
+/// Replaces a single texture in the material applied by an entity's [`GenericMaterial3d`](crate::GenericMaterial3d), such as for skin variants.
+///
+/// Entities with the same material, field, and texture share a single copy of the material, so variants don't need their own material files.
+/// `field` is a reflect path, so nested fields like `extension.layer_0` are supported.
+///
+/// # Examples
+/// ```ignore
+/// commands.spawn((
+///     Mesh3d(mesh),
+///     GenericMaterial3d(asset_server.load("materials/soldier.toml")),
+///     GenericMaterialTextureOverride::new("base_color_texture", "textures/soldier_red.png"),
+/// ));
+/// ```
+#[derive(Component, Debug, Clone)]
+pub struct GenericMaterialTextureOverride {
+	pub field: String,
+	pub texture: TextureOverrideSource,
+}
+impl GenericMaterialTextureOverride {
+	pub fn new(field: impl Into<String>, texture: impl Into<TextureOverrideSource>) -> Self {
+		Self {
+			field: field.into(),
+			texture: texture.into(),
+		}
+	}
+
+	/// Returns a copy of `material` with this override applied, reusing a copy from [`TextureOverrideCache`] if one exists.
+	pub fn instantiate(&self, world: &mut World, material: &ErasedMaterialHandle) -> Option<ErasedMaterialHandle> {
+		let texture = match &self.texture {
+			TextureOverrideSource::Handle(handle) => handle.clone(),
+			TextureOverrideSource::Path(path) => world.resource::<AssetServer>().load(path.clone()),
+		};
+
+		let key = (material.id(), self.field.clone(), texture.id());
+		if let Some(handle) = world.resource::<TextureOverrideCache>().materials.get(&key) {
+			return Some(handle.clone());
+		}
+
+		let Some(mut copy) = material.clone_material(world) else {
+			error!("Failed to copy {material:?} for a texture override, is it a registered generic material?");
+			return None;
+		};
+
+		let applied = match copy.as_reflect_mut().reflect_path_mut(self.field.as_str()) {
+			Ok(field) => field.try_apply(&Some(texture.clone())).is_ok() || field.try_apply(&texture).is_ok(),
+			Err(_) => false,
+		};
+		if !applied {
+			error!(
+				"Failed to override texture `{}` of {}, the field doesn't exist or isn't an image handle",
+				self.field,
+				copy.reflect_short_type_path()
+			);
+			return None;
+		}
+
+		let handle = copy.add_asset(world.resource::<AssetServer>());
+		world.resource_mut::<TextureOverrideCache>().materials.insert(key, handle.clone());
+
+		Some(handle)
+	}
+}
+
+/// Where the texture of a [`GenericMaterialTextureOverride`] comes from.
+#[derive(Debug, Clone)]
+pub enum TextureOverrideSource {
+	Handle(Handle<Image>),
+	/// Loaded through the [`AssetServer`] when the override is applied.
+	Path(AssetPath<'static>),
+}
+impl From<Handle<Image>> for TextureOverrideSource {
+	fn from(value: Handle<Image>) -> Self {
+		Self::Handle(value)
+	}
+}
+impl From<AssetPath<'static>> for TextureOverrideSource {
+	fn from(value: AssetPath<'static>) -> Self {
+		Self::Path(value)
+	}
+}
+impl From<&'static str> for TextureOverrideSource {
+	fn from(value: &'static str) -> Self {
+		Self::Path(value.into())
+	}
+}
+
+/// Material copies made for [`GenericMaterialTextureOverride`]s, keyed by the original material, field, and texture.
+///
+/// Entries are kept until removed, so clear this if you're done with a set of variants.
+#[derive(Resource, Debug, Default)]
+pub struct TextureOverrideCache {
+	pub materials: HashMap<(UntypedAssetId, String, AssetId<Image>), ErasedMaterialHandle>,
+}
+
+/// Re-applies materials of entities whose [`GenericMaterialTextureOverride`] was changed or removed.
+pub fn reapply_texture_overrides(
+	mut commands: Commands,
+	changed_query: Query<Entity, (Changed<GenericMaterialTextureOverride>, With<GenericMaterialApplied>)>,
+	mut removed: RemovedComponents<GenericMaterialTextureOverride>,
+) {
+	for entity in changed_query.iter().chain(removed.read()) {
+		commands.entity(entity).try_remove::<GenericMaterialApplied>();
+	}
+}
@@ -0,0 +1,109 @@
+use bevy::{math::Affine2, prelude::*, reflect::GetPath};
+
+use crate::{erased_material::ErasedMaterialHandle, prelude::*};
+
+impl GenericMaterial {
+	/// Material property that sets the material's `uv_transform` field to show only a region of its textures, for texture sheets.
+	///
+	/// Works with [`StandardMaterial`] and any other material with a `uv_transform: Affine2` field.
+	///
+	/// # Examples
+	/// ```toml
+	/// [properties.uv_region.Pixels]
+	/// min = [64.0, 0.0]
+	/// max = [128.0, 64.0]
+	/// sheet_size = [256.0, 256.0]
+	/// ```
+	pub const UV_REGION: MaterialProperty<UvRegion> = MaterialProperty::new("uv_region");
+
+	/// Material property that gives each entity using the material its own copy, with the `uv_transform` offset by an amount picked from the entity's id.
+	///
+	/// This breaks up visible tiling on repeated props without authoring unique materials, at the cost of those entities no longer batching together.
+	/// Works with [`StandardMaterial`] and any other material with a `uv_transform: Affine2` field. See [`random_uv_offset`] for how offsets are picked.
+	///
+	/// # Examples
+	/// ```toml
+	/// [properties]
+	/// random_uv_offset = true
+	/// ```
+	pub const RANDOM_UV_OFFSET: MaterialProperty<bool> = MaterialProperty::new("random_uv_offset");
+}
+
+/// Region of a texture for the [`UV_REGION`](GenericMaterial::UV_REGION) property.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub enum UvRegion {
+	/// Normalized UV coordinates, from `0` to `1`.
+	Uv { min: Vec2, max: Vec2 },
+	/// Pixel coordinates in a texture sheet that's `sheet_size` pixels large.
+	Pixels { min: Vec2, max: Vec2, sheet_size: Vec2 },
+}
+impl UvRegion {
+	/// Returns the region in normalized UV coordinates as `(min, max)`.
+	pub fn normalized(self) -> (Vec2, Vec2) {
+		match self {
+			Self::Uv { min, max } => (min, max),
+			Self::Pixels { min, max, sheet_size } => (min / sheet_size, max / sheet_size),
+		}
+	}
+
+	/// Returns the transform that maps the whole `0` to `1` UV range onto this region.
+	pub fn to_affine(self) -> Affine2 {
+		let (min, max) = self.normalized();
+		Affine2::from_scale_angle_translation(max - min, 0., min)
+	}
+}
+
+/// Returns the UV offset the [`RANDOM_UV_OFFSET`](GenericMaterial::RANDOM_UV_OFFSET) property gives `entity`, from `0` to `1` on each axis.
+///
+/// Offsets follow the R2 low-discrepancy sequence over the entity's index, so entities spawned together are spread evenly apart, like blue noise, rather than clumping together.
+pub fn random_uv_offset(entity: Entity) -> Vec2 {
+	// The plastic number, the two-dimensional counterpart of the golden ratio.
+	const PLASTIC: f64 = 1.324_717_957_244_746;
+	let n = entity.index_u32() as f64;
+
+	Vec2::new((0.5 + n / PLASTIC).fract() as f32, (0.5 + n / (PLASTIC * PLASTIC)).fract() as f32)
+}
+
+/// Returns a copy of `material` with its `uv_transform` offset by [`random_uv_offset`] for `entity`,
+/// used by [`insert_generic_materials`](crate::insert_generic_materials) for materials with the [`RANDOM_UV_OFFSET`](GenericMaterial::RANDOM_UV_OFFSET) property.
+///
+/// The offset is in the material's UV space, so it's scaled along with any [`UV_REGION`](GenericMaterial::UV_REGION).
+pub fn instantiate_random_uv_offset(world: &mut World, material: &ErasedMaterialHandle, entity: Entity) -> Option<ErasedMaterialHandle> {
+	let Some(mut copy) = material.clone_material(world) else {
+		error!("Failed to copy {material:?} for a random UV offset, is it a registered generic material?");
+		return None;
+	};
+
+	let Some(uv_transform) = copy
+		.as_reflect_mut()
+		.reflect_path_mut("uv_transform")
+		.ok()
+		.and_then(|field| field.try_downcast_mut::<Affine2>())
+	else {
+		error!(
+			"Failed to offset the UVs of {}, it doesn't have a `uv_transform: Affine2` field",
+			copy.reflect_short_type_path()
+		);
+		return None;
+	};
+	uv_transform.translation += uv_transform.matrix2 * random_uv_offset(entity);
+
+	Some(copy.add_asset(world.resource::<AssetServer>()))
+}
+
+/// Sets the `uv_transform` of materials with the [`UV_REGION`](GenericMaterial::UV_REGION) property when they load or reload.
+pub fn apply_uv_region_property(
+	mut commands: Commands,
+	mut asset_events: MessageReader<AssetEvent<GenericMaterial>>,
+	generic_materials: Res<Assets<GenericMaterial>>,
+) {
+	for event in asset_events.read() {
+		let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = event else { continue };
+		let Some(generic_material) = generic_materials.get(*id) else { continue };
+		let Ok(uv_region) = generic_material.get_property(GenericMaterial::UV_REGION) else { continue };
+
+		let handle = generic_material.handle.clone();
+		let uv_transform = uv_region.to_affine();
+		commands.queue(move |world: &mut World| handle.modify_field(world, "uv_transform", uv_transform));
+	}
+}
@@ -7,5 +7,5 @@ use serde::Deserializer;
 /// This is for storing general non type specific data for deserializing on demand, such as in [`GenericMaterial`](crate::GenericMaterial) properties.
 ///
 /// NOTE: Because of the limitation of not being able to implement foreign traits for foreign types, this is automatically implemented for applicable types implementing the [`Deserializer`] trait.
-pub trait GenericValue: Deserializer<'static, Error: Send + Sync> + fmt::Debug + Send + Sync {}
+pub trait GenericValue: Deserializer<'static, Error: Send + Sync> + fmt::Debug + Clone + Send + Sync {}
 impl<T: Deserializer<'static, Error: Send + Sync> + fmt::Debug + Clone + Send + Sync + 'static> GenericValue for T {}
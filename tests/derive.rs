@@ -0,0 +1,35 @@
+#![cfg(all(feature = "derive", feature = "toml", feature = "bevy_pbr"))]
+
+use std::any::TypeId;
+
+use bevy::prelude::*;
+use bevy_materialize::load::{create_material_test_app, load_generic_material_blocking};
+use bevy_materialize::material_property::MaterialPropertyRegistry;
+use bevy_materialize::prelude::*;
+
+#[allow(dead_code)]
+#[derive(MaterialProperties)]
+struct SurfaceProperties {
+	collision: bool,
+	#[property(key = "sounds")]
+	footsteps: String,
+}
+
+#[test]
+fn derive_material_properties() {
+	assert_eq!(SurfaceProperties::COLLISION.key, "collision");
+	assert_eq!(SurfaceProperties::FOOTSTEPS.key, "sounds");
+
+	let mut app = create_material_test_app(TomlMaterialDeserializer);
+	app.register_material_properties::<SurfaceProperties>();
+
+	let registry = app.world().resource::<MaterialPropertyRegistry>().inner.read().unwrap().clone();
+	assert_eq!(registry.get("collision"), Some(&TypeId::of::<bool>()));
+	assert_eq!(registry.get("sounds"), Some(&TypeId::of::<String>()));
+
+	let handle = load_generic_material_blocking(&mut app, "materials/example.material.toml").unwrap();
+	let generic_material = app.world().resource::<Assets<GenericMaterial>>().get(&handle).unwrap();
+
+	assert!(*generic_material.collision().unwrap());
+	assert_eq!(generic_material.footsteps().unwrap(), "wood");
+}